@@ -0,0 +1,77 @@
+//! Compares answering PID polls with a fresh `System::new_all()` per query
+//! (what every process command used to do) against the shared process
+//! snapshot, one poll cycle per round.
+//!
+//! Run with `cargo bench --bench process_snapshot`. Under `cargo test` it
+//! only runs a couple of rounds to make sure it still works.
+
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use workspacelauncher_lib::test_helpers::{
+    are_processes_running, invalidate_process_snapshot, is_process_running,
+};
+
+const POLLED_PIDS: usize = 5;
+
+fn spawn_sleeper() -> Child {
+    #[cfg(target_os = "windows")]
+    let child = Command::new("cmd").args(["/C", "timeout /t 60 /nobreak"]).spawn();
+    #[cfg(not(target_os = "windows"))]
+    let child = Command::new("sleep").arg("60").spawn();
+    child.expect("failed to spawn sleeper")
+}
+
+fn measure(name: &str, rounds: u32, mut round: impl FnMut()) -> Duration {
+    round();
+    let started = Instant::now();
+    for _ in 0..rounds {
+        round();
+    }
+    let per_round = started.elapsed() / rounds;
+    println!("{:<40} {:>10.2?} per poll", name, per_round);
+    per_round
+}
+
+fn main() {
+    let quick = !std::env::args().any(|arg| arg == "--bench");
+    let rounds = if quick { 2 } else { 30 };
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+
+    let mut children: Vec<Child> = (0..POLLED_PIDS).map(|_| spawn_sleeper()).collect();
+    let pids: Vec<u32> = children.iter().map(Child::id).collect();
+
+    println!("Polling {} PIDs, {} rounds", pids.len(), rounds);
+    let baseline = measure("System::new_all per query", rounds, || {
+        for &pid in &pids {
+            let mut system = System::new_all();
+            system.refresh_processes(ProcessesToUpdate::All, true);
+            assert!(system.process(Pid::from_u32(pid)).is_some());
+        }
+    });
+    let shared = measure("shared snapshot, one query per PID", rounds, || {
+        invalidate_process_snapshot();
+        runtime.block_on(async {
+            for &pid in &pids {
+                assert!(is_process_running(pid).await.expect("is_process_running"));
+            }
+        });
+    });
+    let batch = measure("shared snapshot, are_processes_running", rounds, || {
+        invalidate_process_snapshot();
+        let running = runtime
+            .block_on(are_processes_running(pids.clone()))
+            .expect("are_processes_running");
+        assert!(running.values().all(|state| state.running));
+    });
+    println!(
+        "speedup: {:.1}x per PID, {:.1}x batched",
+        baseline.as_secs_f64() / shared.as_secs_f64(),
+        baseline.as_secs_f64() / batch.as_secs_f64()
+    );
+
+    for child in &mut children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
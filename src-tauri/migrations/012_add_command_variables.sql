@@ -0,0 +1,6 @@
+-- Allow variable values computed by running a command at launch time
+ALTER TABLE variables ADD COLUMN value_source TEXT NOT NULL DEFAULT 'static' CHECK (value_source IN ('static', 'command'));
+ALTER TABLE variables ADD COLUMN cache_ttl_secs INTEGER;
+
+ALTER TABLE global_variables ADD COLUMN value_source TEXT NOT NULL DEFAULT 'static' CHECK (value_source IN ('static', 'command'));
+ALTER TABLE global_variables ADD COLUMN cache_ttl_secs INTEGER;
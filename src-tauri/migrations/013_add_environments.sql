@@ -0,0 +1,33 @@
+-- Environments: named variable profiles per workspace (dev, staging, ...)
+CREATE TABLE IF NOT EXISTS environments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces (id) ON DELETE CASCADE,
+    UNIQUE(workspace_id, name)
+);
+
+-- Variables of an environment, layered over the workspace variables
+CREATE TABLE IF NOT EXISTS environment_variables (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    environment_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    is_secure INTEGER NOT NULL DEFAULT 0 CHECK (is_secure IN (0, 1)),
+    enabled INTEGER NOT NULL DEFAULT 1 CHECK (enabled IN (0, 1)),
+    value_source TEXT NOT NULL DEFAULT 'static' CHECK (value_source IN ('static', 'command')),
+    cache_ttl_secs INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (environment_id) REFERENCES environments (id) ON DELETE CASCADE,
+    UNIQUE(environment_id, key)
+);
+
+-- Environment a run was launched with
+ALTER TABLE runs ADD COLUMN environment_id INTEGER REFERENCES environments (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_environments_workspace_id ON environments(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_environment_variables_environment_id ON environment_variables(environment_id);
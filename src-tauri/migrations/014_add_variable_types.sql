@@ -0,0 +1,11 @@
+-- Typed variables, validated when saved and before launch.
+-- type_options holds JSON, e.g. {"values": ["dev", "prod"]} for enum or
+-- {"pattern": "^v[0-9]+$"} for pattern.
+ALTER TABLE variables ADD COLUMN value_type TEXT NOT NULL DEFAULT 'string' CHECK (value_type IN ('string', 'path', 'directory', 'file', 'url', 'integer', 'port', 'enum', 'pattern'));
+ALTER TABLE variables ADD COLUMN type_options TEXT;
+
+ALTER TABLE environment_variables ADD COLUMN value_type TEXT NOT NULL DEFAULT 'string' CHECK (value_type IN ('string', 'path', 'directory', 'file', 'url', 'integer', 'port', 'enum', 'pattern'));
+ALTER TABLE environment_variables ADD COLUMN type_options TEXT;
+
+ALTER TABLE global_variables ADD COLUMN value_type TEXT NOT NULL DEFAULT 'string' CHECK (value_type IN ('string', 'path', 'directory', 'file', 'url', 'integer', 'port', 'enum', 'pattern'));
+ALTER TABLE global_variables ADD COLUMN type_options TEXT;
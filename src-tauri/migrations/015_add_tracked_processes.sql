@@ -0,0 +1,11 @@
+-- Processes tracked for stopping, kept across app restarts and updates
+CREATE TABLE IF NOT EXISTS tracked_processes (
+    pid INTEGER PRIMARY KEY,
+    start_time_secs INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    run_id INTEGER,
+    action_id INTEGER REFERENCES actions (id) ON DELETE SET NULL,
+    workspace_id INTEGER REFERENCES workspaces (id) ON DELETE SET NULL,
+    environment_id INTEGER REFERENCES environments (id) ON DELETE SET NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
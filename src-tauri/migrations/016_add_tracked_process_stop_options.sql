@@ -0,0 +1,5 @@
+-- Process group and stop options of a tracked process, so it is stopped the
+-- same way after a restart. stop_options holds the action config fields
+-- StopOptions reads, e.g. {"stop_signal": "INT", "stop_grace_period_ms": 3000}.
+ALTER TABLE tracked_processes ADD COLUMN pgid INTEGER;
+ALTER TABLE tracked_processes ADD COLUMN stop_options TEXT;
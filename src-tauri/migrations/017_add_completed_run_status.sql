@@ -0,0 +1,9 @@
+-- Runs of processes we did not spawn (detached, resolved or re-attached)
+-- end without an exit status. They are recorded as 'completed', with an
+-- unknown outcome, instead of as a success.
+DROP INDEX IF EXISTS idx_runs_status;
+ALTER TABLE runs ADD COLUMN status_new TEXT NOT NULL DEFAULT 'completed' CHECK (status_new IN ('success', 'failed', 'cancelled', 'completed'));
+UPDATE runs SET status_new = status;
+ALTER TABLE runs DROP COLUMN status;
+ALTER TABLE runs RENAME COLUMN status_new TO status;
+CREATE INDEX IF NOT EXISTS idx_runs_status ON runs(status);
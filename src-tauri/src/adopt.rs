@@ -0,0 +1,351 @@
+//! Attaching a process the launcher did not start (say, a server started in
+//! a terminal before the app opened) to an action. Once adopted it is tracked
+//! like a launched one: listed as running, stoppable and watched for its exit.
+
+use crate::database;
+use crate::process::{self, ProcessIdentity};
+use crate::tracked_processes::{self, ActionReattachedEvent, TrackedProcess};
+use serde::Deserialize;
+use std::collections::HashSet;
+use sysinfo::{Pid, Process, System};
+use tauri::Emitter;
+
+/// How many candidates an ambiguous match lists.
+const MAX_LISTED_CANDIDATES: usize = 5;
+
+/// Every criterion that is set must hold for a process to be adopted.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdoptProcessRequest {
+    pub action_id: i64,
+    pub pid: Option<u32>,
+    /// Process name, compared case-insensitively.
+    pub name: Option<String>,
+    pub cmdline_contains: Option<String>,
+    /// A TCP or UDP port the process listens on (Linux only).
+    pub port: Option<u16>,
+}
+
+impl AdoptProcessRequest {
+    fn has_criteria(&self) -> bool {
+        self.pid.is_some()
+            || self.name.as_deref().is_some_and(|name| !name.trim().is_empty())
+            || self
+                .cmdline_contains
+                .as_deref()
+                .is_some_and(|needle| !needle.is_empty())
+            || self.port.is_some()
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(pid) = self.pid {
+            parts.push(format!("PID {}", pid));
+        }
+        if let Some(name) = &self.name {
+            parts.push(format!("name \"{}\"", name.trim()));
+        }
+        if let Some(needle) = &self.cmdline_contains {
+            parts.push(format!("command line containing \"{}\"", needle));
+        }
+        if let Some(port) = self.port {
+            parts.push(format!("port {}", port));
+        }
+        parts.join(", ")
+    }
+}
+
+fn command_line(proc_info: &Process) -> String {
+    proc_info
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn matches(proc_info: &Process, req: &AdoptProcessRequest, port_owners: &HashSet<u32>) -> bool {
+    let pid = proc_info.pid().as_u32();
+    if req.pid.is_some_and(|wanted| wanted != pid) {
+        return false;
+    }
+    if let Some(name) = req.name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        if !proc_info.name().to_string_lossy().eq_ignore_ascii_case(name) {
+            return false;
+        }
+    }
+    if let Some(needle) = req.cmdline_contains.as_deref().filter(|n| !n.is_empty()) {
+        if !command_line(proc_info).contains(needle) {
+            return false;
+        }
+    }
+    req.port.is_none() || port_owners.contains(&pid)
+}
+
+/// The launcher, its ancestors and PID 1. Stopping an action that adopted
+/// one of them would take down the launcher or the whole session.
+fn protected_pids(system: &System) -> HashSet<u32> {
+    let own_pid = std::process::id();
+    let mut protected = HashSet::from([1, own_pid]);
+    let mut parent = system.process(Pid::from_u32(own_pid)).and_then(Process::parent);
+    while let Some(pid) = parent {
+        if !protected.insert(pid.as_u32()) {
+            break;
+        }
+        parent = system.process(pid).and_then(Process::parent);
+    }
+    protected
+}
+
+/// Finds the single live process `req` describes. Processes the launcher
+/// already tracks, the launcher itself, its ancestors and PID 1 are never
+/// candidates. When a parent and its children all match (a server and its
+/// workers) the parent is picked.
+pub async fn find_adoptable(req: &AdoptProcessRequest) -> Result<ProcessIdentity, String> {
+    if !req.has_criteria() {
+        return Err("Give a PID, name, command line or port to adopt".to_string());
+    }
+    if let Some(pid) = req.pid {
+        if process::is_pid_registered(pid) {
+            return Err(format!("Process {} is already tracked", pid));
+        }
+    }
+
+    let req = req.clone();
+    crate::process_snapshot::query_fresh_commands(move |system| select_adoptable(system, &req))
+        .await
+}
+
+fn select_adoptable(
+    system: &System,
+    req: &AdoptProcessRequest,
+) -> Result<ProcessIdentity, String> {
+    let port_owners: HashSet<u32> = match req.port {
+        Some(port) => {
+            let pids: Vec<u32> = system.processes().keys().map(|pid| pid.as_u32()).collect();
+            crate::ports::listening_sockets(&pids)
+                .into_iter()
+                .filter(|socket| socket.port == port)
+                .map(|socket| socket.pid)
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+
+    let protected = protected_pids(system);
+    if let Some(pid) = req.pid.filter(|pid| protected.contains(pid)) {
+        return Err(format!(
+            "Process {} runs the launcher or the system and cannot be adopted",
+            pid
+        ));
+    }
+    let candidates: Vec<&Process> = system
+        .processes()
+        .values()
+        .filter(|p| crate::process_snapshot::is_alive(p))
+        .filter(|p| {
+            let pid = p.pid().as_u32();
+            !protected.contains(&pid) && !process::is_pid_registered(pid)
+        })
+        .filter(|p| matches(p, req, &port_owners))
+        .collect();
+    let matched: HashSet<Pid> = candidates.iter().map(|p| p.pid()).collect();
+    let mut roots: Vec<&Process> = candidates
+        .into_iter()
+        .filter(|p| !p.parent().is_some_and(|parent| matched.contains(&parent)))
+        .collect();
+    roots.sort_by_key(|p| (p.start_time(), p.pid()));
+
+    match roots.as_slice() {
+        [] => Err(format!("No running process matches {}", req.describe())),
+        [proc_info] => Ok(ProcessIdentity {
+            pid: proc_info.pid().as_u32(),
+            start_time_secs: proc_info.start_time(),
+            name: proc_info.name().to_string_lossy().to_string(),
+        }),
+        several => {
+            let listed: Vec<String> = several
+                .iter()
+                .take(MAX_LISTED_CANDIDATES)
+                .map(|p| format!("{} ({})", p.pid(), p.name().to_string_lossy()))
+                .collect();
+            Err(format!(
+                "{} processes match {}: {}{}. Narrow the search or give a PID.",
+                several.len(),
+                req.describe(),
+                listed.join(", "),
+                if several.len() > MAX_LISTED_CANDIDATES { ", ..." } else { "" }
+            ))
+        }
+    }
+}
+
+/// Tracks `identity` as the running process of `action_id` under a new run.
+/// An action whose previous instance is still running is refused, since
+/// adopting would silently replace the instance the launcher stops.
+pub async fn adopt(
+    identity: &ProcessIdentity,
+    action_id: i64,
+    workspace_id: i64,
+) -> Result<TrackedProcess, String> {
+    if let Some(instance) = process::find_running_action_instance(action_id).await {
+        return Err(format!(
+            "Action {} is already running (PID {}); stop it before adopting another process",
+            action_id, instance.identity.pid
+        ));
+    }
+    let run_id = crate::run_context::new_run_id();
+    let tracked = tracked_processes::track(
+        identity.pid,
+        Some(run_id),
+        Some(action_id),
+        Some(workspace_id),
+    )
+    .ok_or_else(|| format!("Process {} exited before it could be adopted", identity.pid))?;
+    if !tracked.identity.is_same_process(identity) {
+        // The PID was reused between finding the process and tracking it.
+        process::forget_process(identity.pid);
+        return Err(format!(
+            "Process {} exited before it could be adopted",
+            identity.pid
+        ));
+    }
+    Ok(tracked)
+}
+
+/// Attaches a running process to an action, which then shows as running,
+/// can be stopped with `kill_process` and completes when the process exits.
+#[tauri::command]
+pub async fn adopt_process(
+    app: tauri::AppHandle,
+    req: AdoptProcessRequest,
+) -> Result<ActionReattachedEvent, String> {
+    let pool = database::connect_app_db(&app).await?;
+    let action = database::get_action(&pool, req.action_id).await;
+    pool.close().await;
+    let action = action?.ok_or_else(|| format!("Action {} not found", req.action_id))?;
+
+    let identity = find_adoptable(&req).await?;
+    let tracked = adopt(&identity, req.action_id, action.workspace_id).await?;
+    crate::exit_watcher::watch(app.clone(), tracked.clone());
+
+    let event = ActionReattachedEvent {
+        action_id: req.action_id,
+        action_name: action.name,
+        workspace_id: action.workspace_id,
+        run_id: tracked.run_id,
+        environment_id: tracked.environment_id,
+        process_id: tracked.identity.pid,
+        process_start_time_secs: tracked.identity.start_time_secs,
+        process_name: tracked.identity.name,
+    };
+    app.emit("action-reattached", &event)
+        .map_err(|e| format!("Failed to emit action-reattached event: {}", e))?;
+    Ok(event)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::time::{sleep, Duration};
+
+    fn spawn_sleep(secs: &str) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new("sleep");
+        command.arg(secs);
+        command
+    }
+
+    #[tokio::test]
+    async fn adopts_a_process_found_by_command_line() {
+        let mut child = spawn_sleep("51").spawn().expect("spawn sleep");
+        let pid = child.id().expect("pid");
+        sleep(Duration::from_millis(100)).await;
+
+        let req = AdoptProcessRequest {
+            action_id: -50,
+            name: Some("SLEEP".to_string()),
+            cmdline_contains: Some("sleep 51".to_string()),
+            ..Default::default()
+        };
+        let identity = find_adoptable(&req).await.expect("find");
+        assert_eq!(identity.pid, pid);
+
+        let tracked = adopt(&identity, -50, -50).await.expect("adopt");
+        assert_eq!(tracked.action_id, Some(-50));
+        assert!(process::is_pid_registered(pid));
+        let again = find_adoptable(&AdoptProcessRequest {
+            pid: Some(pid),
+            ..Default::default()
+        })
+        .await;
+        assert!(again.unwrap_err().contains("already tracked"));
+
+        let stopped = process::kill_process(pid).await.expect("kill");
+        assert!(stopped.success, "{}", stopped.message);
+        let _ = child.wait().await;
+    }
+
+    #[tokio::test]
+    async fn refuses_ambiguous_and_empty_searches() {
+        assert!(find_adoptable(&AdoptProcessRequest::default()).await.is_err());
+
+        let mut first = spawn_sleep("52").spawn().expect("spawn sleep");
+        let mut second = spawn_sleep("52").spawn().expect("spawn sleep");
+        sleep(Duration::from_millis(100)).await;
+
+        let err = find_adoptable(&AdoptProcessRequest {
+            cmdline_contains: Some("sleep 52".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+        assert!(err.contains("2 processes match"), "{}", err);
+
+        let missing = find_adoptable(&AdoptProcessRequest {
+            cmdline_contains: Some("no-such-command-line-50".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+        assert!(missing.starts_with("No running process"), "{}", missing);
+
+        let _ = first.kill().await;
+        let _ = second.kill().await;
+    }
+
+    #[tokio::test]
+    async fn refuses_the_launcher_its_ancestors_and_init() {
+        let parent = std::os::unix::process::parent_id();
+        for pid in [1, parent, std::process::id()] {
+            let err = find_adoptable(&AdoptProcessRequest {
+                pid: Some(pid),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+            assert!(err.contains("cannot be adopted"), "{}: {}", pid, err);
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_an_action_that_is_already_running() {
+        let mut first = spawn_sleep("53").spawn().expect("spawn sleep");
+        let mut second = spawn_sleep("54").spawn().expect("spawn sleep");
+        sleep(Duration::from_millis(100)).await;
+        let find = |needle: &str| AdoptProcessRequest {
+            cmdline_contains: Some(needle.to_string()),
+            ..Default::default()
+        };
+
+        let identity = find_adoptable(&find("sleep 53")).await.expect("find first");
+        adopt(&identity, -53, -53).await.expect("adopt first");
+        let other = find_adoptable(&find("sleep 54")).await.expect("find second");
+        let err = adopt(&other, -53, -53).await.unwrap_err();
+        assert!(err.contains("already running"), "{}", err);
+        assert!(!process::is_pid_registered(other.pid));
+
+        let stopped = process::kill_process(identity.pid).await.expect("kill");
+        assert!(stopped.success, "{}", stopped.message);
+        let _ = first.wait().await;
+        let _ = second.kill().await;
+    }
+}
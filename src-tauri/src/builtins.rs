@@ -0,0 +1,250 @@
+//! Built-in variables resolved at launch time, e.g. `${RUN_ID}`,
+//! `${DATE:%Y-%m-%d}` or `${GIT_BRANCH:/path/to/repo}`. User-defined variables
+//! with the same name take precedence; names neither define fall back to the
+//! process environment.
+
+use crate::template::{self, VariableLookup};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuiltinVariable {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+const BUILTIN_VARIABLES: &[BuiltinVariable] = &[
+    BuiltinVariable {
+        name: "WORKSPACE_ID",
+        description: "ID of the workspace the action belongs to",
+    },
+    BuiltinVariable {
+        name: "WORKSPACE_NAME",
+        description: "Name of the workspace the action belongs to",
+    },
+    BuiltinVariable {
+        name: "ACTION_NAME",
+        description: "Name of the action being launched",
+    },
+    BuiltinVariable {
+        name: "RUN_ID",
+        description: "ID of the current run",
+    },
+    BuiltinVariable {
+        name: "HOME",
+        description: "Home directory of the current user",
+    },
+    BuiltinVariable {
+        name: "TEMP",
+        description: "Temporary directory",
+    },
+    BuiltinVariable {
+        name: "OS",
+        description: "Operating system (windows, macos, linux)",
+    },
+    BuiltinVariable {
+        name: "ARCH",
+        description: "CPU architecture (x86_64, aarch64, ...)",
+    },
+    BuiltinVariable {
+        name: "DATE:%Y-%m-%d",
+        description: "Current local date/time in a chrono format",
+    },
+    BuiltinVariable {
+        name: "UUID",
+        description: "Random UUID, stable for the whole launch",
+    },
+    BuiltinVariable {
+        name: "GIT_BRANCH:<path>",
+        description: "Current git branch of the repository at <path> (defaults to the working directory)",
+    },
+];
+
+/// Launch details the built-ins are computed from.
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinContext {
+    pub workspace_id: i64,
+    pub workspace_name: Option<String>,
+    pub action_name: Option<String>,
+    pub run_id: Option<i64>,
+    pub working_directory: Option<String>,
+    pub uuid: String,
+}
+
+impl BuiltinContext {
+    pub fn new(workspace_id: i64) -> Self {
+        Self {
+            workspace_id,
+            uuid: uuid::Uuid::new_v4().to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn value(&self, name: &str) -> Option<String> {
+        if let Some(format) = name.strip_prefix("DATE:") {
+            return format_date(format);
+        }
+        if let Some(path) = name.strip_prefix("GIT_BRANCH:") {
+            return git_branch(Path::new(path.trim()));
+        }
+        match name {
+            "WORKSPACE_ID" => Some(self.workspace_id.to_string()),
+            "WORKSPACE_NAME" => self.workspace_name.clone(),
+            "ACTION_NAME" => self.action_name.clone(),
+            "RUN_ID" => self.run_id.map(|id| id.to_string()),
+            "HOME" => home_dir(),
+            "TEMP" => Some(std::env::temp_dir().to_string_lossy().to_string()),
+            "OS" => Some(std::env::consts::OS.to_string()),
+            "ARCH" => Some(std::env::consts::ARCH.to_string()),
+            "DATE" => format_date(DEFAULT_DATE_FORMAT),
+            "UUID" => Some(self.uuid.clone()),
+            "GIT_BRANCH" => git_branch(Path::new(self.working_directory.as_deref()?)),
+            _ => None,
+        }
+    }
+}
+
+/// Variable lookup used for launches: user variables, then built-ins, then
+/// the process environment.
+pub struct LaunchVariables<'a> {
+    pub variables: &'a HashMap<String, String>,
+    pub builtins: &'a BuiltinContext,
+}
+
+impl VariableLookup for LaunchVariables<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.variables
+            .lookup(name)
+            .or_else(|| self.builtins.value(name).map(|value| template::escape(&value)))
+            .or_else(|| template::process_env_value(name))
+    }
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
+
+fn format_date(format: &str) -> Option<String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+    Some(
+        chrono::Local::now()
+            .format_with_items(items.into_iter())
+            .to_string(),
+    )
+}
+
+/// Finds the `.git` directory for `start` (walking up), following the
+/// `gitdir:` indirection used by worktrees and submodules.
+fn find_git_dir(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        let candidate = dir.join(".git");
+        if candidate.is_dir() {
+            return Some(candidate);
+        }
+        if candidate.is_file() {
+            let contents = std::fs::read_to_string(&candidate).ok()?;
+            let target = contents.strip_prefix("gitdir:")?.trim();
+            return Some(dir.join(target));
+        }
+    }
+    None
+}
+
+/// Branch name from `HEAD`, or the short commit hash when detached.
+fn git_branch(path: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(find_git_dir(path)?.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref:") {
+        Some(reference) => Some(
+            reference
+                .trim()
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference.trim())
+                .to_string(),
+        ),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
+
+#[tauri::command]
+pub fn list_builtin_variables() -> Vec<BuiltinVariable> {
+    BUILTIN_VARIABLES.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> BuiltinContext {
+        BuiltinContext {
+            workspace_name: Some("Backend".to_string()),
+            action_name: Some("API server".to_string()),
+            run_id: Some(1700000000000),
+            ..BuiltinContext::new(4)
+        }
+    }
+
+    #[test]
+    fn renders_builtins_with_user_variables_taking_precedence() {
+        let builtins = context();
+        let mut variables = HashMap::new();
+        variables.insert("OS".to_string(), "custom".to_string());
+        let lookup = LaunchVariables {
+            variables: &variables,
+            builtins: &builtins,
+        };
+
+        let rendered = template::render(
+            "${WORKSPACE_ID}/${WORKSPACE_NAME}/${ACTION_NAME}/${RUN_ID}/${OS}/${ARCH}",
+            &lookup,
+            true,
+        )
+        .expect("render");
+        assert_eq!(
+            rendered,
+            format!("4/Backend/API server/1700000000000/custom/{}", std::env::consts::ARCH)
+        );
+
+        let uuid = template::render("${UUID}", &lookup, true).expect("uuid");
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(template::render("${UUID}", &lookup, true).unwrap(), uuid);
+        assert!(!template::render("${TEMP}", &lookup, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn formats_dates() {
+        let year = chrono::Local::now().format("%Y").to_string();
+        assert_eq!(context().value("DATE:%Y").as_deref(), Some(year.as_str()));
+        assert_eq!(context().value("DATE").map(|d| d.len()), Some(10));
+        assert!(context().value("DATE:%Q").is_none());
+    }
+
+    #[test]
+    fn reads_git_branch_from_head() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join(".git")).expect("git dir");
+        std::fs::create_dir_all(repo.join("src/nested")).expect("nested dir");
+        std::fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature/login\n").expect("HEAD");
+
+        let builtins = BuiltinContext {
+            working_directory: Some(repo.join("src/nested").to_string_lossy().to_string()),
+            ..context()
+        };
+        assert_eq!(builtins.value("GIT_BRANCH").as_deref(), Some("feature/login"));
+
+        std::fs::write(repo.join(".git/HEAD"), "0123456789abcdef\n").expect("HEAD");
+        let name = format!("GIT_BRANCH:{}", repo.display());
+        assert_eq!(builtins.value(&name).as_deref(), Some("0123456"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Variables whose value is the trimmed output of a command, e.g. the current
+//! git SHA or `pass show db/dev`. Only variables an action actually references
+//! are evaluated, at most once per launch (shared by every action a workspace
+//! launch starts) unless a cache TTL is set. Values are kept in memory only
+//! and never written back to the database.
+
+use crate::template::{self, VariableLookup};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the values of a frontend launch are kept for its later requests.
+const LAUNCH_LIFETIME: Duration = Duration::from_secs(600);
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandVariable {
+    pub command: String,
+    pub cache_ttl: Option<Duration>,
+}
+
+impl CommandVariable {
+    /// Builds the command definition for a `variables` / `global_variables`
+    /// row, or `None` for static values.
+    pub fn from_source(value_source: &str, value: &str, cache_ttl_secs: Option<i64>) -> Option<Self> {
+        (value_source == "command").then(|| Self {
+            command: value.to_string(),
+            cache_ttl: cache_ttl_secs
+                .filter(|secs| *secs > 0)
+                .map(|secs| Duration::from_secs(secs as u64)),
+        })
+    }
+}
+
+type CacheKey = (String, Option<String>);
+
+static CACHE: LazyLock<Mutex<HashMap<CacheKey, (Instant, String)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Command outputs of one launch, shared by the actions it starts so that a
+/// command runs once and every action sees the same value.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchCommandValues(Arc<Mutex<HashMap<CacheKey, String>>>);
+
+static LAUNCHES: LazyLock<Mutex<HashMap<String, (Instant, LaunchCommandValues)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl LaunchCommandValues {
+    /// The values shared by every request carrying `launch_id`. Launches
+    /// older than `LAUNCH_LIFETIME` are dropped.
+    pub fn for_launch(launch_id: &str) -> Self {
+        let Ok(mut launches) = LAUNCHES.lock() else {
+            return Self::default();
+        };
+        let now = Instant::now();
+        launches.retain(|_, (started_at, _)| now.duration_since(*started_at) < LAUNCH_LIFETIME);
+        launches
+            .entry(launch_id.to_string())
+            .or_insert_with(|| (now, Self::default()))
+            .1
+            .clone()
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<String> {
+        self.0.lock().ok()?.get(key).cloned()
+    }
+
+    fn insert(&self, key: CacheKey, value: String) {
+        if let Ok(mut values) = self.0.lock() {
+            values.insert(key, value);
+        }
+    }
+}
+
+/// The command and the directory it runs in, if that exists.
+fn cache_key(variable: &CommandVariable, working_directory: Option<&str>) -> CacheKey {
+    let working_directory = working_directory
+        .filter(|dir| std::path::Path::new(dir).is_dir())
+        .map(|dir| dir.to_string());
+    (variable.command.clone(), working_directory)
+}
+
+fn cached(key: &CacheKey) -> Option<String> {
+    let cache = CACHE.lock().ok()?;
+    let (expires_at, value) = cache.get(key)?;
+    (Instant::now() < *expires_at).then(|| value.clone())
+}
+
+/// Records which variables a render asks for, without running commands.
+struct RecordingLookup<'a> {
+    inner: &'a dyn VariableLookup,
+    commands: &'a HashMap<String, CommandVariable>,
+    used: Mutex<HashSet<String>>,
+}
+
+impl VariableLookup for RecordingLookup<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        if let Ok(mut used) = self.used.lock() {
+            used.insert(name.to_string());
+        }
+        if self.commands.contains_key(name) {
+            return Some(String::new());
+        }
+        self.inner.lookup(name)
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(map) => map.values().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+/// Names referenced, directly or through other variables, by any string in
+/// `config` or in `extra` templates. Command variables count as referenced
+/// but their commands are not run.
+pub fn referenced_names(
+    config: &Value,
+    extra: &[&str],
+    variables: &dyn VariableLookup,
+    commands: &HashMap<String, CommandVariable>,
+) -> HashSet<String> {
+    let recorder = RecordingLookup {
+        inner: variables,
+        commands,
+        used: Mutex::new(HashSet::new()),
+    };
+    let mut templates = Vec::new();
+    collect_strings(config, &mut templates);
+    templates.extend_from_slice(extra);
+    for input in templates {
+        let _ = template::render(input, &recorder, false);
+    }
+    recorder.used.into_inner().unwrap_or_default()
+}
+
+/// Names of the command variables among `referenced_names`.
+pub fn referenced_commands(
+    referenced: &HashSet<String>,
+    commands: &HashMap<String, CommandVariable>,
+) -> HashSet<String> {
+    referenced
+        .iter()
+        .filter(|name| commands.contains_key(*name))
+        .cloned()
+        .collect()
+}
+
+/// Runs the command through the platform shell and returns its trimmed
+/// stdout, or an error carrying its stderr.
+pub async fn evaluate(
+    name: &str,
+    variable: &CommandVariable,
+    working_directory: Option<&str>,
+) -> Result<String, String> {
+    let key = cache_key(variable, working_directory);
+    if variable.cache_ttl.is_some() {
+        if let Some(value) = cached(&key) {
+            return Ok(value);
+        }
+    }
+
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", &variable.command]);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", &variable.command]);
+        cmd
+    };
+    if let Some(dir) = &key.1 {
+        cmd.current_dir(dir);
+    }
+    cmd.stdin(std::process::Stdio::null()).kill_on_drop(true);
+
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| {
+            format!(
+                "Command for variable {} timed out after {}s",
+                name,
+                COMMAND_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| format!("Failed to run command for variable {}: {}", name, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = if stderr.trim().is_empty() {
+            output.status.to_string()
+        } else {
+            stderr.trim().to_string()
+        };
+        return Err(format!("Command for variable {} failed: {}", name, detail));
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if let Some(ttl) = variable.cache_ttl {
+        if let Ok(mut cache) = CACHE.lock() {
+            cache.insert(key, (Instant::now() + ttl, value.clone()));
+        }
+    }
+    Ok(value)
+}
+
+/// Evaluates `names` and replaces their command text in `variables` with the
+/// (escaped) output. Commands already run for this `launch` are not run again.
+pub async fn evaluate_into(
+    names: &HashSet<String>,
+    commands: &HashMap<String, CommandVariable>,
+    working_directory: Option<&str>,
+    launch: &LaunchCommandValues,
+    variables: &mut HashMap<String, String>,
+) -> Result<(), String> {
+    let mut names: Vec<&String> = names.iter().collect();
+    names.sort();
+    for name in names {
+        let Some(variable) = commands.get(name) else {
+            continue;
+        };
+        let key = cache_key(variable, working_directory);
+        let value = match launch.get(&key) {
+            Some(value) => value,
+            None => {
+                let value = evaluate(name, variable, working_directory).await?;
+                launch.insert(key, value.clone());
+                value
+            }
+        };
+        variables.insert(name.clone(), template::escape(&value));
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn command(command: &str, ttl: Option<u64>) -> CommandVariable {
+        CommandVariable {
+            command: command.to_string(),
+            cache_ttl: ttl.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn finds_only_referenced_commands() {
+        let mut commands = HashMap::new();
+        commands.insert("SHA".to_string(), command("git rev-parse HEAD", None));
+        commands.insert("SECRET".to_string(), command("pass show db/dev", None));
+        let mut variables = HashMap::new();
+        variables.insert("TAG".to_string(), "app:${SHA}".to_string());
+
+        let config = serde_json::json!({
+            "command": "docker",
+            "args": ["run", "${TAG}"],
+        });
+        let referenced = referenced_names(&config, &[], &variables, &commands);
+        assert_eq!(
+            referenced,
+            HashSet::from(["TAG".to_string(), "SHA".to_string()])
+        );
+        let used = referenced_commands(&referenced, &commands);
+        assert_eq!(used, HashSet::from(["SHA".to_string()]));
+
+        let referenced = referenced_names(&config, &["--password ${SECRET}"], &variables, &commands);
+        assert_eq!(referenced_commands(&referenced, &commands).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn evaluates_trimmed_output_and_reports_stderr() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let value = evaluate(
+            "CWD_NAME",
+            &command("printf '  %s\\n\\n' \"$(basename \"$PWD\")\"", None),
+            Some(dir.path().to_str().unwrap()),
+        )
+        .await
+        .expect("evaluate");
+        assert_eq!(value, dir.path().file_name().unwrap().to_string_lossy());
+
+        let err = evaluate("BROKEN", &command("echo 'no such entry' >&2; exit 3", None), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Command for variable BROKEN failed: no such entry");
+    }
+
+    #[tokio::test]
+    async fn caches_with_ttl_and_runs_once_per_launch_otherwise() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let counter = dir.path().join("count");
+        let script = format!("echo x >> '{}'; wc -l < '{}'", counter.display(), counter.display());
+
+        let cached_var = command(&script, Some(60));
+        let first = evaluate("N", &cached_var, None).await.expect("first");
+        let second = evaluate("N", &cached_var, None).await.expect("second");
+        assert_eq!(first.trim(), "1");
+        assert_eq!(second, first);
+
+        let uncached = command(&script, None);
+        let third = evaluate("N", &uncached, None).await.expect("third");
+        assert_eq!(third.trim(), "2");
+
+        // The requests of one launch share a single evaluation.
+        let commands = HashMap::from([("N".to_string(), uncached)]);
+        let names = HashSet::from(["N".to_string()]);
+        let mut values = Vec::new();
+        for _ in 0..2 {
+            let launch = LaunchCommandValues::for_launch("shared-launch");
+            let mut variables = HashMap::new();
+            evaluate_into(&names, &commands, None, &launch, &mut variables)
+                .await
+                .expect("evaluate");
+            values.push(variables["N"].clone());
+        }
+        assert_eq!(values, ["3", "3"]);
+        let mut variables = HashMap::new();
+        let next_launch = LaunchCommandValues::for_launch("next-launch");
+        evaluate_into(&names, &commands, None, &next_launch, &mut variables)
+            .await
+            .expect("next launch");
+        assert_eq!(variables["N"], "4");
+    }
+}
@@ -66,6 +66,14 @@ pub struct Variable {
     pub enabled: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// `static`, or `command` when `value` is a command whose trimmed output
+    /// is used at launch time.
+    pub value_source: String,
+    pub cache_ttl_secs: Option<i64>,
+    /// See [`crate::variable_types::VariableType`].
+    pub value_type: String,
+    /// JSON options for `enum` and `pattern` types.
+    pub type_options: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -78,6 +86,34 @@ pub struct NewVariable {
     pub enabled: bool,
 }
 
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Environment {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EnvironmentVariable {
+    pub id: i64,
+    pub environment_id: i64,
+    pub key: String,
+    pub value: String,
+    pub is_secure: bool,
+    pub enabled: bool,
+    pub value_source: String,
+    pub cache_ttl_secs: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub value_type: String,
+    pub type_options: Option<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Run {
@@ -90,6 +126,7 @@ pub struct Run {
     pub exit_code: Option<i32>,
     pub error_message: Option<String>,
     pub created_at: String,
+    pub environment_id: Option<i64>,
 }
 
 #[allow(dead_code)]
@@ -177,6 +214,14 @@ pub struct GlobalVariable {
     pub enabled: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// `static`, or `command` when `value` is a command whose trimmed output
+    /// is used at launch time.
+    pub value_source: String,
+    pub cache_ttl_secs: Option<i64>,
+    /// See [`crate::variable_types::VariableType`].
+    pub value_type: String,
+    /// JSON options for `enum` and `pattern` types.
+    pub type_options: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -197,8 +242,10 @@ use sqlx::migrate::{
 };
 use sqlx::sqlite::SqlitePoolOptions;
 use sqlx::Sqlite;
+use sqlx::SqlitePool;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 #[derive(Debug)]
@@ -313,6 +360,132 @@ pub fn is_migration_checksum_error(err: &str) -> bool {
     err.contains("migration") && err.contains("has been modified")
 }
 
+pub fn app_db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_local_data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| e.to_string())?;
+    Ok(app_local_data_dir.join("workspacelauncher.db"))
+}
+
+pub async fn connect(db_path: &Path) -> Result<SqlitePool, String> {
+    let conn_str = format!("sqlite://{}", db_path.to_string_lossy());
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&conn_str)
+        .await
+        .map_err(|e| format!("Failed to connect to database: {}", e))
+}
+
+pub async fn connect_app_db(app: &tauri::AppHandle) -> Result<SqlitePool, String> {
+    connect(&app_db_path(app)?).await
+}
+
+static APP_POOL: tokio::sync::OnceCell<SqlitePool> = tokio::sync::OnceCell::const_new();
+
+/// Returns the pool shared by launches, connecting on first use. Unlike a
+/// pool from `connect_app_db`, it must not be closed by the caller.
+pub async fn app_pool(app: &tauri::AppHandle) -> Result<SqlitePool, String> {
+    APP_POOL
+        .get_or_try_init(|| connect_app_db(app))
+        .await
+        .cloned()
+}
+
+pub async fn get_workspace(pool: &SqlitePool, workspace_id: i64) -> Result<Option<Workspace>, String> {
+    sqlx::query_as::<_, Workspace>("SELECT * FROM workspaces WHERE id = ?")
+        .bind(workspace_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load workspace {}: {}", workspace_id, e))
+}
+
+pub async fn get_tool(pool: &SqlitePool, tool_id: i64) -> Result<Option<Tool>, String> {
+    sqlx::query_as::<_, Tool>("SELECT * FROM tools WHERE id = ?")
+        .bind(tool_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load tool {}: {}", tool_id, e))
+}
+
+pub async fn get_workspace_actions(
+    pool: &SqlitePool,
+    workspace_id: i64,
+) -> Result<Vec<Action>, String> {
+    sqlx::query_as::<_, Action>(
+        "SELECT * FROM actions WHERE workspace_id = ? ORDER BY order_index ASC, id ASC",
+    )
+    .bind(workspace_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load actions for workspace {}: {}", workspace_id, e))
+}
+
+pub async fn get_workspace_variables(
+    pool: &SqlitePool,
+    workspace_id: i64,
+) -> Result<Vec<Variable>, String> {
+    sqlx::query_as::<_, Variable>("SELECT * FROM variables WHERE workspace_id = ? ORDER BY key")
+        .bind(workspace_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load variables for workspace {}: {}", workspace_id, e))
+}
+
+pub async fn get_action(pool: &SqlitePool, action_id: i64) -> Result<Option<Action>, String> {
+    sqlx::query_as::<_, Action>("SELECT * FROM actions WHERE id = ?")
+        .bind(action_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load action {}: {}", action_id, e))
+}
+
+pub async fn get_global_variables(pool: &SqlitePool) -> Result<Vec<GlobalVariable>, String> {
+    sqlx::query_as::<_, GlobalVariable>("SELECT * FROM global_variables ORDER BY key")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load global variables: {}", e))
+}
+
+pub async fn get_environment(
+    pool: &SqlitePool,
+    environment_id: i64,
+) -> Result<Option<Environment>, String> {
+    sqlx::query_as::<_, Environment>("SELECT * FROM environments WHERE id = ?")
+        .bind(environment_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load environment {}: {}", environment_id, e))
+}
+
+pub async fn find_environment_by_name(
+    pool: &SqlitePool,
+    workspace_id: i64,
+    name: &str,
+) -> Result<Option<Environment>, String> {
+    sqlx::query_as::<_, Environment>(
+        "SELECT * FROM environments WHERE workspace_id = ? AND name = ?",
+    )
+    .bind(workspace_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load environment {}: {}", name, e))
+}
+
+pub async fn get_environment_variables(
+    pool: &SqlitePool,
+    environment_id: i64,
+) -> Result<Vec<EnvironmentVariable>, String> {
+    sqlx::query_as::<_, EnvironmentVariable>(
+        "SELECT * FROM environment_variables WHERE environment_id = ? ORDER BY key",
+    )
+    .bind(environment_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load variables for environment {}: {}", environment_id, e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,4 +557,64 @@ mod tests {
 
         assert_eq!(count, 1);
     }
+
+    #[tokio::test]
+    async fn loads_workspace_actions_in_order() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        run_migrations(&db_path).await.expect("migrate");
+
+        let pool = connect(&db_path).await;
+        sqlx::query("INSERT INTO workspaces (id, name) VALUES (7, 'backend stack')")
+            .execute(&pool)
+            .await
+            .expect("insert workspace");
+        sqlx::query(
+            "INSERT INTO actions (workspace_id, name, action_type, config, order_index) VALUES \
+             (7, 'second', 'delay', '{\"duration_ms\":1}', 2), \
+             (7, 'first', 'url', '{\"url\":\"http://localhost\"}', 1)",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert actions");
+        sqlx::query(
+            "INSERT INTO variables (workspace_id, key, value, enabled) VALUES (7, 'PORT', '8080', 1)",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert variable");
+
+        let workspace = get_workspace(&pool, 7).await.expect("load workspace");
+        assert_eq!(workspace.map(|w| w.name).as_deref(), Some("backend stack"));
+        assert!(get_workspace(&pool, 8).await.expect("load missing").is_none());
+
+        let actions = get_workspace_actions(&pool, 7).await.expect("load actions");
+        let names: Vec<&str> = actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        let variables = get_workspace_variables(&pool, 7).await.expect("load variables");
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables[0].value, "8080");
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn loads_seeded_tool() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        run_migrations(&db_path).await.expect("migrate");
+
+        let pool = connect(&db_path).await;
+        let (id,): (i64,) = sqlx::query_as("SELECT id FROM tools WHERE name = 'Docker Compose'")
+            .fetch_one(&pool)
+            .await
+            .expect("find seeded tool");
+        let tool = get_tool(&pool, id)
+            .await
+            .expect("load tool")
+            .expect("tool exists");
+        assert_eq!(tool.template, "docker-compose -f \"{{compose_file}}\" {{command}}");
+        assert!(get_tool(&pool, -1).await.expect("load missing").is_none());
+        pool.close().await;
+    }
 }
@@ -0,0 +1,115 @@
+//! Environments are named variable profiles of a workspace (dev, staging,
+//! local-docker, ...). Launching with an environment layers its variables
+//! over the workspace variables, so the same actions can target different
+//! backends.
+
+use crate::database::{self, Environment};
+use sqlx::SqlitePool;
+
+/// Copies `environment_id` and all of its variables into a new environment of
+/// the same workspace named `name`.
+pub async fn clone(
+    pool: &SqlitePool,
+    environment_id: i64,
+    name: &str,
+) -> Result<Environment, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Environment name must not be empty".to_string());
+    }
+    let source = database::get_environment(pool, environment_id)
+        .await?
+        .ok_or_else(|| format!("Environment {} not found", environment_id))?;
+    if database::find_environment_by_name(pool, source.workspace_id, name)
+        .await?
+        .is_some()
+    {
+        return Err(format!("Environment {} already exists", name));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let id = sqlx::query(
+        "INSERT INTO environments (workspace_id, name, description) VALUES (?, ?, ?)",
+    )
+    .bind(source.workspace_id)
+    .bind(name)
+    .bind(&source.description)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create environment {}: {}", name, e))?
+    .last_insert_rowid();
+    sqlx::query(
+        "INSERT INTO environment_variables \
+         (environment_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, \
+          value_type, type_options) \
+         SELECT ?, key, value, is_secure, enabled, value_source, cache_ttl_secs, \
+          value_type, type_options \
+         FROM environment_variables WHERE environment_id = ?",
+    )
+    .bind(id)
+    .bind(environment_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to copy variables of {}: {}", source.name, e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    database::get_environment(pool, id)
+        .await?
+        .ok_or_else(|| format!("Environment {} not found", id))
+}
+
+#[tauri::command]
+pub async fn clone_environment(
+    app: tauri::AppHandle,
+    environment_id: i64,
+    name: String,
+) -> Result<Environment, String> {
+    let pool = database::connect_app_db(&app).await?;
+    let result = clone(&pool, environment_id, &name).await;
+    pool.close().await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn clones_environment_with_variables() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        database::run_migrations(&db_path).await.expect("migrate");
+        let pool = database::connect(&db_path).await.expect("connect");
+        sqlx::query(
+            "INSERT INTO workspaces (id, name) VALUES (1, 'api'); \
+             INSERT INTO environments (id, workspace_id, name, description) \
+             VALUES (5, 1, 'staging', 'Staging cluster'); \
+             INSERT INTO environment_variables (environment_id, key, value, is_secure, value_type) \
+             VALUES (5, 'API_URL', 'https://staging.example.com', 0, 'url'), \
+             (5, 'TOKEN', 'enc:v1:k:abc', 1, 'string')",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert environment");
+
+        let cloned = clone(&pool, 5, " staging-eu ").await.expect("clone");
+        assert_eq!(cloned.name, "staging-eu");
+        assert_eq!(cloned.workspace_id, 1);
+        assert_eq!(cloned.description.as_deref(), Some("Staging cluster"));
+        let variables = database::get_environment_variables(&pool, cloned.id)
+            .await
+            .expect("variables");
+        assert_eq!(variables.len(), 2);
+        assert!(variables.iter().any(|v| v.key == "TOKEN" && v.is_secure));
+        assert!(variables.iter().any(|v| v.key == "API_URL" && v.value_type == "url"));
+
+        let err = clone(&pool, 5, "staging-eu").await.unwrap_err();
+        assert_eq!(err, "Environment staging-eu already exists");
+        pool.close().await;
+    }
+}
@@ -0,0 +1,179 @@
+//! Reports the exit of tracked processes that are not our direct children
+//! (detached servers, resolved descendants, re-attached processes). Each one
+//! gets a task that waits on a pidfd where the kernel supports it and polls
+//! the process table otherwise, then emits `action-completed` right away so
+//! the UI no longer has to poll.
+
+use crate::process::{self, ProcessIdentity};
+use crate::tracked_processes::TrackedProcess;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+static NEXT_WATCH: AtomicU64 = AtomicU64::new(1);
+
+/// Watched PIDs and the watch that owns them. A watch whose entry is gone was
+/// cancelled, e.g. because we are stopping the process ourselves.
+static WATCHED: LazyLock<Mutex<HashMap<u32, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether the process is still the one we recorded. A PID that now belongs
+/// to a process with a different start time has been reused. The name is not
+/// compared because it changes when the process execs.
+pub async fn still_running(identity: &ProcessIdentity) -> bool {
+    process::query_identity(identity.pid)
+        .await
+        .is_some_and(|current| current.is_same_process(identity))
+}
+
+#[cfg(target_os = "linux")]
+fn open_pidfd(pid: u32) -> Option<std::os::fd::OwnedFd> {
+    use nix::libc;
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: pidfd_open takes a PID and flags and returns a new descriptor
+    // that we take ownership of, or -1.
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        return None;
+    }
+    // SAFETY: `fd` was just returned by pidfd_open and is owned by no one else.
+    Some(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd as i32) })
+}
+
+/// Waits until the process exits. On Linux the pidfd becomes readable when it
+/// does; the identity is checked after opening it so a reused PID is never
+/// waited on.
+async fn wait_for_exit(identity: &ProcessIdentity) {
+    #[cfg(target_os = "linux")]
+    if let Some(fd) = open_pidfd(identity.pid) {
+        if !still_running(identity).await {
+            return;
+        }
+        if let Ok(fd) = tokio::io::unix::AsyncFd::new(fd) {
+            let _ = fd.readable().await;
+            return;
+        }
+    }
+
+    while still_running(identity).await {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn finish(pid: u32, watch: u64) -> bool {
+    let Ok(mut watched) = WATCHED.lock() else {
+        return false;
+    };
+    if watched.get(&pid) != Some(&watch) {
+        return false;
+    }
+    watched.remove(&pid);
+    true
+}
+
+/// Stops reporting the exit of `pid`. Called before we stop a process
+/// ourselves, so the stop is not also reported as a completion. Returns the
+/// watch so it can be resumed if the stop fails.
+pub fn unwatch(pid: u32) -> Option<u64> {
+    WATCHED.lock().ok()?.remove(&pid)
+}
+
+/// Resumes a watch cancelled by [`unwatch`].
+pub fn rewatch(pid: u32, watch: Option<u64>) {
+    if let (Some(watch), Ok(mut watched)) = (watch, WATCHED.lock()) {
+        watched.entry(pid).or_insert(watch);
+    }
+}
+
+/// Watches `tracked` and emits `action-completed` when it exits, and
+/// `action-ports-changed` when it starts listening on new ports.
+pub fn watch(app: AppHandle, tracked: TrackedProcess) {
+    let pid = tracked.identity.pid;
+    let watch = NEXT_WATCH.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut watched) = WATCHED.lock() {
+        watched.insert(pid, watch);
+    }
+
+    crate::ports::watch_ports(app.clone(), tracked.clone());
+    tauri::async_runtime::spawn(async move {
+        wait_for_exit(&tracked.identity).await;
+        if !finish(pid, watch) {
+            return;
+        }
+        process::forget_process(pid);
+        let completed_at = chrono::Utc::now().to_rfc3339();
+        println!("Tracked process {} exited at {}", pid, completed_at);
+
+        let (Some(action_id), Some(workspace_id)) = (tracked.action_id, tracked.workspace_id)
+        else {
+            return;
+        };
+        // The exit status of a process we did not spawn is not available, so
+        // whether it succeeded is unknown.
+        let _ = app.emit(
+            "action-completed",
+            serde_json::json!({
+                "action_id": action_id,
+                "environment_id": tracked.environment_id,
+                "workspace_id": workspace_id,
+                "run_id": tracked.run_id,
+                "exit_code": null,
+                "success": null,
+                "process_id": pid,
+                "completed_at": completed_at,
+            }),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_exit_and_ignores_reused_pids() {
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id().expect("pid");
+        let identity = process::current_identity(pid).expect("identity");
+        assert!(still_running(&identity).await);
+
+        let reused = ProcessIdentity {
+            start_time_secs: identity.start_time_secs + 1,
+            ..identity.clone()
+        };
+        tokio::time::timeout(Duration::from_millis(500), wait_for_exit(&reused))
+            .await
+            .expect("a different process under the same PID counts as exited");
+
+        let watcher = tokio::spawn(async move { wait_for_exit(&identity).await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!watcher.is_finished(), "the live process is still running");
+        child.kill().await.expect("kill");
+        tokio::time::timeout(Duration::from_secs(5), watcher)
+            .await
+            .expect("exit should be detected")
+            .expect("join");
+    }
+
+    #[test]
+    fn unwatched_processes_are_not_reported() {
+        let pid = u32::MAX - 3;
+        WATCHED.lock().unwrap().insert(pid, 7);
+        let watch = unwatch(pid);
+        assert!(!finish(pid, 7));
+        rewatch(pid, watch);
+        assert!(finish(pid, 7), "a failed stop resumes the watch");
+
+        WATCHED.lock().unwrap().insert(pid, 8);
+        assert!(!finish(pid, 7), "a newer watch owns the PID");
+        assert!(finish(pid, 8));
+    }
+}
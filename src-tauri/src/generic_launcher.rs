@@ -1,11 +1,12 @@
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_shell::ShellExt;
 
 use crate::launcher_core::{
     spawn_attached_with_logs, spawn_detached, AttachedSpawnRequest, DetachedSpawnRequest,
+    ResourceLimits,
 };
+use crate::pid_matcher::PidMatcher;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnRequest {
@@ -16,6 +17,10 @@ pub struct SpawnRequest {
     pub detached: Option<bool>,
     pub action_id: Option<i64>,
     pub workspace_id: Option<i64>,
+    #[serde(default)]
+    pub resource_limits: Option<ResourceLimits>,
+    #[serde(default)]
+    pub pid_matcher: Option<PidMatcher>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -50,17 +55,23 @@ fn emit_log(
             workspace_id,
             run_id,
             level: level.to_string(),
-            message: message.to_string(),
+            message: crate::redact::redact_run(run_id, message),
         },
     );
 }
 
 #[tauri::command]
 pub async fn spawn_process(app: AppHandle, request: SpawnRequest) -> Result<SpawnResult, String> {
-    let run_id = Utc::now().timestamp_millis();
+    let run_id = crate::run_context::new_run_id();
     let args = request.args.unwrap_or_default();
     let keep_open = request.keep_terminal_open.unwrap_or(false);
     let detached = request.detached.unwrap_or(false);
+    if let Some(limits) = &request.resource_limits {
+        limits.validate()?;
+    }
+    if let Some(matcher) = &request.pid_matcher {
+        matcher.compile()?;
+    }
 
     emit_log(
         &app,
@@ -99,6 +110,8 @@ pub async fn spawn_process(app: AppHandle, request: SpawnRequest) -> Result<Spaw
                 args: args.clone(),
                 working_directory: request.working_directory.clone(),
                 track_process,
+                resource_limits: request.resource_limits,
+                pid_matcher: request.pid_matcher.clone(),
             },
         )
         .await?
@@ -113,6 +126,8 @@ pub async fn spawn_process(app: AppHandle, request: SpawnRequest) -> Result<Spaw
                 args: args.clone(),
                 working_directory: request.working_directory.clone(),
                 track_process: false,
+                resource_limits: request.resource_limits,
+                pid_matcher: request.pid_matcher.clone(),
             },
         )
         .await?;
@@ -1,8 +1,13 @@
-use crate::launcher_utils::replace_variables;
-use chrono::Utc;
+use crate::builtins::{BuiltinContext, LaunchVariables};
+use crate::command_variables::LaunchCommandValues;
+use crate::template::{self, VariableLookup};
+use crate::process::StopOptions;
+use crate::tool_template;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_shell::ShellExt;
@@ -15,12 +20,34 @@ pub struct LaunchActionRequest {
     pub action_type: String,
     pub config: Value,
     pub variables: HashMap<String, String>,
+    /// Environment whose variables are layered over the workspace variables.
+    #[serde(default)]
+    pub environment_id: Option<i64>,
+    /// Set by the frontend on every request of one launch, so they share
+    /// `command_values`.
+    #[serde(default)]
+    pub launch_id: Option<String>,
+    #[serde(skip)]
+    pub builtins: BuiltinContext,
+    /// Values of the secure variables, masked in the run's logs.
+    #[serde(skip)]
+    pub secrets: Vec<String>,
+    /// Names in `variables` that hold secure values, so nested workspaces
+    /// keep treating them as secrets.
+    #[serde(skip)]
+    pub secure_variables: HashSet<String>,
+    /// Command variable outputs shared with the other actions of the launch.
+    #[serde(skip)]
+    pub command_values: LaunchCommandValues,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LaunchWorkspaceRequest {
     pub workspace_id: i64,
     pub actions: Vec<LaunchActionRequest>,
+    /// Applied to every action that does not name its own environment.
+    #[serde(default)]
+    pub environment_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -29,11 +56,40 @@ pub struct LaunchResult {
     pub message: String,
     pub process_id: Option<u32>,
     pub run_id: Option<i64>,
+    pub instance_decision: Option<InstanceDecision>,
+}
+
+/// What to do when an action is launched while its previous instance is
+/// still running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstancePolicy {
+    #[default]
+    AllowMultiple,
+    SkipIfRunning,
+    RestartIfRunning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceDecision {
+    Started,
+    SkippedRunning,
+    Restarted,
+}
+
+fn instance_policy_from_config(config: &Value) -> Result<InstancePolicy, String> {
+    match config.get("instance_policy") {
+        None | Some(Value::Null) => Ok(InstancePolicy::default()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|_| format!("Invalid instance_policy: {}", value)),
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ActionStartedEvent {
     pub action_id: i64,
+    pub environment_id: Option<i64>,
     pub workspace_id: i64,
     pub run_id: i64,
     pub process_id: Option<u32>,
@@ -42,6 +98,7 @@ pub struct ActionStartedEvent {
 #[derive(Debug, Clone, Serialize)]
 pub struct ActionCompletedEvent {
     pub action_id: i64,
+    pub environment_id: Option<i64>,
     pub workspace_id: i64,
     pub run_id: i64,
     pub exit_code: Option<i32>,
@@ -60,7 +117,33 @@ pub struct ActionLogEvent {
 use crate::executable::build_executable_candidates;
 use crate::launcher_core::{
     spawn_attached_with_logs, spawn_detached, AttachedSpawnRequest, DetachedSpawnRequest,
+    ResourceLimits,
 };
+use crate::pid_matcher::PidMatcher;
+
+fn strict_variables(config: &Value) -> bool {
+    config
+        .get("strict_variables")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+fn launch_variables(request: &LaunchActionRequest) -> LaunchVariables<'_> {
+    LaunchVariables {
+        variables: &request.variables,
+        builtins: &request.builtins,
+    }
+}
+
+/// Renders `${...}` references in a config value with the request's variables.
+/// With `strict_variables` set, unknown names fail the launch.
+fn substitute(request: &LaunchActionRequest, input: &str) -> Result<String, String> {
+    template::render(
+        input,
+        &launch_variables(request),
+        strict_variables(&request.config),
+    )
+}
 
 fn extra_paths_from_config(config: &Value) -> Option<Vec<String>> {
     config
@@ -131,7 +214,11 @@ async fn spawn_with_candidates(
         .config
         .get("working_directory")
         .and_then(|v| v.as_str())
-        .map(|s| replace_variables(s, &request.variables));
+        .map(|s| substitute(request, s))
+        .transpose()?;
+
+    let resource_limits = ResourceLimits::from_config(&request.config)?;
+    let pid_matcher = PidMatcher::from_config(&request.config)?;
 
     let extra_paths = extra_paths_from_config(&request.config);
     let expanded =
@@ -156,6 +243,8 @@ async fn spawn_with_candidates(
                     args: args.to_vec(),
                     working_directory: working_directory.clone(),
                     track_process,
+                    resource_limits,
+                    pid_matcher: pid_matcher.clone(),
                 },
             )
             .await
@@ -186,6 +275,8 @@ async fn spawn_with_candidates(
                     args: args.to_vec(),
                     working_directory: working_directory.clone(),
                     track_process,
+                    resource_limits,
+                    pid_matcher: pid_matcher.clone(),
                 },
             )
             .await
@@ -226,49 +317,729 @@ pub async fn launch_action(
     app: AppHandle,
     request: LaunchActionRequest,
 ) -> Result<LaunchResult, String> {
-    let run_id = Utc::now().timestamp_millis();
+    let stack = vec![request.workspace_id];
+    launch_action_results(app, request, stack)
+        .await
+        .map(summarize_results)
+}
+
+/// Renders each of `inputs` with the variables a launch of `request` would
+/// get, for actions the frontend spawns itself. `request.config` should hold
+/// the raw templates so the command variables they reference are run.
+#[tauri::command]
+pub async fn render_launch_strings(
+    app: AppHandle,
+    request: LaunchActionRequest,
+    inputs: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let request = with_resolved_variables(&app, request).await?;
+    inputs.iter().map(|input| substitute(&request, input)).collect()
+}
+
+/// Replaces the request's variables with the backend-resolved chain (action >
+/// workspace > request > global), fills in the built-in launch context and
+/// runs the command-valued variables the action references.
+async fn with_resolved_variables(
+    app: &AppHandle,
+    mut request: LaunchActionRequest,
+) -> Result<LaunchActionRequest, String> {
+    if let Some(launch_id) = &request.launch_id {
+        request.command_values = LaunchCommandValues::for_launch(launch_id);
+    }
+    let pool = crate::database::app_pool(app).await?;
+    resolve_request_variables(&pool, &crate::secure_store::key_dir(app)?, request).await
+}
+
+async fn resolve_request_variables(
+    pool: &sqlx::SqlitePool,
+    key_dir: &std::path::Path,
+    mut request: LaunchActionRequest,
+) -> Result<LaunchActionRequest, String> {
+    let overrides = crate::variables::action_overrides(&request.config)?;
+    let tool_id = request
+        .config
+        .get("tool_id")
+        .and_then(|id| id.as_i64().or_else(|| id.as_str()?.trim().parse().ok()));
+    let layers = crate::variables::load_layers(
+        pool,
+        request.workspace_id,
+        request.environment_id,
+        &overrides,
+        &request.variables,
+        &request.secure_variables,
+    )
+    .await?;
+    let workspace = crate::database::get_workspace(pool, request.workspace_id).await?;
+    let action = crate::database::get_action(pool, request.action_id).await?;
+    let tool = match tool_id {
+        Some(id) => crate::database::get_tool(pool, id).await?,
+        None => None,
+    };
 
+    let mut resolved = crate::variables::merge_layers(&layers);
+    let mut builtins = BuiltinContext::new(request.workspace_id);
+    builtins.workspace_name = workspace.map(|workspace| workspace.name);
+    builtins.action_name = action.map(|action| action.name);
+    let tool_template: Vec<&str> = tool.iter().map(|tool| tool.template.as_str()).collect();
+    // Only referenced secure values are decrypted. A decrypted value can
+    // reference further secure values, so repeat until nothing new is found.
+    let mut decrypted = HashSet::new();
+    loop {
+        let referenced = crate::command_variables::referenced_names(
+            &request.config,
+            &tool_template,
+            &LaunchVariables {
+                variables: &resolved.values,
+                builtins: &builtins,
+            },
+            &resolved.commands,
+        );
+        if referenced.is_subset(&decrypted) {
+            break;
+        }
+        crate::variables::decrypt_values(&mut resolved, key_dir, Some(&referenced))?;
+        decrypted.extend(referenced);
+    }
+    request.variables = std::mem::take(&mut resolved.values);
+    request.secure_variables = resolved
+        .provenance
+        .iter()
+        .filter(|(_, provenance)| provenance.secure)
+        .map(|(name, _)| name.clone())
+        .collect();
+    builtins.working_directory = request
+        .config
+        .get("working_directory")
+        .and_then(|value| value.as_str())
+        .map(|dir| crate::launcher_utils::replace_variables(dir, &request.variables))
+        .transpose()?;
+    request.builtins = builtins;
+
+    let referenced = crate::command_variables::referenced_names(
+        &request.config,
+        &tool_template,
+        &launch_variables(&request),
+        &resolved.commands,
+    );
+    crate::command_variables::evaluate_into(
+        &crate::command_variables::referenced_commands(&referenced, &resolved.commands),
+        &resolved.commands,
+        request.builtins.working_directory.as_deref(),
+        &request.command_values,
+        &mut request.variables,
+    )
+    .await?;
+    let violations =
+        crate::variables::validate(&resolved, &launch_variables(&request), Some(&referenced));
+    if !violations.is_empty() {
+        return Err(crate::variables::violations_error(&violations));
+    }
+
+    let lookup = launch_variables(&request);
+    let mut secrets = Vec::new();
+    for (name, provenance) in &resolved.provenance {
+        if !provenance.secure || !referenced.contains(name) {
+            continue;
+        }
+        if let Some(value) = request.variables.get(name) {
+            secrets.push(value.clone());
+            if let Ok(rendered) = template::render(value, &lookup, false) {
+                secrets.push(rendered);
+            }
+        }
+    }
+    request.secrets = secrets;
+    Ok(request)
+}
+
+type LaunchFuture = Pin<Box<dyn Future<Output = Result<Vec<LaunchResult>, String>> + Send>>;
+
+/// Launches a single action and returns its result followed by the results of
+/// any nested workspace it started. `stack` holds the workspace IDs currently
+/// being launched, outermost first, and is used for cycle detection.
+fn launch_action_results(
+    app: AppHandle,
+    request: LaunchActionRequest,
+    stack: Vec<i64>,
+) -> LaunchFuture {
+    Box::pin(async move {
+        let request = with_resolved_variables(&app, request).await?;
+        if request.action_type == "workspace" {
+            let mut request = request;
+            let run_id = crate::run_context::new_run_id();
+            request.builtins.run_id = Some(run_id);
+            register_run(&request, run_id);
+            let results = match emit_action_started(&app, &request, run_id) {
+                Ok(()) => launch_workspace_action(app, &request, run_id, stack).await,
+                Err(e) => Err(e),
+            };
+            crate::run_context::release_run(run_id);
+            return results;
+        }
+        launch_single_action(app, request).await.map(|result| vec![result])
+    })
+}
+
+fn summarize_results(results: Vec<LaunchResult>) -> LaunchResult {
+    let mut iter = results.into_iter();
+    let first = iter.next().unwrap_or(LaunchResult {
+        success: false,
+        message: "No result".to_string(),
+        process_id: None,
+        run_id: None,
+        instance_decision: None,
+    });
+    let failed = iter.filter(|result| !result.success).count();
+    if failed == 0 {
+        return first;
+    }
+    LaunchResult {
+        success: false,
+        message: format!("{} ({} nested action(s) failed)", first.message, failed),
+        ..first
+    }
+}
+
+fn register_run(request: &LaunchActionRequest, run_id: i64) {
+    crate::run_context::register_run(
+        run_id,
+        crate::run_context::RunContext {
+            secret_patterns: crate::redact::secret_patterns(&request.secrets),
+            environment_id: request.environment_id,
+        },
+    );
+}
+
+fn emit_action_started(
+    app: &AppHandle,
+    request: &LaunchActionRequest,
+    run_id: i64,
+) -> Result<(), String> {
     app.emit(
         "action-started",
         ActionStartedEvent {
             action_id: request.action_id,
+            environment_id: request.environment_id,
             workspace_id: request.workspace_id,
             run_id,
             process_id: None,
         },
     )
-    .map_err(|error| format!("Failed to emit action-started event: {}", error))?;
+    .map_err(|error| format!("Failed to emit action-started event: {}", error))
+}
+
+async fn launch_single_action(
+    app: AppHandle,
+    request: LaunchActionRequest,
+) -> Result<LaunchResult, String> {
+    let policy = instance_policy_from_config(&request.config)?;
+    let mut decision = None;
+    if policy != InstancePolicy::AllowMultiple {
+        match crate::process::find_running_action_instance(request.action_id).await {
+            Some(instance) if policy == InstancePolicy::SkipIfRunning => {
+                return Ok(LaunchResult {
+                    success: true,
+                    message: format!(
+                        "Already running (PID {}), launch skipped",
+                        instance.identity.pid
+                    ),
+                    process_id: Some(instance.identity.pid),
+                    run_id: Some(instance.run_id),
+                    instance_decision: Some(InstanceDecision::SkippedRunning),
+                });
+            }
+            Some(instance) => {
+                let pid = instance.identity.pid;
+                let stopped = crate::process::kill_process(pid).await?;
+                if !stopped.success {
+                    return Err(format!(
+                        "Failed to stop running instance (PID {}) before restart: {}",
+                        pid, stopped.message
+                    ));
+                }
+                decision = Some(InstanceDecision::Restarted);
+            }
+            None => decision = Some(InstanceDecision::Started),
+        }
+    }
+
+    start_single_action(app, request, None)
+        .await
+        .map(|result| LaunchResult {
+            instance_decision: decision,
+            ..result
+        })
+}
 
+/// Validates per-action process options, then starts the action. `run_id`
+/// continues an existing run session (used by restarts); otherwise a new
+/// one is created.
+async fn start_single_action(
+    app: AppHandle,
+    mut request: LaunchActionRequest,
+    run_id: Option<i64>,
+) -> Result<LaunchResult, String> {
+    let mut stop_options = None;
+    if matches!(request.action_type.as_str(), "command" | "tool") {
+        let ports = crate::ports::ports_from_config(&request.config)?;
+        let kill_owner = request
+            .config
+            .get("kill_port_owner")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        crate::ports::ensure_ports_available(&ports, kill_owner).await?;
+        ResourceLimits::from_config(&request.config)?;
+        PidMatcher::from_config(&request.config)?;
+        stop_options = Some(stop_options_from_request(&request)?);
+    }
+
+    let run_id = run_id.unwrap_or_else(crate::run_context::new_run_id);
+    request.builtins.run_id = Some(run_id);
+    register_run(&request, run_id);
+    let result = dispatch_action(&app, &request, run_id).await;
+    crate::run_context::release_run(run_id);
+    let result = result?;
+
+    if let (Some(pid), Some(options)) = (result.process_id, stop_options) {
+        crate::process::register_stop_options(pid, options);
+    }
+    Ok(result)
+}
+
+async fn dispatch_action(
+    app: &AppHandle,
+    request: &LaunchActionRequest,
+    run_id: i64,
+) -> Result<LaunchResult, String> {
+    emit_action_started(app, request, run_id)?;
     match request.action_type.as_str() {
-        "command" => launch_command_action(app.clone(), &request, run_id).await,
-        "url" => launch_url_action(app.clone(), &request, run_id).await,
-        "delay" => launch_delay_action(app.clone(), &request, run_id).await,
-        "tool" => launch_tool_action(app.clone(), &request, run_id).await,
+        "command" => launch_command_action(app.clone(), request, run_id).await,
+        "url" => launch_url_action(app.clone(), request, run_id).await,
+        "delay" => launch_delay_action(app.clone(), request, run_id).await,
+        "tool" => launch_tool_action(app.clone(), request, run_id).await,
         unknown => Err(format!("Unknown action type: {}", unknown)),
     }
 }
 
+fn stop_options_from_request(request: &LaunchActionRequest) -> Result<StopOptions, String> {
+    let mut options = StopOptions::from_config(&request.config)?;
+    options.stop_command = options
+        .stop_command
+        .map(|command| substitute(request, &command))
+        .transpose()?;
+    options.working_directory = options
+        .working_directory
+        .map(|dir| substitute(request, &dir))
+        .transpose()?;
+    Ok(options)
+}
+
+/// Stops the action's running instance using its stop options, then starts it
+/// again under the same run ID so its logs stay in one session. Untracked
+/// actions are found through the process group of `run_id`. Fails when no
+/// running instance is known, since starting would not be a restart.
+#[tauri::command]
+pub async fn restart_action(
+    app: AppHandle,
+    request: LaunchActionRequest,
+    run_id: Option<i64>,
+) -> Result<LaunchResult, String> {
+    if !matches!(request.action_type.as_str(), "command" | "tool") {
+        return Err(format!(
+            "Restart is not supported for {} actions",
+            request.action_type
+        ));
+    }
+
+    let request = with_resolved_variables(&app, request).await?;
+    let (session, stopped) =
+        if let Some(instance) = crate::process::find_running_action_instance(request.action_id).await {
+            let pid = instance.identity.pid;
+            let stopped = crate::process::kill_process(pid).await?;
+            if !stopped.success {
+                return Err(format!(
+                    "Failed to stop running instance (PID {}) before restart: {}",
+                    pid, stopped.message
+                ));
+            }
+            (instance.run_id, stopped)
+        } else {
+            let group_stop = match run_id {
+                Some(run_id) => crate::process::stop_run_group(run_id).await,
+                None => None,
+            };
+            let (Some(run_id), Some(stopped)) = (run_id, group_stop) else {
+                return Err(format!(
+                    "No running instance of action {} is known; launch it instead",
+                    request.action_id
+                ));
+            };
+            let stopped = stopped?;
+            if !stopped.success {
+                return Err(format!(
+                    "Failed to stop run {} before restart: {}",
+                    run_id, stopped.message
+                ));
+            }
+            (run_id, stopped)
+        };
+    emit_log(
+        &app,
+        request.action_id,
+        request.workspace_id,
+        session,
+        "info",
+        &format!("Restarting: {}", stopped.message),
+    );
+
+    start_single_action(app, request, Some(session))
+        .await
+        .map(|result| LaunchResult {
+            instance_decision: Some(InstanceDecision::Restarted),
+            ..result
+        })
+}
+
+/// Puts `actions` in the order of `ordered_ids`, the workspace's actions by
+/// `order_index` and then id, as nested workspaces are launched. Actions
+/// missing from it go last.
+fn sort_by_workspace_order(actions: &mut [LaunchActionRequest], ordered_ids: &[i64]) {
+    let position: HashMap<i64, usize> = ordered_ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (*id, index))
+        .collect();
+    actions.sort_by_key(|action| {
+        (
+            position.get(&action.action_id).copied().unwrap_or(usize::MAX),
+            action.action_id,
+        )
+    });
+}
+
 #[tauri::command]
 pub async fn launch_workspace(
     app: AppHandle,
     mut request: LaunchWorkspaceRequest,
 ) -> Result<Vec<LaunchResult>, String> {
-    request.actions.sort_by_key(|action| action.action_id);
+    let pool = crate::database::app_pool(&app).await?;
+    let ordered_ids: Vec<i64> =
+        crate::database::get_workspace_actions(&pool, request.workspace_id)
+            .await?
+            .iter()
+            .map(|action| action.id)
+            .collect();
+    sort_by_workspace_order(&mut request.actions, &ordered_ids);
+    let command_values = LaunchCommandValues::default();
+    for action in &mut request.actions {
+        action.environment_id = action.environment_id.or(request.environment_id);
+        action.command_values = command_values.clone();
+    }
 
-    let mut results = Vec::with_capacity(request.actions.len());
+    Ok(launch_action_sequence(app, request.actions, vec![request.workspace_id]).await)
+}
 
-    for action in request.actions {
-        match launch_action(app.clone(), action).await {
-            Ok(result) => results.push(result),
+async fn launch_action_sequence(
+    app: AppHandle,
+    actions: Vec<LaunchActionRequest>,
+    stack: Vec<i64>,
+) -> Vec<LaunchResult> {
+    let mut results = Vec::with_capacity(actions.len());
+
+    for action in actions {
+        match launch_action_results(app.clone(), action, stack.clone()).await {
+            Ok(action_results) => results.extend(action_results),
             Err(error) => results.push(LaunchResult {
                 success: false,
                 message: error,
                 process_id: None,
                 run_id: None,
+                instance_decision: None,
             }),
         }
     }
 
+    results
+}
+
+fn check_workspace_cycle(stack: &[i64], target: i64) -> Result<(), String> {
+    if !stack.contains(&target) {
+        return Ok(());
+    }
+    let chain = stack
+        .iter()
+        .chain(std::iter::once(&target))
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    Err(format!("Workspace cycle detected: {}", chain))
+}
+
+/// Maps the legacy action types of older workspaces (`vscode` and the like)
+/// to a `command` or `tool` config, as the frontend does before a launch.
+fn normalize_action_config(action_type: &str, config: Value) -> (String, Value) {
+    let action_type = action_type.to_lowercase();
+    if matches!(
+        action_type.as_str(),
+        "tool" | "command" | "url" | "delay" | "workspace"
+    ) {
+        return (action_type, config);
+    }
+
+    let text = |key: &str| {
+        config
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|value| !value.trim().is_empty())
+    };
+    let mut normalized = serde_json::Map::new();
+    let args = config.get("args").cloned().unwrap_or_else(|| Value::Array(Vec::new()));
+    normalized.insert("args".to_string(), args);
+    for key in ["detached", "working_directory"] {
+        if let Some(value) = config.get(key) {
+            normalized.insert(key.to_string(), value.clone());
+        }
+    }
+    let tool_name = config
+        .get("tool_name")
+        .and_then(Value::as_str)
+        .unwrap_or(action_type.as_str())
+        .to_string();
+
+    let kind = if let Some(command) = text("command") {
+        normalized.insert("command".to_string(), Value::from(command));
+        "command"
+    } else {
+        normalized.insert("source".to_string(), Value::from("custom"));
+        normalized.insert("tool_name".to_string(), Value::from(tool_name));
+        match text("binary_path") {
+            Some(binary_path) => {
+                normalized.insert("tool_type".to_string(), Value::from("binary"));
+                normalized.insert("binary_path".to_string(), Value::from(binary_path));
+            }
+            None => {
+                let command = text("tool_name").unwrap_or(action_type.as_str()).to_string();
+                normalized.insert("tool_type".to_string(), Value::from("cli"));
+                normalized.insert("command".to_string(), Value::from(command));
+            }
+        }
+        "tool"
+    };
+    normalized.insert("type".to_string(), Value::from(kind));
+    (kind.to_string(), Value::Object(normalized))
+}
+
+fn request_from_action(
+    action: &crate::database::Action,
+    parent: &LaunchActionRequest,
+    environment_id: Option<i64>,
+) -> Result<LaunchActionRequest, String> {
+    let config: Value = serde_json::from_str(&action.config)
+        .map_err(|e| format!("Invalid config for action {}: {}", action.name, e))?;
+    let (action_type, mut config) = normalize_action_config(&action.action_type, config);
+    if matches!(action_type.as_str(), "command" | "tool") {
+        if let Some(obj) = config.as_object_mut() {
+            obj.entry("detached").or_insert(Value::Bool(action.detached));
+            obj.insert("track_process".to_string(), Value::Bool(action.track_process));
+        }
+    }
+    Ok(LaunchActionRequest {
+        workspace_id: action.workspace_id,
+        action_id: action.id,
+        action_type,
+        config,
+        variables: parent.variables.clone(),
+        environment_id,
+        launch_id: parent.launch_id.clone(),
+        builtins: BuiltinContext::default(),
+        secrets: Vec::new(),
+        secure_variables: parent.secure_variables.clone(),
+        command_values: parent.command_values.clone(),
+    })
+}
+
+/// Loads the actions of a nested workspace, passing down the variables of
+/// the `parent` request. `environment` names one of the nested workspace's
+/// environments to launch them with.
+async fn load_nested_workspace(
+    app: &AppHandle,
+    target_id: i64,
+    parent: &LaunchActionRequest,
+    environment: Option<&str>,
+) -> Result<(String, Vec<LaunchActionRequest>), String> {
+    let pool = crate::database::app_pool(app).await?;
+    let workspace = crate::database::get_workspace(&pool, target_id)
+        .await?
+        .ok_or_else(|| format!("Workspace {} not found", target_id))?;
+    let actions = crate::database::get_workspace_actions(&pool, target_id).await?;
+    let environment_id = match environment {
+        Some(name) => {
+            let environment = crate::database::find_environment_by_name(&pool, target_id, name)
+                .await?
+                .ok_or_else(|| {
+                    format!("Environment {} not found in workspace {}", name, workspace.name)
+                })?;
+            Some(environment.id)
+        }
+        None => None,
+    };
+
+    let requests = actions
+        .iter()
+        .map(|action| request_from_action(action, parent, environment_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((workspace.name, requests))
+}
+
+async fn launch_workspace_action(
+    app: AppHandle,
+    request: &LaunchActionRequest,
+    run_id: i64,
+    mut stack: Vec<i64>,
+) -> Result<Vec<LaunchResult>, String> {
+    let config = &request.config;
+    let target_id = config
+        .get("workspace_id")
+        .and_then(|value| value.as_i64())
+        .ok_or("Missing workspace_id in workspace action config")?;
+    let wait_for_started = config
+        .get("wait_for_started")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+    let timeout_ms = config.get("timeout_ms").and_then(|value| value.as_u64());
+
+    let prepared = match check_workspace_cycle(&stack, target_id) {
+        Ok(()) => {
+            let environment = config.get("environment").and_then(|value| value.as_str());
+            load_nested_workspace(&app, target_id, request, environment).await
+        }
+        Err(error) => Err(error),
+    };
+    let (workspace_name, actions) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            emit_log(
+                &app,
+                request.action_id,
+                request.workspace_id,
+                run_id,
+                "error",
+                &error,
+            );
+            emit_completed(
+                &app,
+                request.action_id,
+                request.workspace_id,
+                run_id,
+                None,
+                false,
+            );
+            return Err(error);
+        }
+    };
+
+    stack.push(target_id);
+    emit_log(
+        &app,
+        request.action_id,
+        request.workspace_id,
+        run_id,
+        "info",
+        &format!(
+            "Launching nested workspace {} ({} actions)",
+            workspace_name,
+            actions.len()
+        ),
+    );
+
+    if !wait_for_started {
+        tokio::spawn(launch_action_sequence(app.clone(), actions, stack));
+        emit_completed(
+            &app,
+            request.action_id,
+            request.workspace_id,
+            run_id,
+            Some(0),
+            true,
+        );
+        return Ok(vec![LaunchResult {
+            success: true,
+            message: format!("Nested workspace started in background: {}", workspace_name),
+            process_id: None,
+            run_id: Some(run_id),
+            instance_decision: None,
+        }]);
+    }
+
+    let sequence = launch_action_sequence(app.clone(), actions, stack);
+    let nested = match timeout_ms {
+        Some(ms) => match tokio::time::timeout(Duration::from_millis(ms), sequence).await {
+            Ok(nested) => nested,
+            Err(_) => {
+                let error = format!(
+                    "Nested workspace {} did not finish starting within {} ms",
+                    workspace_name, ms
+                );
+                emit_log(
+                    &app,
+                    request.action_id,
+                    request.workspace_id,
+                    run_id,
+                    "error",
+                    &error,
+                );
+                emit_completed(
+                    &app,
+                    request.action_id,
+                    request.workspace_id,
+                    run_id,
+                    None,
+                    false,
+                );
+                return Err(error);
+            }
+        },
+        None => sequence.await,
+    };
+
+    let failed = nested.iter().filter(|result| !result.success).count();
+    let success = failed == 0;
+    let message = if success {
+        format!("Nested workspace started: {}", workspace_name)
+    } else {
+        format!(
+            "Nested workspace {}: {} of {} actions failed",
+            workspace_name,
+            failed,
+            nested.len()
+        )
+    };
+    emit_log(
+        &app,
+        request.action_id,
+        request.workspace_id,
+        run_id,
+        if success { "info" } else { "error" },
+        &message,
+    );
+    emit_completed(
+        &app,
+        request.action_id,
+        request.workspace_id,
+        run_id,
+        Some(if success { 0 } else { 1 }),
+        success,
+    );
+
+    let mut results = Vec::with_capacity(nested.len() + 1);
+    results.push(LaunchResult {
+        success,
+        message,
+        process_id: None,
+        run_id: Some(run_id),
+        instance_decision: None,
+    });
+    results.extend(nested);
     Ok(results)
 }
 
@@ -303,14 +1074,15 @@ async fn launch_command_action(
         .and_then(|value| value.as_bool())
         .unwrap_or(false);
 
-    let command_str = replace_variables(command_str, &request.variables);
+    let command_str = substitute(request, command_str)?;
     let args: Vec<String> = args
         .iter()
-        .map(|arg| replace_variables(arg, &request.variables))
-        .collect();
+        .map(|arg| substitute(request, arg))
+        .collect::<Result<_, _>>()?;
 
-    let working_directory_resolved =
-        working_directory.map(|dir| replace_variables(dir, &request.variables));
+    let working_directory_resolved = working_directory
+        .map(|dir| substitute(request, dir))
+        .transpose()?;
 
     emit_log(
         &app,
@@ -337,7 +1109,7 @@ async fn launch_command_action(
             request,
             run_id,
             &command_str,
-            &[command_str.clone()],
+            std::slice::from_ref(&command_str),
             &args,
         )
         .await?;
@@ -355,6 +1127,7 @@ async fn launch_command_action(
             "action-started",
             ActionStartedEvent {
                 action_id: request.action_id,
+                environment_id: request.environment_id,
                 workspace_id: request.workspace_id,
                 run_id,
                 process_id: Some(process_id),
@@ -366,6 +1139,7 @@ async fn launch_command_action(
             message: format!("Command launched successfully: {}", command_str),
             process_id: Some(process_id),
             run_id: Some(run_id),
+            instance_decision: None,
         });
     }
 
@@ -416,6 +1190,8 @@ async fn launch_command_action(
                 detached: Some(detached_cfg),
                 action_id: Some(request.action_id),
                 workspace_id: Some(request.workspace_id),
+                resource_limits: ResourceLimits::from_config(config)?,
+                pid_matcher: PidMatcher::from_config(config)?,
             },
         )
         .await
@@ -459,6 +1235,7 @@ async fn launch_command_action(
             message: format!("Command launched successfully (detached): {}", command_str),
             process_id: Some(process_id),
             run_id: Some(run_id),
+            instance_decision: None,
         });
     }
 
@@ -467,6 +1244,7 @@ async fn launch_command_action(
         message: format!("Command started: {}", command_str),
         process_id: Some(process_id),
         run_id: Some(run_id),
+        instance_decision: None,
     })
 }
 
@@ -480,7 +1258,7 @@ async fn launch_url_action(
         .get("url")
         .and_then(|value| value.as_str())
         .ok_or("Missing url in URL action config")?;
-    let url = replace_variables(url, &request.variables);
+    let url = substitute(request, url)?;
 
     emit_log(
         &app,
@@ -507,6 +1285,7 @@ async fn launch_url_action(
                 message: format!("URL opened successfully: {}", url),
                 process_id: None,
                 run_id: Some(run_id),
+                instance_decision: None,
             })
         }
         Err(error) => {
@@ -576,6 +1355,60 @@ async fn launch_delay_action(
         message: format!("Delay completed: {} ms", duration_ms),
         process_id: None,
         run_id: Some(run_id),
+        instance_decision: None,
+    })
+}
+
+#[derive(Debug)]
+struct SavedToolCommand {
+    tool_name: String,
+    program: String,
+    args: Vec<String>,
+}
+
+/// Loads a saved tool and turns its template into a program and arguments:
+/// `{{placeholder}}` tokens are validated and rendered first, then `${VAR}`
+/// references are substituted and the result is split into words.
+async fn plan_saved_tool(
+    app: &AppHandle,
+    tool_id: i64,
+    provided: &HashMap<String, String>,
+    variables: &dyn VariableLookup,
+    strict: bool,
+) -> Result<SavedToolCommand, String> {
+    let pool = crate::database::app_pool(app).await?;
+    let tool = crate::database::get_tool(&pool, tool_id)
+        .await?
+        .ok_or_else(|| format!("Tool {} not found", tool_id))?;
+
+    if !tool.enabled {
+        return Err(format!("Tool {} is disabled", tool.name));
+    }
+    if tool.tool_type == "http" {
+        return Err(format!("Tool {} is an HTTP tool and cannot be launched as a process", tool.name));
+    }
+
+    build_tool_command(&tool, provided, variables, strict)
+}
+
+fn build_tool_command(
+    tool: &crate::database::Tool,
+    provided: &HashMap<String, String>,
+    variables: &dyn VariableLookup,
+    strict: bool,
+) -> Result<SavedToolCommand, String> {
+    let definitions = tool_template::parse_placeholders(&tool.placeholders)?;
+    let values = tool_template::resolve_placeholder_values(&definitions, provided)?;
+    let command_line = tool_template::render_template(&tool.template, &values, variables, strict)?;
+    let mut words = tool_template::split_words(&command_line)?;
+    if words.is_empty() {
+        return Err(format!("Tool {} rendered an empty command", tool.name));
+    }
+    let program = words.remove(0);
+    Ok(SavedToolCommand {
+        tool_name: tool.name.clone(),
+        program,
+        args: words,
     })
 }
 
@@ -601,43 +1434,70 @@ async fn launch_tool_action(
         args: Vec<String>,
     }
 
-    let plan = if config.get("tool_id").is_some() {
+    let plan = if let Some(tool_id) = config.get("tool_id") {
+        let tool_id = tool_id
+            .as_i64()
+            .or_else(|| tool_id.as_str().and_then(|s| s.trim().parse().ok()))
+            .ok_or("Invalid tool_id in tool action config")?;
         let placeholder_values = config
             .get("placeholder_values")
             .and_then(|value| value.as_object())
             .ok_or("Missing placeholder_values in tool action config")?;
 
-        let mut resolved = HashMap::new();
+        let mut provided = HashMap::new();
         for (name, value) in placeholder_values {
-            let value_str = value
-                .as_str()
-                .ok_or_else(|| format!("Placeholder {} must be a string", name))?;
-            let resolved_value = replace_variables(value_str, &request.variables);
-            resolved.insert(name.clone(), resolved_value);
+            let value_str = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Null => String::new(),
+                _ => return Err(format!("Placeholder {} must be a string", name)),
+            };
+            provided.insert(name.clone(), value_str);
         }
 
-        let binary_path = resolved
-            .get("binary_path")
-            .cloned()
-            .ok_or("Missing binary_path placeholder for tool")?;
-        let args: Vec<String> = resolved
-            .get("args")
-            .map(|v| v.to_string())
-            .map(|s| {
-                if s.is_empty() {
-                    vec![]
-                } else {
-                    s.split_whitespace().map(|x| x.to_string()).collect()
-                }
-            })
-            .unwrap_or_default();
+        let saved = match plan_saved_tool(
+            &app,
+            tool_id,
+            &provided,
+            &launch_variables(request),
+            strict_variables(&request.config),
+        )
+        .await
+        {
+            Ok(saved) => saved,
+            Err(error_message) => {
+                emit_log(
+                    &app,
+                    request.action_id,
+                    request.workspace_id,
+                    run_id,
+                    "error",
+                    &error_message,
+                );
+                emit_completed(
+                    &app,
+                    request.action_id,
+                    request.workspace_id,
+                    run_id,
+                    None,
+                    false,
+                );
+                return Err(error_message);
+            }
+        };
+        let tool_name = config
+            .get("tool_name")
+            .and_then(|value| value.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or(saved.tool_name);
 
         ToolCommandPlan {
             tool_name: tool_name.clone(),
             description: format!("Launching {}", tool_name),
             success_message: format!("{} launched", tool_name),
-            candidates: vec![binary_path],
-            args,
+            candidates: vec![saved.program],
+            args: saved.args,
         }
     } else {
         let tool_type = config
@@ -657,9 +1517,10 @@ async fn launch_tool_action(
                 .map(|arr| {
                     arr.iter()
                         .filter_map(|v| v.as_str())
-                        .map(|s| replace_variables(s, &request.variables))
-                        .collect()
+                        .map(|s| substitute(request, s))
+                        .collect::<Result<Vec<_>, _>>()
                 })
+                .transpose()?
                 .unwrap_or_default();
 
             (command.to_string(), args)
@@ -675,9 +1536,10 @@ async fn launch_tool_action(
                 .map(|arr| {
                     arr.iter()
                         .filter_map(|v| v.as_str())
-                        .map(|s| replace_variables(s, &request.variables))
-                        .collect()
+                        .map(|s| substitute(request, s))
+                        .collect::<Result<Vec<_>, _>>()
                 })
+                .transpose()?
                 .unwrap_or_default();
 
             (binary_path.to_string(), args)
@@ -737,6 +1599,7 @@ async fn launch_tool_action(
         "action-started",
         ActionStartedEvent {
             action_id: request.action_id,
+            environment_id: request.environment_id,
             workspace_id: request.workspace_id,
             run_id,
             process_id: Some(process_id),
@@ -760,6 +1623,7 @@ async fn launch_tool_action(
         message: plan.success_message,
         process_id: Some(process_id),
         run_id: Some(run_id),
+        instance_decision: None,
     })
 }
 
@@ -778,7 +1642,7 @@ fn emit_log(
             workspace_id,
             run_id,
             level: level.to_string(),
-            message: message.to_string(),
+            message: crate::redact::redact_run(run_id, message),
         },
     );
 }
@@ -795,6 +1659,8 @@ fn emit_completed(
         "action-completed",
         ActionCompletedEvent {
             action_id,
+            environment_id: crate::run_context::run_context(run_id)
+                .and_then(|context| context.environment_id),
             workspace_id,
             run_id,
             exit_code,
@@ -805,6 +1671,281 @@ fn emit_completed(
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_policy_from_config() {
+        assert_eq!(
+            instance_policy_from_config(&serde_json::json!({})).unwrap(),
+            InstancePolicy::AllowMultiple
+        );
+        assert_eq!(
+            instance_policy_from_config(&serde_json::json!({"instance_policy": "skip_if_running"}))
+                .unwrap(),
+            InstancePolicy::SkipIfRunning
+        );
+        assert_eq!(
+            instance_policy_from_config(
+                &serde_json::json!({"instance_policy": "restart_if_running"})
+            )
+            .unwrap(),
+            InstancePolicy::RestartIfRunning
+        );
+        assert!(instance_policy_from_config(&serde_json::json!({"instance_policy": "sometimes"}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_workspace_cycle() {
+        assert!(check_workspace_cycle(&[1, 2], 3).is_ok());
+        let err = check_workspace_cycle(&[1, 2], 1).unwrap_err();
+        assert_eq!(err, "Workspace cycle detected: 1 -> 2 -> 1");
+        assert!(check_workspace_cycle(&[4], 4).is_err());
+    }
+
+    #[test]
+    fn test_summarize_results_reports_nested_failures() {
+        let result = |success: bool| LaunchResult {
+            success,
+            message: "Nested workspace started: stack".to_string(),
+            process_id: None,
+            run_id: Some(1),
+            instance_decision: None,
+        };
+        assert!(summarize_results(vec![result(true), result(true)]).success);
+        let summary = summarize_results(vec![result(true), result(false)]);
+        assert!(!summary.success);
+        assert!(summary.message.contains("1 nested action(s) failed"));
+    }
+
+    fn launch_request(action_id: i64) -> LaunchActionRequest {
+        LaunchActionRequest {
+            workspace_id: 1,
+            action_id,
+            action_type: "command".to_string(),
+            config: serde_json::json!({}),
+            variables: HashMap::new(),
+            environment_id: None,
+            launch_id: None,
+            builtins: BuiltinContext::default(),
+            secrets: Vec::new(),
+            secure_variables: HashSet::new(),
+            command_values: LaunchCommandValues::default(),
+        }
+    }
+
+    #[test]
+    fn test_workspace_launch_follows_order_index() {
+        let mut actions = vec![launch_request(1), launch_request(9), launch_request(4)];
+        sort_by_workspace_order(&mut actions, &[4, 1]);
+        let ids: Vec<i64> = actions.iter().map(|action| action.action_id).collect();
+        assert_eq!(ids, vec![4, 1, 9]);
+    }
+
+    #[test]
+    fn test_request_from_action_normalizes_legacy_types() {
+        let mut action = crate::database::Action {
+            id: 5,
+            workspace_id: 7,
+            name: "editor".to_string(),
+            action_type: "vscode".to_string(),
+            config: r#"{"args":["."],"working_directory":"/src"}"#.to_string(),
+            dependencies: None,
+            timeout_seconds: None,
+            detached: true,
+            track_process: false,
+            auto_launch: false,
+            os_overrides: None,
+            order_index: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let parent = launch_request(1);
+
+        let request = request_from_action(&action, &parent, None).expect("request");
+        assert_eq!(request.action_type, "tool");
+        assert_eq!(request.config["tool_type"], "cli");
+        assert_eq!(request.config["command"], "vscode");
+        assert_eq!(request.config["args"], serde_json::json!(["."]));
+        assert_eq!(request.config["working_directory"], "/src");
+        assert_eq!(request.config["detached"], Value::Bool(true));
+
+        action.action_type = "Terminal".to_string();
+        action.config = r#"{"command":"htop"}"#.to_string();
+        let request = request_from_action(&action, &parent, None).expect("request");
+        assert_eq!(request.action_type, "command");
+        assert_eq!(request.config["command"], "htop");
+        assert_eq!(request.config["args"], serde_json::json!([]));
+
+        action.action_type = "URL".to_string();
+        action.config = r#"{"url":"https://example.com"}"#.to_string();
+        let request = request_from_action(&action, &parent, None).expect("request");
+        assert_eq!(request.action_type, "url");
+    }
+
+    #[test]
+    fn test_request_from_action_applies_flags() {
+        let action = crate::database::Action {
+            id: 3,
+            workspace_id: 7,
+            name: "api".to_string(),
+            action_type: "command".to_string(),
+            config: r#"{"command":"npm","args":["run","dev"]}"#.to_string(),
+            dependencies: None,
+            timeout_seconds: None,
+            detached: true,
+            track_process: true,
+            auto_launch: false,
+            os_overrides: None,
+            order_index: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let parent = LaunchActionRequest {
+            workspace_id: 1,
+            action_id: 1,
+            action_type: "workspace".to_string(),
+            config: serde_json::json!({}),
+            variables: HashMap::from([
+                ("PORT".to_string(), "8080".to_string()),
+                ("TOKEN".to_string(), "s3cret".to_string()),
+            ]),
+            environment_id: None,
+            launch_id: None,
+            builtins: BuiltinContext::default(),
+            secrets: Vec::new(),
+            secure_variables: HashSet::from(["TOKEN".to_string()]),
+            command_values: LaunchCommandValues::default(),
+        };
+
+        let request = request_from_action(&action, &parent, Some(3)).expect("request");
+        assert_eq!(request.workspace_id, 7);
+        assert_eq!(request.config["detached"], Value::Bool(true));
+        assert_eq!(request.config["track_process"], Value::Bool(true));
+        assert_eq!(request.variables.get("PORT").map(String::as_str), Some("8080"));
+        assert!(request.secure_variables.contains("TOKEN"));
+        assert_eq!(request.environment_id, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_command_valued_global_runs_at_launch() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        crate::database::run_migrations(&db_path).await.expect("migrate");
+        let pool = crate::database::connect(&db_path).await.expect("connect");
+        sqlx::query(
+            "INSERT INTO workspaces (id, name) VALUES (1, 'api'); \
+             INSERT INTO global_variables (key, value, enabled, value_source) VALUES \
+             ('SHA', 'echo abc123', 1, 'command')",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert");
+
+        let request = LaunchActionRequest {
+            workspace_id: 1,
+            action_id: 0,
+            action_type: "command".to_string(),
+            config: serde_json::json!({ "command": "git", "args": ["checkout", "${SHA}"] }),
+            variables: HashMap::new(),
+            environment_id: None,
+            launch_id: None,
+            builtins: BuiltinContext::default(),
+            secrets: Vec::new(),
+            secure_variables: HashSet::new(),
+            command_values: LaunchCommandValues::default(),
+        };
+        let request = resolve_request_variables(&pool, dir.path(), request)
+            .await
+            .expect("resolve");
+        pool.close().await;
+        assert_eq!(request.variables.get("SHA").map(String::as_str), Some("abc123"));
+        assert_eq!(substitute(&request, "${SHA}").expect("render"), "abc123");
+    }
+
+    #[tokio::test]
+    async fn test_only_referenced_secure_values_are_decrypted() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        crate::database::run_migrations(&db_path).await.expect("migrate");
+        let pool = crate::database::connect(&db_path).await.expect("connect");
+        let token = crate::secure_store::encrypt(dir.path(), "s3cret").expect("encrypt");
+        // Fails to decrypt if it is ever touched: no key has this id.
+        let foreign = format!("{}unknownkey:AAAA", crate::secure_store::ENCRYPTED_PREFIX);
+        sqlx::query(
+            "INSERT INTO workspaces (id, name) VALUES (1, 'api'); \
+             INSERT INTO variables (workspace_id, key, value, is_secure, enabled) VALUES \
+             (1, 'TOKEN', ?, 1, 1), (1, 'UNUSED', ?, 1, 1)",
+        )
+        .bind(&token)
+        .bind(&foreign)
+        .execute(&pool)
+        .await
+        .expect("insert");
+
+        let request = LaunchActionRequest {
+            workspace_id: 1,
+            action_id: 0,
+            action_type: "command".to_string(),
+            config: serde_json::json!({ "command": "curl", "args": ["-H", "${TOKEN}"] }),
+            variables: HashMap::new(),
+            environment_id: None,
+            launch_id: None,
+            builtins: BuiltinContext::default(),
+            secrets: Vec::new(),
+            secure_variables: HashSet::new(),
+            command_values: LaunchCommandValues::default(),
+        };
+        let request = resolve_request_variables(&pool, dir.path(), request)
+            .await
+            .expect("resolve");
+        pool.close().await;
+        assert_eq!(request.variables.get("TOKEN").map(String::as_str), Some("s3cret"));
+        assert_eq!(request.variables.get("UNUSED"), Some(&foreign));
+        assert!(request.secrets.contains(&"s3cret".to_string()));
+        assert!(!request.secrets.contains(&foreign));
+    }
+
+    #[test]
+    fn test_build_tool_command_renders_template() {
+        let tool = crate::database::Tool {
+            id: 8,
+            name: "Docker Compose".to_string(),
+            description: None,
+            icon: None,
+            enabled: true,
+            tool_type: "command".to_string(),
+            template: r#"docker-compose -f "{{compose_file}}" {{command}}"#.to_string(),
+            placeholders: r#"[{"key":"compose_file","label":"Compose File Path","type":"text"},{"key":"command","label":"Command (up/down/restart)","type":"text"}]"#.to_string(),
+            category: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let mut provided = HashMap::new();
+        provided.insert(
+            "compose_file".to_string(),
+            "${STACK_DIR}/docker-compose.yml".to_string(),
+        );
+        provided.insert("command".to_string(), "up -d".to_string());
+        let mut variables = HashMap::new();
+        variables.insert("STACK_DIR".to_string(), "/srv/backend stack".to_string());
+
+        let command = build_tool_command(&tool, &provided, &variables, false).expect("command");
+        assert_eq!(command.program, "docker-compose");
+        assert_eq!(
+            command.args,
+            vec!["-f", "/srv/backend stack/docker-compose.yml", "up", "-d"]
+        );
+
+        provided.remove("command");
+        let err = build_tool_command(&tool, &provided, &variables, false).unwrap_err();
+        assert!(err.contains("Command (up/down/restart) is required"), "{}", err);
+
+        provided.insert("command".to_string(), "up ${SERVICE}".to_string());
+        let err = build_tool_command(&tool, &provided, &variables, true).unwrap_err();
+        assert_eq!(err, "Unresolved variables: SERVICE");
+    }
+
     #[tokio::test]
     async fn test_spawn_hidden_process_echo() {
         #[cfg(target_os = "windows")]
@@ -1,3 +1,6 @@
+use crate::pid_matcher::PidMatcher;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command as TokioCommand};
@@ -5,6 +8,187 @@ use tokio::process::{Child, Command as TokioCommand};
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Scheduling priority and Unix rlimits applied to a spawned process before
+/// exec. `nice` follows Unix semantics (-20 highest .. 19 lowest); on Windows
+/// it is mapped to a priority class and rlimits are not supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    pub nice: Option<i32>,
+    pub address_space_mb: Option<u64>,
+    pub open_files: Option<u64>,
+    pub cpu_seconds: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn from_config(config: &Value) -> Result<Option<Self>, String> {
+        let Some(value) = config.get("resource_limits").filter(|v| !v.is_null()) else {
+            return Ok(None);
+        };
+        let limits: ResourceLimits = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid resource_limits: {}", e))?;
+        limits.validate()?;
+        Ok(Some(limits).filter(|l| *l != ResourceLimits::default()))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(nice) = self.nice {
+            if !(-20..=19).contains(&nice) {
+                return Err(format!("nice must be between -20 and 19, got {}", nice));
+            }
+        }
+        for (name, value) in [
+            ("address_space_mb", self.address_space_mb),
+            ("open_files", self.open_files),
+            ("cpu_seconds", self.cpu_seconds),
+        ] {
+            if value == Some(0) {
+                return Err(format!("{} must be greater than 0", name));
+            }
+        }
+        #[cfg(unix)]
+        self.check_hard_limits()?;
+        #[cfg(windows)]
+        if self.address_space_mb.is_some() || self.open_files.is_some() || self.cpu_seconds.is_some() {
+            return Err("Resource limits (address_space_mb, open_files, cpu_seconds) are not supported on Windows".to_string());
+        }
+        Ok(())
+    }
+
+    /// Soft limits above the launcher's hard limit would fail at spawn.
+    #[cfg(unix)]
+    fn check_hard_limits(&self) -> Result<(), String> {
+        use nix::libc;
+
+        macro_rules! check {
+            ($name:expr, $resource:expr, $value:expr, $scale:expr) => {
+                let mut rlim = libc::rlimit {
+                    rlim_cur: 0,
+                    rlim_max: 0,
+                };
+                // SAFETY: getrlimit only writes to `rlim`.
+                let known = unsafe { libc::getrlimit($resource, &mut rlim) } == 0
+                    && rlim.rlim_max != libc::RLIM_INFINITY;
+                if let Some(value) = $value.filter(|_| known) {
+                    let max = rlim.rlim_max / $scale;
+                    if value > max {
+                        return Err(format!(
+                            "{} {} exceeds the hard limit of {}",
+                            $name, value, max
+                        ));
+                    }
+                }
+            };
+        }
+        check!("address_space_mb", libc::RLIMIT_AS, self.address_space_mb, 1024 * 1024);
+        check!("open_files", libc::RLIMIT_NOFILE, self.open_files, 1);
+        check!("cpu_seconds", libc::RLIMIT_CPU, self.cpu_seconds, 1);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn priority_class_flags(&self) -> u32 {
+        const IDLE_PRIORITY_CLASS: u32 = 0x00000040;
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x00004000;
+        const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x00008000;
+        const HIGH_PRIORITY_CLASS: u32 = 0x00000080;
+        match self.nice {
+            Some(n) if n >= 15 => IDLE_PRIORITY_CLASS,
+            Some(n) if n >= 5 => BELOW_NORMAL_PRIORITY_CLASS,
+            Some(n) if n <= -15 => HIGH_PRIORITY_CLASS,
+            Some(n) if n <= -5 => ABOVE_NORMAL_PRIORITY_CLASS,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(windows)]
+fn priority_flags(limits: Option<&ResourceLimits>) -> u32 {
+    limits.map(|l| l.priority_class_flags()).unwrap_or(0)
+}
+
+/// Puts the child in its own process group (attached) or session (detached)
+/// so the whole tree can be signalled on stop.
+#[cfg(unix)]
+fn isolate_process_group(cmd: &mut TokioCommand, new_session: bool) {
+    if new_session {
+        // SAFETY: setsid is async-signal-safe.
+        unsafe {
+            cmd.pre_exec(|| {
+                if nix::libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    } else {
+        cmd.process_group(0);
+    }
+}
+
+fn register_spawned_group(tracked_pid: u32, run_id: i64, leader_pid: u32) {
+    #[cfg(unix)]
+    crate::process::register_process_group(tracked_pid, run_id, leader_pid as i32);
+    #[cfg(not(unix))]
+    let _ = (tracked_pid, run_id, leader_pid);
+}
+
+/// Spawn errors from a negative `nice` only say "permission denied".
+fn spawn_error(error: std::io::Error, limits: Option<&ResourceLimits>) -> String {
+    let raises_priority = limits.and_then(|l| l.nice).is_some_and(|nice| nice < 0);
+    if cfg!(unix) && raises_priority && error.kind() == std::io::ErrorKind::PermissionDenied {
+        return format!(
+            "Failed to spawn: {}. A negative nice value raises the priority, which needs root or CAP_SYS_NICE; use 0 or higher.",
+            error
+        );
+    }
+    format!("Failed to spawn: {}", error)
+}
+
+#[cfg(unix)]
+fn apply_resource_limits(cmd: &mut TokioCommand, limits: &ResourceLimits) {
+    use nix::libc;
+
+    let limits = *limits;
+    // SAFETY: the closure only issues setpriority/setrlimit syscalls, which are
+    // async-signal-safe, and does not allocate.
+    unsafe {
+        cmd.pre_exec(move || {
+            // Only the soft limit is set: lowering the hard limit could not be
+            // undone by anything the action starts.
+            macro_rules! set_limit {
+                ($resource:expr, $value:expr) => {
+                    let mut rlim = libc::rlimit {
+                        rlim_cur: 0,
+                        rlim_max: 0,
+                    };
+                    if libc::getrlimit($resource, &mut rlim) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    rlim.rlim_cur = $value as libc::rlim_t;
+                    if libc::setrlimit($resource, &rlim) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                };
+            }
+            if let Some(nice) = limits.nice {
+                if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(mb) = limits.address_space_mb {
+                set_limit!(libc::RLIMIT_AS, mb.saturating_mul(1024 * 1024));
+            }
+            if let Some(files) = limits.open_files {
+                set_limit!(libc::RLIMIT_NOFILE, files);
+            }
+            if let Some(secs) = limits.cpu_seconds {
+                set_limit!(libc::RLIMIT_CPU, secs);
+            }
+            Ok(())
+        });
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AttachedSpawnRequest {
     pub action_id: Option<i64>,
@@ -14,6 +198,8 @@ pub struct AttachedSpawnRequest {
     pub args: Vec<String>,
     pub working_directory: Option<String>,
     pub track_process: bool,
+    pub resource_limits: Option<ResourceLimits>,
+    pub pid_matcher: Option<PidMatcher>,
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +211,8 @@ pub struct DetachedSpawnRequest {
     pub args: Vec<String>,
     pub working_directory: Option<String>,
     pub track_process: bool,
+    pub resource_limits: Option<ResourceLimits>,
+    pub pid_matcher: Option<PidMatcher>,
 }
 
 struct SpawnCompletionContext {
@@ -42,8 +230,12 @@ fn unix_now_secs() -> u64 {
 }
 
 fn spawn_child_supervisor(app: AppHandle, mut child: Child, ctx: SpawnCompletionContext) {
+    crate::run_context::retain_run(ctx.run_id);
     tokio::spawn(async move {
         let exit_status = child.wait().await;
+        let environment_id = crate::run_context::run_context(ctx.run_id)
+            .and_then(|context| context.environment_id);
+        crate::run_context::release_run(ctx.run_id);
         if !ctx.emit_completion_on_exit {
             return;
         }
@@ -59,6 +251,7 @@ fn spawn_child_supervisor(app: AppHandle, mut child: Child, ctx: SpawnCompletion
             "action-completed",
             serde_json::json!({
                 "action_id": action_id,
+                "environment_id": environment_id,
                 "workspace_id": workspace_id,
                 "run_id": ctx.run_id,
                 "exit_code": exit_code,
@@ -68,50 +261,42 @@ fn spawn_child_supervisor(app: AppHandle, mut child: Child, ctx: SpawnCompletion
     });
 }
 
+/// Emits each line of `pipe` as an `action-log` event. The run's context is
+/// held until the pipe closes, so lines written after the child exits are
+/// still redacted.
+fn forward_lines<R>(app: &AppHandle, pipe: R, req: &AttachedSpawnRequest, level: &'static str)
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    let app = app.clone();
+    let action_id = req.action_id;
+    let workspace_id = req.workspace_id;
+    let run_id = req.run_id;
+    crate::run_context::retain_run(run_id);
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = app.emit(
+                "action-log",
+                serde_json::json!({
+                    "action_id": action_id,
+                    "workspace_id": workspace_id,
+                    "run_id": run_id,
+                    "level": level,
+                    "message": crate::redact::redact_run(run_id, &line),
+                }),
+            );
+        }
+        crate::run_context::release_run(run_id);
+    });
+}
+
 fn pipe_child_output(app: &AppHandle, child: &mut Child, req: &AttachedSpawnRequest) {
     if let Some(stdout) = child.stdout.take() {
-        let app_clone = app.clone();
-        let action_id = req.action_id;
-        let workspace_id = req.workspace_id;
-        let run_id = req.run_id;
-        tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                let _ = app_clone.emit(
-                    "action-log",
-                    serde_json::json!({
-                        "action_id": action_id,
-                        "workspace_id": workspace_id,
-                        "run_id": run_id,
-                        "level": "info",
-                        "message": line,
-                    }),
-                );
-            }
-        });
+        forward_lines(app, stdout, req, "info");
     }
     if let Some(stderr) = child.stderr.take() {
-        let app_clone = app.clone();
-        let action_id = req.action_id;
-        let workspace_id = req.workspace_id;
-        let run_id = req.run_id;
-        tokio::spawn(async move {
-            let reader = BufReader::new(stderr);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                let _ = app_clone.emit(
-                    "action-log",
-                    serde_json::json!({
-                        "action_id": action_id,
-                        "workspace_id": workspace_id,
-                        "run_id": run_id,
-                        "level": "error",
-                        "message": line,
-                    }),
-                );
-            }
-        });
+        forward_lines(app, stderr, req, "error");
     }
 }
 
@@ -128,16 +313,26 @@ pub async fn spawn_attached_with_logs(
     if let Some(dir) = &req.working_directory {
         cmd.current_dir(dir);
     }
+    cmd.env(crate::orphans::RUN_ID_ENV, req.run_id.to_string());
     #[cfg(windows)]
     {
         #[allow(unused_imports)]
         use std::os::windows::process::CommandExt;
-        let _ = cmd.creation_flags(CREATE_NO_WINDOW);
+        let _ = cmd.creation_flags(CREATE_NO_WINDOW | priority_flags(req.resource_limits.as_ref()));
+    }
+    #[cfg(unix)]
+    {
+        isolate_process_group(&mut cmd, false);
+        if let Some(limits) = &req.resource_limits {
+            apply_resource_limits(&mut cmd, limits);
+        }
     }
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
 
-    let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| spawn_error(e, req.resource_limits.as_ref()))?;
     let pid = child.id().unwrap_or(0);
 
     pipe_child_output(app, &mut child, &req);
@@ -154,18 +349,34 @@ pub async fn spawn_attached_with_logs(
     );
 
     if req.track_process {
-        let resolved = resolve_pid_for_tracking(
-            pid,
-            &req.command,
-            &req.args,
-            req.working_directory.as_deref(),
-            started_after_secs,
-        )
-        .await;
+        let resolved = match &req.pid_matcher {
+            Some(matcher) => {
+                crate::pid_matcher::resolve_with_matcher(matcher, pid, started_after_secs).await
+            }
+            None => {
+                resolve_pid_for_tracking(
+                    pid,
+                    &req.command,
+                    &req.args,
+                    req.working_directory.as_deref(),
+                    started_after_secs,
+                )
+                .await
+            }
+        };
         let tracked_pid = resolved.unwrap_or(pid);
-        crate::process::register_tracked_pid(tracked_pid);
+        if let Some(tracked) = crate::tracked_processes::track(
+            tracked_pid,
+            Some(req.run_id),
+            req.action_id,
+            req.workspace_id,
+        ) {
+            crate::exit_watcher::watch(app.clone(), tracked);
+        }
+        register_spawned_group(tracked_pid, req.run_id, pid);
         Ok(tracked_pid)
     } else {
+        register_spawned_group(pid, req.run_id, pid);
         Ok(pid)
     }
 }
@@ -193,7 +404,7 @@ async fn resolve_pid_for_tracking(
     started_after_secs: u64,
 ) -> Option<u32> {
     let expected_name = args
-        .get(0)
+        .first()
         .filter(|s| s.ends_with(".exe") || s.contains('/') || s.contains('\\'))
         .map(|s| s.to_string())
         .or_else(|| Some(command.to_string()));
@@ -221,20 +432,39 @@ pub async fn spawn_detached(app: &AppHandle, req: DetachedSpawnRequest) -> Resul
     if let Some(dir) = &req.working_directory {
         cmd.current_dir(dir);
     }
+    // Untracked detached processes are meant to outlive the launcher, so they
+    // carry no marker and are never mistaken for orphans.
+    if req.track_process {
+        cmd.env(crate::orphans::RUN_ID_ENV, req.run_id.to_string());
+    }
     #[cfg(windows)]
     {
         #[allow(unused_imports)]
         use std::os::windows::process::CommandExt;
         const DETACHED_PROCESS: u32 = 0x00000008;
         const CREATE_BREAKAWAY_FROM_JOB: u32 = 0x00000200;
-        let _ = cmd.creation_flags(CREATE_NO_WINDOW | DETACHED_PROCESS | CREATE_BREAKAWAY_FROM_JOB);
+        let _ = cmd.creation_flags(
+            CREATE_NO_WINDOW
+                | DETACHED_PROCESS
+                | CREATE_BREAKAWAY_FROM_JOB
+                | priority_flags(req.resource_limits.as_ref()),
+        );
+    }
+    #[cfg(unix)]
+    {
+        isolate_process_group(&mut cmd, true);
+        if let Some(limits) = &req.resource_limits {
+            apply_resource_limits(&mut cmd, limits);
+        }
     }
 
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
     cmd.stdin(std::process::Stdio::null());
 
-    let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| spawn_error(e, req.resource_limits.as_ref()))?;
     let pid = child.id().unwrap_or(0);
 
     let attached_req = AttachedSpawnRequest {
@@ -245,6 +475,8 @@ pub async fn spawn_detached(app: &AppHandle, req: DetachedSpawnRequest) -> Resul
         args: req.args.clone(),
         working_directory: req.working_directory.clone(),
         track_process: req.track_process,
+        resource_limits: req.resource_limits,
+        pid_matcher: req.pid_matcher.clone(),
     };
     pipe_child_output(app, &mut child, &attached_req);
 
@@ -260,18 +492,101 @@ pub async fn spawn_detached(app: &AppHandle, req: DetachedSpawnRequest) -> Resul
     );
 
     if req.track_process {
-        let resolved = resolve_pid_for_tracking(
-            pid,
-            &req.command,
-            &req.args,
-            req.working_directory.as_deref(),
-            started_after_secs,
-        )
-        .await;
+        let resolved = match &req.pid_matcher {
+            Some(matcher) => {
+                crate::pid_matcher::resolve_with_matcher(matcher, pid, started_after_secs).await
+            }
+            None => {
+                resolve_pid_for_tracking(
+                    pid,
+                    &req.command,
+                    &req.args,
+                    req.working_directory.as_deref(),
+                    started_after_secs,
+                )
+                .await
+            }
+        };
         let tracked_pid = resolved.unwrap_or(pid);
-        crate::process::register_tracked_pid(tracked_pid);
+        if let Some(tracked) = crate::tracked_processes::track(
+            tracked_pid,
+            Some(req.run_id),
+            req.action_id,
+            req.workspace_id,
+        ) {
+            crate::exit_watcher::watch(app.clone(), tracked);
+        }
+        register_spawned_group(tracked_pid, req.run_id, pid);
         Ok(tracked_pid)
     } else {
+        register_spawned_group(pid, req.run_id, pid);
         Ok(pid)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_limits_from_config() {
+        assert_eq!(ResourceLimits::from_config(&serde_json::json!({})), Ok(None));
+        assert_eq!(
+            ResourceLimits::from_config(&serde_json::json!({ "resource_limits": { "nice": 10 } })),
+            Ok(Some(ResourceLimits {
+                nice: Some(10),
+                ..ResourceLimits::default()
+            }))
+        );
+        assert!(
+            ResourceLimits::from_config(&serde_json::json!({ "resource_limits": { "nice": 40 } }))
+                .is_err()
+        );
+        assert!(ResourceLimits::from_config(
+            &serde_json::json!({ "resource_limits": { "open_files": 0 } })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn negative_nice_spawn_errors_are_explained() {
+        let limits = ResourceLimits {
+            nice: Some(-10),
+            ..ResourceLimits::default()
+        };
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let message = spawn_error(denied, Some(&limits));
+        if cfg!(unix) {
+            assert!(message.contains("CAP_SYS_NICE"), "{}", message);
+        }
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(!spawn_error(missing, Some(&limits)).contains("nice"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn resource_limits_are_visible_from_child() {
+        let limits = ResourceLimits {
+            nice: Some(5),
+            address_space_mb: Some(2048),
+            open_files: Some(256),
+            cpu_seconds: Some(120),
+        };
+        let hard_files = TokioCommand::new("sh")
+            .args(["-c", "ulimit -Hn"])
+            .output()
+            .await
+            .expect("spawn sh");
+        let hard_files = String::from_utf8_lossy(&hard_files.stdout).trim().to_string();
+
+        let mut cmd = TokioCommand::new("sh");
+        cmd.args(["-c", "ulimit -n; ulimit -t; ulimit -v; nice; ulimit -Hn"]);
+        apply_resource_limits(&mut cmd, &limits);
+        let output = cmd.output().await.expect("spawn sh");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().map(str::trim).collect();
+        assert_eq!(lines, vec!["256", "120", "2097152", "5", hard_files.as_str()]);
+    }
+}
@@ -7,16 +7,13 @@ use tokio::process::Command as TokioCommand;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// `${VAR}` substitution that leaves unknown names as written. Failed
+/// `${VAR:?message}` checks and reference cycles are errors.
 pub fn replace_variables(
     input: &str,
     variables: &std::collections::HashMap<String, String>,
-) -> String {
-    let mut result = input.to_string();
-    for (key, value) in variables {
-        let placeholder = format!("${{{}}}", key);
-        result = result.replace(&placeholder, value);
-    }
-    result
+) -> Result<String, String> {
+    crate::template::render(input, variables, false)
 }
 
 pub async fn spawn_hidden_process(
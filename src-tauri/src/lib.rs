@@ -1,24 +1,56 @@
+mod adopt;
+mod builtins;
+mod command_variables;
 mod database;
+mod environments;
 mod executable;
+mod exit_watcher;
 mod generic_launcher;
 mod launcher;
 mod launcher_core;
 mod launcher_utils;
 mod monitor;
+mod orphans;
+mod pid_matcher;
+mod ports;
 mod process;
+mod process_snapshot;
 mod recovery;
+mod redact;
+mod run_context;
+mod secure_store;
+mod suspend;
+mod template;
+mod tool_template;
+mod tracked_processes;
+mod variable_types;
+mod variables;
 
 pub mod test_helpers;
 
+use adopt::adopt_process;
+use builtins::list_builtin_variables;
+use environments::clone_environment;
 use generic_launcher::{auto_launch_actions, spawn_process};
-use launcher::{launch_action, launch_workspace};
+use launcher::{launch_action, launch_workspace, render_launch_strings, restart_action};
 use monitor::get_system_metrics;
+use orphans::{find_orphaned_processes, stop_orphaned_processes, take_startup_orphans};
+use pid_matcher::test_pid_matcher;
+use ports::{check_ports, get_action_ports};
+use process_snapshot::are_processes_running;
+use tracked_processes::emit_reattached_processes;
+use suspend::{resume_action, suspend_action};
+use variable_types::check_variable_value;
+use variables::{resolve_variables, validate_variables};
 use executable::discover_executable;
 use process::{
-    find_server_process, get_process_identity, is_process_running, kill_process,
+    find_server_process, get_action_process_tree, get_process_identity, is_process_running, kill_process,
     register_tracked_pid_command, resolve_descendant_pid, verify_tracked_process,
 };
 use recovery::AllData;
+use secure_store::{
+    encrypt_secure_value, get_secure_store_status, rotate_secure_key, unlock_secure_store,
+};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tauri::Manager;
@@ -85,7 +117,7 @@ async fn reset_and_rescue(
 
     let rescued_data = if db_path.exists() {
         println!("Attempting to rescue data from database...");
-        match recovery::rescue_data(&db_path).await {
+        match recovery::rescue_data(&db_path, &app_local_data_dir).await {
             Ok(data) => {
                 println!("Data rescued successfully.");
                 Some(data)
@@ -108,6 +140,31 @@ async fn reset_and_rescue(
     rescued_data
 }
 
+/// Encrypts secure values left in plaintext by older versions. A store that
+/// is locked behind a passphrase is left alone until the next start.
+async fn encrypt_plaintext_secure_values(app_local_data_dir: &Path, db_path: &Path) {
+    if let Err(e) = secure_store::ensure_key(app_local_data_dir) {
+        eprintln!("Failed to set up secure store: {e}");
+        return;
+    }
+    if secure_store::status(app_local_data_dir).is_ok_and(|status| status.locked) {
+        return;
+    }
+    let pool = match database::connect(db_path).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to open database for secure values: {e}");
+            return;
+        }
+    };
+    match secure_store::encrypt_plaintext_values(app_local_data_dir, &pool).await {
+        Ok(0) => {}
+        Ok(count) => println!("Encrypted {count} plaintext secure values."),
+        Err(e) => eprintln!("Failed to encrypt secure values: {e}"),
+    }
+    pool.close().await;
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 #[tauri::command]
 fn schedule_db_reset(app_handle: tauri::AppHandle) -> Result<(), String> {
@@ -240,6 +297,10 @@ pub fn run() {
                             println!("Data restored successfully.");
                         }
                     }
+                    encrypt_plaintext_secure_values(&app_local_data_dir, &db_path).await;
+                    tracked_processes::restore_tracked_processes(&handle, &db_path).await;
+                    orphans::sweep_on_startup(&db_path).await;
+                    tracked_processes::start_persisting(db_path.clone());
                 }
 
                 handle.manage(db_init_state);
@@ -258,16 +319,39 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             launch_action,
+            restart_action,
             launch_workspace,
+            render_launch_strings,
             spawn_process,
             auto_launch_actions,
             kill_process,
             register_tracked_pid_command,
+            adopt_process,
+            emit_reattached_processes,
             is_process_running,
+            are_processes_running,
             get_process_identity,
+            get_action_process_tree,
             verify_tracked_process,
             resolve_descendant_pid,
             find_server_process,
+            test_pid_matcher,
+            check_ports,
+            get_action_ports,
+            find_orphaned_processes,
+            stop_orphaned_processes,
+            take_startup_orphans,
+            suspend_action,
+            resume_action,
+            resolve_variables,
+            validate_variables,
+            check_variable_value,
+            list_builtin_variables,
+            clone_environment,
+            encrypt_secure_value,
+            unlock_secure_store,
+            get_secure_store_status,
+            rotate_secure_key,
             discover_executable,
             get_system_metrics,
             schedule_db_reset,
@@ -280,11 +364,6 @@ pub fn run() {
 
 #[tauri::command]
 fn get_db_path(app_handle: tauri::AppHandle) -> Result<String, String> {
-    use tauri::Manager;
-    let app_local_data_dir = app_handle
-        .path()
-        .app_local_data_dir()
-        .map_err(|e| e.to_string())?;
-    let db_path = app_local_data_dir.join("workspacelauncher.db");
+    let db_path = database::app_db_path(&app_handle)?;
     Ok(db_path.to_string_lossy().to_string())
 }
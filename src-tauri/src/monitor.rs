@@ -1,3 +1,4 @@
+use crate::process_snapshot;
 use serde::Serialize;
 use sysinfo::{Pid, System};
 
@@ -21,9 +22,17 @@ pub struct ProcessSnapshot {
 
 #[tauri::command]
 pub async fn get_system_metrics(target_pid: Option<u32>) -> Result<MetricsSnapshot, String> {
-    let mut sys = System::new_all();
+    Ok(process_snapshot::query_system(process_snapshot::POLL_MAX_AGE, move |sys| {
+        snapshot(sys, target_pid)
+    })
+    .await)
+}
 
-    sys.refresh_all();
+fn snapshot(sys: &mut System, target_pid: Option<u32>) -> MetricsSnapshot {
+    // The shared `System` keeps the previous sample, so CPU usage is measured
+    // since the last call instead of reading as zero on a fresh instance.
+    sys.refresh_cpu_usage();
+    sys.refresh_memory();
 
     let cpu_usage = sys.global_cpu_usage();
     let total_memory = sys.total_memory();
@@ -41,12 +50,12 @@ pub async fn get_system_metrics(target_pid: Option<u32>) -> Result<MetricsSnapsh
         })
     });
 
-    Ok(MetricsSnapshot {
+    MetricsSnapshot {
         cpu_usage,
         total_memory,
         used_memory,
         total_swap,
         used_swap,
         process,
-    })
+    }
 }
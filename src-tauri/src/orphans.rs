@@ -0,0 +1,264 @@
+//! Every process an action spawns carries `WORKSPACELAUNCHER_RUN_ID` in its
+//! environment, and its children inherit it; only untracked detached
+//! launches, which are meant to outlive the launcher, go without it. When the
+//! launcher crashes the in-memory registries are lost, but the marker is not:
+//! any process that carries it and belongs to no run this instance still
+//! manages was leaked. Such processes are listed and only stopped once the
+//! user confirms the list.
+
+use crate::process;
+use crate::process_snapshot::{self, POLL_MAX_AGE};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use sysinfo::{Pid, Process, Signal, System};
+use tokio::time::{sleep, Duration};
+
+/// Environment variable holding the run a spawned process belongs to.
+pub const RUN_ID_ENV: &str = "WORKSPACELAUNCHER_RUN_ID";
+
+/// Setting that makes the launcher stop orphaned processes on startup.
+const SWEEP_ON_STARTUP_SETTING: &str = "sweep_orphans_on_startup";
+
+const TERMINATE_GRACE: Duration = Duration::from_secs(2);
+
+/// Orphans found at startup, kept until the UI asks for them via
+/// [`take_startup_orphans`].
+static STARTUP_ORPHANS: LazyLock<Mutex<Vec<OrphanedProcess>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub run_id: i64,
+    pub start_time_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrphanSweepResult {
+    pub orphans: Vec<OrphanedProcess>,
+    pub killed: Vec<u32>,
+    pub failed: Vec<u32>,
+}
+
+/// Reads the run id marker from a process environment.
+pub fn run_id_from_environ(environ: &[OsString]) -> Option<i64> {
+    environ.iter().find_map(|entry| {
+        entry
+            .to_str()?
+            .strip_prefix(RUN_ID_ENV)?
+            .strip_prefix('=')?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+fn orphan_of(system: &System, proc_info: &Process) -> Option<OrphanedProcess> {
+    let pid = proc_info.pid().as_u32();
+    if pid == std::process::id() || !process_snapshot::is_alive(proc_info) {
+        return None;
+    }
+    let run_id = run_id_from_environ(proc_info.environ())?;
+    if process::live_run_ids().contains(&run_id) || process::is_managed(system, pid) {
+        return None;
+    }
+    Some(OrphanedProcess {
+        pid,
+        name: proc_info.name().to_string_lossy().to_string(),
+        cmd: proc_info
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+        run_id,
+        start_time_secs: proc_info.start_time(),
+    })
+}
+
+/// Processes launched by WorkspaceLauncher that belong to no live run.
+pub async fn find_orphans() -> Vec<OrphanedProcess> {
+    let mut orphans: Vec<OrphanedProcess> = process_snapshot::query_environ(|system| {
+        system
+            .processes()
+            .values()
+            .filter_map(|proc_info| orphan_of(system, proc_info))
+            .collect()
+    })
+    .await;
+    orphans.sort_by_key(|orphan| (orphan.run_id, orphan.start_time_secs, orphan.pid));
+    orphans
+}
+
+/// Runs `f` on the orphan's process, re-read first, unless it has exited or
+/// its PID now belongs to another process.
+async fn with_same_process<T, F>(orphan: &OrphanedProcess, f: F) -> Option<T>
+where
+    F: FnOnce(&Process) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (pid, start_time_secs) = (orphan.pid, orphan.start_time_secs);
+    process_snapshot::query_refreshed(vec![pid], POLL_MAX_AGE, move |system| {
+        system
+            .process(Pid::from_u32(pid))
+            .filter(|proc_info| process_snapshot::is_alive(proc_info))
+            .filter(|proc_info| proc_info.start_time() == start_time_secs)
+            .map(f)
+    })
+    .await
+}
+
+async fn is_same_process(orphan: &OrphanedProcess) -> bool {
+    with_same_process(orphan, |_| ()).await.is_some()
+}
+
+async fn signal_orphan(orphan: &OrphanedProcess, signal: Signal) -> bool {
+    with_same_process(orphan, move |proc_info| proc_info.kill_with(signal))
+        .await
+        .flatten()
+        .unwrap_or(false)
+}
+
+/// Asks the orphan to terminate and kills it once the grace period is over.
+async fn kill_orphan(orphan: &OrphanedProcess) -> bool {
+    if signal_orphan(orphan, Signal::Term).await {
+        let mut waited = Duration::ZERO;
+        while waited < TERMINATE_GRACE && is_same_process(orphan).await {
+            sleep(Duration::from_millis(100)).await;
+            waited += Duration::from_millis(100);
+        }
+    }
+    if is_same_process(orphan).await {
+        signal_orphan(orphan, Signal::Kill).await;
+        sleep(Duration::from_millis(100)).await;
+    }
+    process_snapshot::invalidate();
+    !is_same_process(orphan).await
+}
+
+/// Stops the given orphans. Entries whose PID has exited or now belongs to
+/// another process are left alone and reported as failed.
+pub async fn stop_orphans(orphans: Vec<OrphanedProcess>) -> OrphanSweepResult {
+    let mut result = OrphanSweepResult::default();
+    for orphan in &orphans {
+        if kill_orphan(orphan).await {
+            result.killed.push(orphan.pid);
+        } else {
+            result.failed.push(orphan.pid);
+        }
+    }
+    result.orphans = orphans;
+    result
+}
+
+/// Lists processes launched by us that belong to no live run.
+#[tauri::command]
+pub async fn find_orphaned_processes() -> Result<Vec<OrphanedProcess>, String> {
+    Ok(find_orphans().await)
+}
+
+/// Stops orphans the user confirmed, as listed by [`find_orphaned_processes`]
+/// or [`take_startup_orphans`].
+#[tauri::command]
+pub async fn stop_orphaned_processes(
+    orphans: Vec<OrphanedProcess>,
+) -> Result<OrphanSweepResult, String> {
+    Ok(stop_orphans(orphans).await)
+}
+
+/// Returns the orphans found at startup, once. Called by the UI to ask the
+/// user whether to stop them.
+#[tauri::command]
+pub async fn take_startup_orphans() -> Result<Vec<OrphanedProcess>, String> {
+    Ok(STARTUP_ORPHANS
+        .lock()
+        .map(|mut orphans| std::mem::take(&mut *orphans))
+        .unwrap_or_default())
+}
+
+/// Startup step, run after tracked processes were re-attached: when the
+/// setting is enabled, looks for orphans left by a previous session and
+/// keeps them for [`take_startup_orphans`].
+pub async fn sweep_on_startup(db_path: &Path) {
+    let enabled = match crate::database::connect(db_path).await {
+        Ok(pool) => {
+            let value: Option<String> =
+                sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+                    .bind(SWEEP_ON_STARTUP_SETTING)
+                    .fetch_optional(&pool)
+                    .await
+                    .ok()
+                    .flatten();
+            pool.close().await;
+            value.is_some_and(|value| value.trim() == "true")
+        }
+        Err(e) => {
+            eprintln!("Failed to open database for orphan sweep: {e}");
+            false
+        }
+    };
+    if !enabled {
+        return;
+    }
+    let orphans = find_orphans().await;
+    if !orphans.is_empty() {
+        println!("Found {} leftover processes of a previous session.", orphans.len());
+    }
+    if let Ok(mut startup_orphans) = STARTUP_ORPHANS.lock() {
+        *startup_orphans = orphans;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_run_id_marker() {
+        let environ = |entries: &[&str]| -> Vec<OsString> {
+            entries.iter().map(OsString::from).collect()
+        };
+        assert_eq!(
+            run_id_from_environ(&environ(&["PATH=/bin", "WORKSPACELAUNCHER_RUN_ID=42"])),
+            Some(42)
+        );
+        assert_eq!(
+            run_id_from_environ(&environ(&["WORKSPACELAUNCHER_RUN_ID_X=1"])),
+            None
+        );
+        assert_eq!(run_id_from_environ(&environ(&["PATH=/bin"])), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn finds_and_kills_processes_of_dead_runs() {
+        let run_id = -46;
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .env(RUN_ID_ENV, run_id.to_string())
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id();
+
+        process::register_process_group(pid, run_id, pid as i32);
+        assert!(
+            !find_orphans().await.iter().any(|orphan| orphan.pid == pid),
+            "a process of a live run is not an orphan"
+        );
+        process::unregister_process_group(pid);
+
+        let orphan = find_orphans()
+            .await
+            .into_iter()
+            .find(|orphan| orphan.pid == pid)
+            .expect("orphan listed");
+        assert_eq!(orphan.run_id, run_id);
+
+        let result = stop_orphans(vec![orphan]).await;
+        let _ = child.wait();
+        assert_eq!(result.killed, vec![pid]);
+    }
+}
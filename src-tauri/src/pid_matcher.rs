@@ -0,0 +1,450 @@
+//! Per-action rules for picking the process to track after a launch. The
+//! default heuristics (skip known wrappers, prefer `expected_name`, else a
+//! leaf) guess wrong for launcher scripts that start a JVM or an Electron
+//! main process; a `pid_matcher` in the action config states the target
+//! explicitly. Every criterion that is set must hold.
+
+use crate::process::collect_filtered_descendants;
+use crate::process_snapshot::{self, is_alive};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use sysinfo::{Pid, Process, System};
+use tokio::time::{sleep, Duration, Instant};
+
+const DEFAULT_MAX_WAIT_MS: u64 = 10_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PidMatcher {
+    /// Case-insensitive regex matched against the process name.
+    pub name_regex: Option<String>,
+    /// Substring of the full command line, e.g. a main class or `--type=`.
+    pub cmdline_contains: Option<String>,
+    /// The process must run in this directory or below it.
+    pub cwd_prefix: Option<String>,
+    /// Full path of the executable, or its trailing components.
+    pub exe_path: Option<String>,
+    /// How long to wait for a matching process after the launch.
+    pub max_wait_ms: Option<u64>,
+}
+
+/// A matcher with its regex compiled, ready to be evaluated.
+#[derive(Clone)]
+pub struct CompiledPidMatcher {
+    name: Option<Regex>,
+    cmdline: Option<String>,
+    cwd_prefix: Option<String>,
+    exe_path: Option<String>,
+}
+
+/// A process considered by a matcher and the outcome of each criterion.
+#[derive(Debug, Clone, Serialize)]
+pub struct PidCandidate {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub exe: Option<String>,
+    pub cwd: Option<String>,
+    pub start_time_secs: u64,
+    /// Whether the process descends from the launched one.
+    pub descendant: bool,
+    pub matched: bool,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PidMatcherReport {
+    pub selected: Option<u32>,
+    pub candidates: Vec<PidCandidate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestPidMatcherRequest {
+    pub matcher: PidMatcher,
+    /// Only consider this process and processes started from it; when unset,
+    /// every running process is a candidate.
+    pub parent_pid: Option<u32>,
+    pub started_after_secs: Option<u64>,
+}
+
+/// Lowercased with `/` separators and no trailing separator, so paths compare
+/// the same on every platform.
+fn normalize_path(path: &str) -> String {
+    path.to_lowercase()
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+impl PidMatcher {
+    pub fn from_config(config: &Value) -> Result<Option<Self>, String> {
+        let Some(value) = config.get("pid_matcher").filter(|v| !v.is_null()) else {
+            return Ok(None);
+        };
+        let matcher: PidMatcher = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid pid_matcher: {}", e))?;
+        matcher.compile()?;
+        Ok(Some(matcher))
+    }
+
+    pub fn compile(&self) -> Result<CompiledPidMatcher, String> {
+        let name = non_empty(&self.name_regex)
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("Invalid pid_matcher.name_regex: {}", e))
+            })
+            .transpose()?;
+        let compiled = CompiledPidMatcher {
+            name,
+            cmdline: non_empty(&self.cmdline_contains).map(str::to_lowercase),
+            cwd_prefix: non_empty(&self.cwd_prefix).map(normalize_path),
+            exe_path: non_empty(&self.exe_path).map(normalize_path),
+        };
+        if compiled.is_empty() {
+            return Err(
+                "pid_matcher needs at least one of name_regex, cmdline_contains, cwd_prefix or exe_path"
+                    .to_string(),
+            );
+        }
+        Ok(compiled)
+    }
+
+    pub fn max_wait(&self) -> Duration {
+        Duration::from_millis(self.max_wait_ms.unwrap_or(DEFAULT_MAX_WAIT_MS))
+    }
+}
+
+impl CompiledPidMatcher {
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.cmdline.is_none()
+            && self.cwd_prefix.is_none()
+            && self.exe_path.is_none()
+    }
+
+    /// Evaluates every criterion, returning whether all held and why.
+    pub fn evaluate(
+        &self,
+        name: &str,
+        cmd: &[String],
+        exe: Option<&str>,
+        cwd: Option<&str>,
+    ) -> (bool, Vec<String>) {
+        let mut matched = true;
+        let mut reasons = Vec::new();
+        let mut check = |ok: bool, reason: String| {
+            matched &= ok;
+            reasons.push(reason);
+        };
+
+        if let Some(regex) = &self.name {
+            let ok = regex.is_match(name);
+            check(
+                ok,
+                format!(
+                    "name {:?} {} /{}/",
+                    name,
+                    if ok { "matches" } else { "does not match" },
+                    regex.as_str()
+                ),
+            );
+        }
+        if let Some(needle) = &self.cmdline {
+            let ok = cmd.join(" ").to_lowercase().contains(needle.as_str());
+            check(
+                ok,
+                format!(
+                    "command line {} {:?}",
+                    if ok { "contains" } else { "does not contain" },
+                    needle
+                ),
+            );
+        }
+        if let Some(prefix) = &self.cwd_prefix {
+            let (ok, reason) = match cwd {
+                Some(cwd) => {
+                    let cwd_norm = normalize_path(cwd);
+                    let ok = cwd_norm == *prefix || cwd_norm.starts_with(&format!("{}/", prefix));
+                    let verdict = if ok { "is under" } else { "is not under" };
+                    (ok, format!("cwd {:?} {} {:?}", cwd, verdict, prefix))
+                }
+                None => (false, "cwd is not readable".to_string()),
+            };
+            check(ok, reason);
+        }
+        if let Some(path) = &self.exe_path {
+            let (ok, reason) = match exe {
+                Some(exe) => {
+                    let exe_norm = normalize_path(exe);
+                    let ok = exe_norm == *path || exe_norm.ends_with(&format!("/{}", path));
+                    let verdict = if ok { "matches" } else { "does not match" };
+                    (ok, format!("executable {:?} {} {:?}", exe, verdict, path))
+                }
+                None => (false, "executable path is not readable".to_string()),
+            };
+            check(ok, reason);
+        }
+        (matched, reasons)
+    }
+
+    fn candidate(&self, proc_info: &Process, descendant: bool) -> PidCandidate {
+        let name = proc_info.name().to_string_lossy().to_string();
+        let cmd: Vec<String> = proc_info
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let exe = proc_info.exe().map(|exe| exe.to_string_lossy().to_string());
+        let cwd = proc_info.cwd().map(|cwd| cwd.to_string_lossy().to_string());
+        let (matched, reasons) = self.evaluate(&name, &cmd, exe.as_deref(), cwd.as_deref());
+        PidCandidate {
+            pid: proc_info.pid().as_u32(),
+            parent_pid: proc_info.parent().map(Pid::as_u32),
+            name,
+            cmd,
+            exe,
+            cwd,
+            start_time_secs: proc_info.start_time(),
+            descendant,
+            matched,
+            reasons,
+        }
+    }
+}
+
+/// Evaluates `matcher` with fresh command lines, executables and working
+/// directories. They are otherwise read once per process, which can be from
+/// before the process exec'd into the program we are looking for.
+async fn evaluate_fresh(
+    matcher: &CompiledPidMatcher,
+    parent_pid: Option<u32>,
+    started_after_secs: Option<u64>,
+) -> PidMatcherReport {
+    let matcher = matcher.clone();
+    process_snapshot::query_fresh_commands(move |system| {
+        evaluate_candidates(system, &matcher, parent_pid, started_after_secs)
+    })
+    .await
+}
+
+/// Evaluates `matcher` against the launched process and its descendants, and
+/// against processes started since the launch that were re-parented away
+/// from it (daemonizing launchers). Without a parent every live process is a
+/// candidate.
+pub fn evaluate_candidates(
+    system: &System,
+    matcher: &CompiledPidMatcher,
+    parent_pid: Option<u32>,
+    started_after_secs: Option<u64>,
+) -> PidMatcherReport {
+    let threshold = started_after_secs.unwrap_or(0);
+    let mut candidates = Vec::new();
+    let mut seen = HashSet::new();
+    if let Some(parent) = parent_pid {
+        let parent = Pid::from_u32(parent);
+        let tree = system
+            .process(parent)
+            .into_iter()
+            .chain(collect_filtered_descendants(system, parent, &[]));
+        for proc_info in tree.filter(|p| is_alive(p)) {
+            seen.insert(proc_info.pid());
+            candidates.push(matcher.candidate(proc_info, true));
+        }
+    }
+    if parent_pid.is_none() || started_after_secs.is_some() {
+        for proc_info in system.processes().values() {
+            if seen.contains(&proc_info.pid())
+                || !is_alive(proc_info)
+                || proc_info.start_time() < threshold
+                || proc_info.pid().as_u32() == std::process::id()
+            {
+                continue;
+            }
+            candidates.push(matcher.candidate(proc_info, false));
+        }
+    }
+
+    // Descendants first, then the oldest match: the main process of an app
+    // starts before the helpers it spawns.
+    candidates.sort_by_key(|c| (!c.matched, !c.descendant, c.start_time_secs, c.pid));
+    let selected = candidates.first().filter(|c| c.matched).map(|c| c.pid);
+    PidMatcherReport {
+        selected,
+        candidates,
+    }
+}
+
+/// Waits up to the matcher's `max_wait_ms` for `parent_pid` or one of its
+/// descendants to match. Only when none did are processes started since the
+/// launch outside its tree considered, so an unrelated process that happens
+/// to match cannot win over a descendant that is slow to start.
+pub async fn resolve_with_matcher(
+    matcher: &PidMatcher,
+    parent_pid: u32,
+    started_after_secs: u64,
+) -> Option<u32> {
+    let compiled = matcher.compile().ok()?;
+    let deadline = Instant::now() + matcher.max_wait();
+    loop {
+        let report = evaluate_fresh(&compiled, Some(parent_pid), None).await;
+        if report.selected.is_some() {
+            return report.selected;
+        }
+        if Instant::now() >= deadline {
+            return evaluate_fresh(&compiled, Some(parent_pid), Some(started_after_secs))
+                .await
+                .selected;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Shows which running processes a matcher would pick and why, so matchers
+/// can be tried out before saving them.
+#[tauri::command]
+pub async fn test_pid_matcher(req: TestPidMatcherRequest) -> Result<PidMatcherReport, String> {
+    let compiled = req.matcher.compile()?;
+    Ok(evaluate_fresh(&compiled, req.parent_pid, req.started_after_secs).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_and_validates_config() {
+        assert_eq!(PidMatcher::from_config(&serde_json::json!({})), Ok(None));
+        let matcher = PidMatcher::from_config(&serde_json::json!({
+            "pid_matcher": { "name_regex": "^java", "max_wait_ms": 500 }
+        }))
+        .unwrap()
+        .expect("matcher");
+        assert_eq!(matcher.max_wait(), Duration::from_millis(500));
+
+        let err = PidMatcher::from_config(&serde_json::json!({
+            "pid_matcher": { "name_regex": "(" }
+        }))
+        .unwrap_err();
+        assert!(err.contains("name_regex"), "{}", err);
+        assert!(PidMatcher::from_config(&serde_json::json!({
+            "pid_matcher": { "max_wait_ms": 500 }
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn evaluates_every_criterion() {
+        let matcher = PidMatcher {
+            name_regex: Some("^java(w)?(\\.exe)?$".to_string()),
+            cmdline_contains: Some("com.example.Main".to_string()),
+            cwd_prefix: Some("C:\\Work\\App\\".to_string()),
+            exe_path: Some("bin/java.exe".to_string()),
+            max_wait_ms: None,
+        }
+        .compile()
+        .unwrap();
+
+        let java = cmd(&["java", "-cp", "app.jar", "com.example.Main"]);
+        let (matched, reasons) = matcher.evaluate(
+            "java.exe",
+            &java,
+            Some("C:\\Program Files\\Java\\bin\\java.exe"),
+            Some("c:/work/app/server"),
+        );
+        assert!(matched, "{:?}", reasons);
+        assert_eq!(reasons.len(), 4);
+
+        let (matched, reasons) = matcher.evaluate(
+            "cmd.exe",
+            &cmd(&["cmd", "/c", "run.bat"]),
+            None,
+            Some("C:\\Work\\App"),
+        );
+        assert!(!matched);
+        assert!(reasons[0].contains("does not match"), "{:?}", reasons);
+        assert!(reasons[1].contains("does not contain"), "{:?}", reasons);
+        assert!(reasons[2].contains("is under"), "{:?}", reasons);
+        assert_eq!(reasons[3], "executable path is not readable");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn resolves_the_matching_descendant() {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "sleep 31 & sleep 32 & wait"])
+            .spawn()
+            .expect("spawn script");
+        let parent = child.id().expect("pid");
+        // The shell's own command line mentions both sleeps, so the name has
+        // to rule it out.
+        let matcher = PidMatcher {
+            name_regex: Some("^sleep$".to_string()),
+            cmdline_contains: Some("32".to_string()),
+            max_wait_ms: Some(3000),
+            ..PidMatcher::default()
+        };
+
+        let pid = resolve_with_matcher(&matcher, parent, 0)
+            .await
+            .expect("matching process");
+        let report = test_pid_matcher(TestPidMatcherRequest {
+            matcher,
+            parent_pid: Some(parent),
+            started_after_secs: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(report.selected, Some(pid));
+        let shell = report
+            .candidates
+            .iter()
+            .find(|c| c.pid == parent)
+            .expect("the launched process is a candidate");
+        assert!(!shell.matched);
+
+        let _ = child.kill().await;
+        let _ = nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGKILL,
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn takes_other_processes_only_after_the_wait() {
+        let mut launched = tokio::process::Command::new("sleep")
+            .arg("35")
+            .spawn()
+            .expect("spawn launched process");
+        let mut unrelated = tokio::process::Command::new("sleep")
+            .arg("36")
+            .spawn()
+            .expect("spawn unrelated process");
+        let matcher = PidMatcher {
+            cmdline_contains: Some("sleep 36".to_string()),
+            max_wait_ms: Some(600),
+            ..PidMatcher::default()
+        };
+
+        let started = Instant::now();
+        let pid = resolve_with_matcher(&matcher, launched.id().expect("pid"), 0).await;
+        assert_eq!(pid, unrelated.id());
+        assert!(started.elapsed() >= Duration::from_millis(600));
+
+        let _ = launched.kill().await;
+        let _ = unrelated.kill().await;
+    }
+}
@@ -0,0 +1,480 @@
+use crate::process::{self, ProcessTreeRequest};
+use crate::process_snapshot::{self, POLL_MAX_AGE};
+use crate::tracked_processes::TrackedProcess;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+use tauri::{AppHandle, Emitter};
+use tokio::time::{sleep, Duration};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: String,
+    pub launched_by_us: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortStatus {
+    pub port: u16,
+    pub available: bool,
+    pub owner: Option<PortOwner>,
+}
+
+/// A socket entry parsed from `/proc/net/{tcp,tcp6,udp,udp6}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketEntry {
+    pub local_port: u16,
+    pub state: u8,
+    pub inode: u64,
+}
+
+pub const TCP_LISTEN: u8 = 0x0A;
+/// State of a bound but unconnected UDP socket (`TCP_CLOSE` in the kernel).
+pub const UDP_UNCONNECTED: u8 = 0x07;
+
+/// How often running actions are checked for new listening sockets.
+const PORT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A socket a process of an action is listening on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ListeningSocket {
+    pub pid: u32,
+    /// `tcp`, `tcp6`, `udp` or `udp6`, after the `/proc/net` table it came from.
+    pub protocol: String,
+    pub port: u16,
+}
+
+pub fn parse_proc_net(contents: &str) -> Vec<SocketEntry> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local = fields.get(1)?;
+            let port_hex = local.rsplit(':').next()?;
+            let local_port = u16::from_str_radix(port_hex, 16).ok()?;
+            let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some(SocketEntry {
+                local_port,
+                state,
+                inode,
+            })
+        })
+        .collect()
+}
+
+/// Whether nothing listens on `port` on any IPv4 or IPv6 address. A test
+/// bind only counts as a conflict when the address is in use, so hosts
+/// without IPv6 do not report every port as taken.
+pub fn is_port_free(port: u16) -> bool {
+    if has_listener(port) {
+        return false;
+    }
+    let addresses: [IpAddr; 4] = [
+        Ipv4Addr::UNSPECIFIED.into(),
+        Ipv4Addr::LOCALHOST.into(),
+        Ipv6Addr::UNSPECIFIED.into(),
+        Ipv6Addr::LOCALHOST.into(),
+    ];
+    addresses.iter().all(|ip| {
+        !matches!(
+            TcpListener::bind(SocketAddr::from((*ip, port))),
+            Err(e) if e.kind() == ErrorKind::AddrInUse
+        )
+    })
+}
+
+/// Catches listeners bound to a specific interface address, which the test
+/// binds in [`is_port_free`] do not conflict with.
+#[cfg(target_os = "linux")]
+fn has_listener(port: u16) -> bool {
+    !listening_inodes(port).is_empty()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn has_listener(_port: u16) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn listening_inodes(port: u16) -> Vec<u64> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|contents| parse_proc_net(&contents))
+        .filter(|entry| entry.local_port == port && entry.state == TCP_LISTEN && entry.inode != 0)
+        .map(|entry| entry.inode)
+        .collect()
+}
+
+/// Returns the PID whose file descriptors include one of `inodes`.
+#[cfg(target_os = "linux")]
+pub fn find_pid_by_socket_inodes(inodes: &[u64]) -> Option<u32> {
+    if inodes.is_empty() {
+        return None;
+    }
+    let targets: Vec<String> = inodes.iter().map(|i| format!("socket:[{}]", i)).collect();
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if let Ok(link) = std::fs::read_link(fd.path()) {
+                if targets.iter().any(|t| link.as_os_str() == t.as_str()) {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The socket inodes among the open file descriptors of `pid`.
+#[cfg(target_os = "linux")]
+fn socket_inodes(pid: u32) -> Vec<u64> {
+    let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    fds.flatten()
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+        .filter_map(|link| {
+            link.to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// Listening TCP and bound UDP sockets owned by any of `pids`, found by
+/// matching the socket inodes of their descriptors against `/proc/net`.
+#[cfg(target_os = "linux")]
+pub fn listening_sockets(pids: &[u32]) -> Vec<ListeningSocket> {
+    let owners: HashMap<u64, u32> = pids
+        .iter()
+        .flat_map(|&pid| {
+            socket_inodes(pid)
+                .into_iter()
+                .map(move |inode| (inode, pid))
+        })
+        .collect();
+    if owners.is_empty() {
+        return Vec::new();
+    }
+    let mut sockets: Vec<ListeningSocket> = [
+        ("tcp", TCP_LISTEN),
+        ("tcp6", TCP_LISTEN),
+        ("udp", UDP_UNCONNECTED),
+        ("udp6", UDP_UNCONNECTED),
+    ]
+    .iter()
+    .flat_map(|&(protocol, listen_state)| {
+        let contents =
+            std::fs::read_to_string(format!("/proc/net/{}", protocol)).unwrap_or_default();
+        parse_proc_net(&contents)
+            .into_iter()
+            .filter(move |entry| entry.state == listen_state && entry.local_port != 0)
+            .filter_map(|entry| {
+                Some(ListeningSocket {
+                    pid: *owners.get(&entry.inode)?,
+                    protocol: protocol.to_string(),
+                    port: entry.local_port,
+                })
+            })
+            .collect::<Vec<_>>()
+    })
+    .collect();
+    sockets.sort_by(|a, b| (a.port, &a.protocol, a.pid).cmp(&(b.port, &b.protocol, b.pid)));
+    sockets.dedup();
+    sockets
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn listening_sockets(_pids: &[u32]) -> Vec<ListeningSocket> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn find_port_owner_pid(port: u16) -> Option<u32> {
+    find_pid_by_socket_inodes(&listening_inodes(port))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn find_port_owner_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+pub fn find_port_owner(port: u16) -> Option<PortOwner> {
+    let pid = find_port_owner_pid(port)?;
+    let name = process_snapshot::with_process(pid, POLL_MAX_AGE, |p| {
+        p.map(|p| p.name().to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let launched_by_us =
+        process_snapshot::with_processes(POLL_MAX_AGE, |system| process::is_managed(system, pid));
+    Some(PortOwner {
+        pid,
+        name,
+        launched_by_us,
+    })
+}
+
+pub fn port_status(port: u16) -> PortStatus {
+    if is_port_free(port) {
+        return PortStatus {
+            port,
+            available: true,
+            owner: None,
+        };
+    }
+    PortStatus {
+        port,
+        available: false,
+        owner: find_port_owner(port),
+    }
+}
+
+fn describe_conflict(status: &PortStatus) -> String {
+    match &status.owner {
+        Some(owner) if owner.launched_by_us => format!(
+            "Port {} is already in use by {} (PID {}), started by WorkspaceLauncher. Stop it first or enable kill_port_owner.",
+            status.port, owner.name, owner.pid
+        ),
+        Some(owner) => format!(
+            "Port {} is already in use by {} (PID {}). Stop that process or change the port.",
+            status.port, owner.name, owner.pid
+        ),
+        None => format!(
+            "Port {} is already in use by another process. Stop that process or change the port.",
+            status.port
+        ),
+    }
+}
+
+pub fn ports_from_config(config: &serde_json::Value) -> Result<Vec<u16>, String> {
+    let Some(value) = config.get("ports") else {
+        return Ok(vec![]);
+    };
+    if value.is_null() {
+        return Ok(vec![]);
+    }
+    let entries = value.as_array().ok_or("ports must be an array of port numbers")?;
+    entries
+        .iter()
+        .map(|entry| {
+            entry
+                .as_u64()
+                .or_else(|| entry.as_str().and_then(|s| s.trim().parse().ok()))
+                .and_then(|port| u16::try_from(port).ok())
+                .filter(|port| *port > 0)
+                .ok_or_else(|| format!("Invalid port in ports: {}", entry))
+        })
+        .collect()
+}
+
+/// Checks the ports an action declares before it is spawned. Owners launched
+/// by WorkspaceLauncher are stopped when `kill_owner` is set; any other
+/// conflict fails with a message naming the owning process.
+pub async fn ensure_ports_available(ports: &[u16], kill_owner: bool) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for &port in ports {
+        let status = port_status(port);
+        if status.available {
+            continue;
+        }
+        match &status.owner {
+            Some(owner) if kill_owner && owner.launched_by_us => {
+                let result = crate::process::kill_process(owner.pid).await?;
+                if !result.success || !wait_for_port_free(port, 2000).await {
+                    errors.push(format!(
+                        "Port {} is still in use after stopping {} (PID {}): {}",
+                        port, owner.name, owner.pid, result.message
+                    ));
+                }
+            }
+            _ => errors.push(describe_conflict(&status)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+async fn wait_for_port_free(port: u16, max_wait_ms: u64) -> bool {
+    let mut waited = 0;
+    while !is_port_free(port) {
+        if waited >= max_wait_ms {
+            return false;
+        }
+        sleep(Duration::from_millis(100)).await;
+        waited += 100;
+    }
+    true
+}
+
+#[tauri::command]
+pub async fn check_ports(ports: Vec<u16>) -> Result<Vec<PortStatus>, String> {
+    Ok(ports.into_iter().map(port_status).collect())
+}
+
+/// The sockets the process tree of a run (`run_id`) or process (`pid`) is
+/// listening on, e.g. the port a dev server actually bound.
+#[tauri::command]
+pub async fn get_action_ports(req: ProcessTreeRequest) -> Result<Vec<ListeningSocket>, String> {
+    let tree = crate::process::get_action_process_tree(req).await?;
+    Ok(tree
+        .map(|tree| listening_sockets(&tree.pids()))
+        .unwrap_or_default())
+}
+
+/// Sockets in `current` that were not in `previous`.
+pub fn new_listeners(
+    previous: &[ListeningSocket],
+    current: &[ListeningSocket],
+) -> Vec<ListeningSocket> {
+    current
+        .iter()
+        .filter(|socket| !previous.contains(socket))
+        .cloned()
+        .collect()
+}
+
+/// Polls the tree of a tracked action while it runs and emits
+/// `action-ports-changed` whenever it starts listening on a new socket.
+pub fn watch_ports(app: AppHandle, tracked: TrackedProcess) {
+    let (Some(action_id), Some(workspace_id)) = (tracked.action_id, tracked.workspace_id) else {
+        return;
+    };
+    if cfg!(not(target_os = "linux")) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let req = || ProcessTreeRequest {
+            run_id: tracked.run_id,
+            pid: tracked.run_id.is_none().then_some(tracked.identity.pid),
+        };
+        let mut known: Vec<ListeningSocket> = Vec::new();
+        while crate::exit_watcher::still_running(&tracked.identity).await {
+            let current = get_action_ports(req()).await.unwrap_or_default();
+            let added = new_listeners(&known, &current);
+            if !added.is_empty() {
+                let _ = app.emit(
+                    "action-ports-changed",
+                    serde_json::json!({
+                        "action_id": action_id,
+                        "workspace_id": workspace_id,
+                        "run_id": tracked.run_id,
+                        "process_id": tracked.identity.pid,
+                        "ports": current,
+                        "added": added,
+                    }),
+                );
+            }
+            known = current;
+            sleep(PORT_POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:D2F0 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 0 1 0000000000000000 20 4 30 10 -1
+";
+
+    #[test]
+    fn parses_proc_net_tcp() {
+        let entries = parse_proc_net(SAMPLE_TCP);
+        assert_eq!(
+            entries[0],
+            SocketEntry {
+                local_port: 8080,
+                state: TCP_LISTEN,
+                inode: 12345
+            }
+        );
+        assert_eq!(entries[1].local_port, 54000);
+        assert_eq!(entries[1].state, 0x01);
+    }
+
+    #[test]
+    fn parses_ports_from_config() {
+        let config = serde_json::json!({ "ports": [5173, "8080"] });
+        assert_eq!(ports_from_config(&config).unwrap(), vec![5173, 8080]);
+        assert!(ports_from_config(&serde_json::json!({})).unwrap().is_empty());
+        assert!(ports_from_config(&serde_json::json!({ "ports": [70000] })).is_err());
+    }
+
+    #[tokio::test]
+    async fn detects_taken_port_and_owner() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+
+        let status = port_status(port);
+        assert!(!status.available);
+        #[cfg(target_os = "linux")]
+        {
+            let owner = status.owner.expect("owner should be resolved");
+            assert_eq!(owner.pid, std::process::id());
+            assert!(!owner.launched_by_us);
+        }
+
+        let err = ensure_ports_available(&[port], true).await.unwrap_err();
+        assert!(err.contains(&format!("Port {} is already in use", port)), "{}", err);
+
+        drop(listener);
+        assert!(ensure_ports_available(&[port], false).await.is_ok());
+    }
+
+    #[test]
+    fn detects_ipv6_only_listener() {
+        // Hosts without IPv6 cannot run this check.
+        let Ok(listener) = TcpListener::bind("[::1]:0") else {
+            return;
+        };
+        let port = listener.local_addr().expect("addr").port();
+        assert!(!is_port_free(port));
+        drop(listener);
+        assert!(is_port_free(port));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_listening_sockets_of_a_process() {
+        let tcp = TcpListener::bind("127.0.0.1:0").expect("bind tcp");
+        let tcp_port = tcp.local_addr().expect("addr").port();
+        let udp = std::net::UdpSocket::bind("127.0.0.1:0").expect("bind udp");
+        let udp_port = udp.local_addr().expect("addr").port();
+
+        let sockets = listening_sockets(&[std::process::id()]);
+        let tcp_socket = ListeningSocket {
+            pid: std::process::id(),
+            protocol: "tcp".to_string(),
+            port: tcp_port,
+        };
+        assert!(sockets.contains(&tcp_socket), "{:?}", sockets);
+        assert!(
+            sockets.iter().any(|s| s.protocol == "udp" && s.port == udp_port),
+            "{:?}",
+            sockets
+        );
+        assert!(listening_sockets(&[u32::MAX - 7]).is_empty());
+
+        let previous = vec![tcp_socket.clone()];
+        let added = new_listeners(&previous, &sockets);
+        assert!(!added.contains(&tcp_socket));
+        assert!(added.iter().any(|s| s.port == udp_port));
+    }
+}
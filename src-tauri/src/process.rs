@@ -1,17 +1,33 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{LazyLock, Mutex};
-use sysinfo::{Pid, Process, ProcessesToUpdate, System};
+use crate::process_snapshot::{self, is_alive, POLL_MAX_AGE};
+use sysinfo::{Pid, Process, ProcessStatus, System};
 use tokio::time::{sleep, Duration};
 
-static TRACKED_PIDS: LazyLock<Mutex<HashSet<u32>>> =
-    LazyLock::new(|| Mutex::new(HashSet::new()));
+/// Tracked PIDs and the identity of the process that used the PID when it
+/// was tracked (`None` if it had already exited). Signals are only sent while
+/// the live process still has that identity, so a reused PID is never hit.
+static TRACKED_PIDS: LazyLock<Mutex<HashMap<u32, Option<ProcessIdentity>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Tracks `pid` together with the identity of the process now using it, and
+/// returns that identity unless the process is already gone.
+pub fn register_tracked_pid(pid: u32) -> Option<ProcessIdentity> {
+    if pid == 0 {
+        return None;
+    }
+    let identity = current_identity(pid);
+    if let Ok(mut pids) = TRACKED_PIDS.lock() {
+        pids.insert(pid, identity.clone());
+    }
+    identity
+}
 
-pub fn register_tracked_pid(pid: u32) {
-    if pid > 0 {
-        if let Ok(mut pids) = TRACKED_PIDS.lock() {
-            pids.insert(pid);
-        }
+/// Tracks a process whose identity was already verified, e.g. on restore.
+pub fn register_tracked_identity(identity: ProcessIdentity) {
+    if let Ok(mut pids) = TRACKED_PIDS.lock() {
+        pids.insert(identity.pid, Some(identity));
     }
 }
 
@@ -21,18 +37,557 @@ pub fn unregister_tracked_pid(pid: u32) {
     }
 }
 
+/// The most recent tracked process started for an action, used to decide
+/// whether launching the action again would start a second copy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionInstance {
+    pub action_id: i64,
+    pub run_id: i64,
+    pub identity: ProcessIdentity,
+}
+
+static ACTION_INSTANCES: LazyLock<Mutex<HashMap<i64, ActionInstance>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn insert_action_instance(instance: ActionInstance) {
+    if let Ok(mut instances) = ACTION_INSTANCES.lock() {
+        instances.insert(instance.action_id, instance);
+    }
+}
+
+/// Returns the action's previous instance if it is still tracked and the live
+/// process still matches the recorded identity. Stale entries are dropped.
+pub async fn find_running_action_instance(action_id: i64) -> Option<ActionInstance> {
+    let instance = ACTION_INSTANCES
+        .lock()
+        .ok()
+        .and_then(|instances| instances.get(&action_id).cloned())?;
+
+    let pid = instance.identity.pid;
+    let alive = is_pid_registered(pid)
+        && verify_tracked_process(VerifyTrackedProcessRequest {
+            pid,
+            expected_start_time_secs: Some(instance.identity.start_time_secs),
+            expected_name: Some(instance.identity.name.clone()),
+        })
+        .await
+        .unwrap_or(false);
+
+    if alive {
+        return Some(instance);
+    }
+    if let Ok(mut instances) = ACTION_INSTANCES.lock() {
+        if instances.get(&action_id).map(|i| i.identity.pid) == Some(pid) {
+            instances.remove(&action_id);
+        }
+    }
+    None
+}
+
+/// Process group each action was spawned into, keyed by the tracked PID
+/// (which may be a descendant of the group leader). Stopping a tracked PID
+/// signals the whole group so double-forked and reparented children are
+/// included.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessGroup {
+    pub pgid: i32,
+    pub run_id: i64,
+}
+
+static PROCESS_GROUPS: LazyLock<Mutex<HashMap<u32, ProcessGroup>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn register_process_group(pid: u32, run_id: i64, pgid: i32) {
+    if pid == 0 || pgid <= 0 {
+        return;
+    }
+    if let Ok(mut groups) = PROCESS_GROUPS.lock() {
+        groups.insert(pid, ProcessGroup { pgid, run_id });
+    }
+    crate::tracked_processes::record_process_group(pid, pgid);
+}
+
+pub fn unregister_process_group(pid: u32) {
+    if let Ok(mut groups) = PROCESS_GROUPS.lock() {
+        groups.remove(&pid);
+    }
+}
+
+pub fn process_group_for_pid(pid: u32) -> Option<ProcessGroup> {
+    PROCESS_GROUPS
+        .lock()
+        .ok()
+        .and_then(|groups| groups.get(&pid).copied())
+}
+
+pub fn process_group_for_run(run_id: i64) -> Option<i32> {
+    PROCESS_GROUPS.lock().ok().and_then(|groups| {
+        groups
+            .values()
+            .find(|group| group.run_id == run_id)
+            .map(|group| group.pgid)
+    })
+}
+
+/// Stops the process group a run's (possibly untracked) process was spawned
+/// into. `None` if no group is recorded for `run_id`.
+pub async fn stop_run_group(run_id: i64) -> Option<Result<KillProcessResult, String>> {
+    let (pid, group) = PROCESS_GROUPS.lock().ok().and_then(|groups| {
+        groups
+            .iter()
+            .find(|(_, group)| group.run_id == run_id)
+            .map(|(pid, group)| (*pid, *group))
+    })?;
+    #[cfg(unix)]
+    {
+        let options = stop_options_for(pid);
+        Some(kill_process_group(pid, group.pgid, &options).await)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (pid, group);
+        None
+    }
+}
+
+/// Live (non-zombie) members of a process group, read from `/proc`.
+#[cfg(target_os = "linux")]
+pub fn live_group_members(pgid: i32) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+                return false;
+            };
+            // Fields after the parenthesised command name: state ppid pgrp ...
+            let Some(rest) = stat.rfind(')').map(|i| &stat[i + 1..]) else {
+                return false;
+            };
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            fields.first() != Some(&"Z") && fields.get(2).and_then(|g| g.parse().ok()) == Some(pgid)
+        })
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn group_is_alive(pgid: i32) -> bool {
+    nix::sys::signal::killpg(nix::unistd::Pid::from_raw(pgid), None).is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn group_is_alive(pgid: i32) -> bool {
+    !live_group_members(pgid).is_empty()
+}
+
+/// How long the tracked process may take to vanish after its group is gone.
+#[cfg(unix)]
+const GROUP_KILL_SETTLE: Duration = Duration::from_millis(200);
+
+#[cfg(unix)]
+async fn kill_process_group(
+    pid: u32,
+    pgid: i32,
+    options: &StopOptions,
+) -> Result<KillProcessResult, String> {
+    use nix::errno::Errno;
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid as UnixPid;
+
+    let group = UnixPid::from_raw(pgid);
+    match killpg(group, options.signal.as_nix()) {
+        Ok(()) => {}
+        Err(Errno::ESRCH) if pid_is_alive(pid).await => {
+            return kill_process_tree(pid, options).await
+        }
+        Err(Errno::ESRCH) => {
+            forget_process(pid);
+            return Ok(KillProcessResult {
+                success: true,
+                message: format!("Process {} already terminated", pid),
+                denied: false,
+                identity_mismatch: false,
+            });
+        }
+        Err(Errno::EPERM) => {
+            return Ok(KillProcessResult {
+                success: false,
+                denied: true,
+                identity_mismatch: false,
+                message: format!(
+                    "Failed to kill process group {} of process {}. The process may require elevated permissions.",
+                    pgid, pid
+                ),
+            });
+        }
+        Err(e) => return Err(format!("Failed to signal process group {}: {}", pgid, e)),
+    }
+
+    let exited = wait_until(options.grace_period, || async move { !group_is_alive(pgid) }).await;
+    if !exited {
+        let _ = killpg(group, Signal::SIGKILL);
+    }
+    // A tracked process that moved to its own session or group (setsid) is
+    // not reached by killpg; stop it and its tree directly.
+    if !wait_until(GROUP_KILL_SETTLE, || async move { !pid_is_alive(pid).await }).await {
+        return kill_process_tree(pid, options).await;
+    }
+
+    forget_process(pid);
+    Ok(KillProcessResult {
+        success: true,
+        message: if exited {
+            format!("Process {} terminated (process group {})", pid, pgid)
+        } else {
+            format!(
+                "Process {} killed after {} ms grace period (process group {})",
+                pid,
+                options.grace_period.as_millis(),
+                pgid
+            )
+        },
+        denied: false,
+        identity_mismatch: false,
+    })
+}
+
+/// Signals `pid` and its descendants one by one, for processes without a
+/// recorded group (e.g. registered by the frontend) or that left theirs.
+#[cfg(unix)]
+async fn kill_process_tree(
+    pid: u32,
+    options: &StopOptions,
+) -> Result<KillProcessResult, String> {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid as UnixPid;
+
+    // Walk the tree via parent links. Each process is signalled only while
+    // it still has the identity it had when the tree was collected.
+    let root = Pid::from_u32(pid);
+    let to_kill = process_snapshot::query_refreshed(vec![pid], POLL_MAX_AGE, move |system| {
+        let mut to_kill: Vec<Pid> = vec![root];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (p_pid, proc_info) in system.processes() {
+                if let Some(parent) = proc_info.parent() {
+                    if to_kill.contains(&parent) && !to_kill.contains(p_pid) {
+                        to_kill.push(*p_pid);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        to_kill
+            .into_iter()
+            .filter_map(|p| {
+                system
+                    .process(p)
+                    .map(|proc_info| identity_of(p.as_u32(), proc_info))
+            })
+            .collect::<Vec<ProcessIdentity>>()
+    })
+    .await;
+
+    // The collected PIDs are re-read on every check while waiting for them
+    // to exit.
+    let running = || {
+        let to_kill = to_kill.clone();
+        let pids = to_kill.iter().map(|identity| identity.pid).collect();
+        process_snapshot::query_refreshed(pids, POLL_MAX_AGE, move |system| {
+            to_kill
+                .into_iter()
+                .filter(|identity| {
+                    system
+                        .process(Pid::from_u32(identity.pid))
+                        .is_some_and(|p| is_alive(p) && identity.matches(p))
+                })
+                .collect::<Vec<ProcessIdentity>>()
+        })
+    };
+
+    for identity in running().await.iter().rev() {
+        let upid = UnixPid::from_raw(identity.pid as i32);
+        let _ = signal::kill(upid, options.signal.as_nix());
+    }
+
+    wait_until(options.grace_period, || {
+        let running = running();
+        async move { running.await.is_empty() }
+    })
+    .await;
+    let still_running = running().await;
+
+    let mut any_success = to_kill.len() > still_running.len();
+    for identity in still_running.iter().rev() {
+        let upid = UnixPid::from_raw(identity.pid as i32);
+        if signal::kill(upid, Signal::SIGKILL).is_ok() {
+            any_success = true;
+        }
+    }
+
+    if any_success {
+        forget_process(pid);
+        Ok(KillProcessResult {
+            success: true,
+            message: format!("Process {} terminated", pid),
+            denied: false,
+            identity_mismatch: false,
+        })
+    } else {
+        Ok(KillProcessResult {
+            success: false,
+            denied: true,
+            identity_mismatch: false,
+            message: format!(
+                "Failed to kill process {}. The process may require elevated permissions.",
+                pid
+            ),
+        })
+    }
+}
+
+pub const DEFAULT_STOP_GRACE_PERIOD_MS: u64 = 500;
+const MAX_STOP_GRACE_PERIOD_MS: u64 = 10 * 60 * 1000;
+
+/// Signal sent to an action when it is stopped. Ignored on Windows, where
+/// `taskkill` is used instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum StopSignal {
+    #[default]
+    #[serde(alias = "SIGTERM")]
+    Term,
+    #[serde(alias = "SIGINT")]
+    Int,
+    #[serde(alias = "SIGHUP")]
+    Hup,
+    #[serde(alias = "SIGQUIT")]
+    Quit,
+}
+
+#[cfg(unix)]
+impl StopSignal {
+    fn as_nix(self) -> nix::sys::signal::Signal {
+        use nix::sys::signal::Signal;
+        match self {
+            StopSignal::Term => Signal::SIGTERM,
+            StopSignal::Int => Signal::SIGINT,
+            StopSignal::Hup => Signal::SIGHUP,
+            StopSignal::Quit => Signal::SIGQUIT,
+        }
+    }
+}
+
+/// How a tracked process is stopped: an optional shell command run first
+/// (e.g. `docker compose down`), then the stop signal, then a forced kill once
+/// the grace period has elapsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StopOptions {
+    pub stop_command: Option<String>,
+    pub working_directory: Option<String>,
+    pub signal: StopSignal,
+    pub grace_period: Duration,
+}
+
+impl Default for StopOptions {
+    fn default() -> Self {
+        Self {
+            stop_command: None,
+            working_directory: None,
+            signal: StopSignal::default(),
+            grace_period: Duration::from_millis(DEFAULT_STOP_GRACE_PERIOD_MS),
+        }
+    }
+}
+
+impl StopOptions {
+    /// Reads `stop_command`, `stop_signal` and `stop_grace_period_ms` from an
+    /// action config.
+    pub fn from_config(config: &serde_json::Value) -> Result<Self, String> {
+        let stop_command = config
+            .get("stop_command")
+            .and_then(|value| value.as_str())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let working_directory = config
+            .get("working_directory")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+        let signal = match config.get("stop_signal") {
+            None | Some(serde_json::Value::Null) => StopSignal::default(),
+            Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+                format!(
+                    "Invalid stop_signal: {} (expected TERM, INT, HUP or QUIT)",
+                    value
+                )
+            })?,
+        };
+        let grace_ms = match config.get("stop_grace_period_ms") {
+            None | Some(serde_json::Value::Null) => DEFAULT_STOP_GRACE_PERIOD_MS,
+            Some(value) => value
+                .as_u64()
+                .filter(|ms| *ms <= MAX_STOP_GRACE_PERIOD_MS)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid stop_grace_period_ms: {} (expected 0-{})",
+                        value, MAX_STOP_GRACE_PERIOD_MS
+                    )
+                })?,
+        };
+        Ok(Self {
+            stop_command,
+            working_directory,
+            signal,
+            grace_period: Duration::from_millis(grace_ms),
+        })
+    }
+
+    /// The config fields [`Self::from_config`] reads back, used to persist
+    /// the options of a tracked process.
+    pub fn to_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "stop_command": self.stop_command,
+            "working_directory": self.working_directory,
+            "stop_signal": self.signal,
+            "stop_grace_period_ms": self.grace_period.as_millis() as u64,
+        })
+    }
+}
+
+static STOP_OPTIONS: LazyLock<Mutex<HashMap<u32, StopOptions>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn register_stop_options(pid: u32, options: StopOptions) {
+    if pid == 0 {
+        return;
+    }
+    let options = (options != StopOptions::default()).then_some(options);
+    crate::tracked_processes::record_stop_options(pid, options.as_ref());
+    if let Ok(mut registry) = STOP_OPTIONS.lock() {
+        match options {
+            Some(options) => registry.insert(pid, options),
+            None => registry.remove(&pid),
+        };
+    }
+}
+
+fn stop_options_for(pid: u32) -> StopOptions {
+    STOP_OPTIONS
+        .lock()
+        .ok()
+        .and_then(|registry| registry.get(&pid).cloned())
+        .unwrap_or_default()
+}
+
+/// Drops everything recorded about a stopped process.
+pub fn forget_process(pid: u32) {
+    unregister_tracked_pid(pid);
+    process_snapshot::invalidate();
+    crate::tracked_processes::forget(pid);
+    crate::suspend::forget(pid);
+    unregister_process_group(pid);
+    if let Ok(mut registry) = STOP_OPTIONS.lock() {
+        registry.remove(&pid);
+    }
+}
+
+/// Polls `done` until it returns true or `timeout` elapses.
+async fn wait_until<F, Fut>(timeout: Duration, mut done: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if done().await {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+}
+
+async fn run_stop_command(
+    command: &str,
+    working_directory: Option<&str>,
+    timeout: Duration,
+) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    if let Some(dir) = working_directory.filter(|dir| std::path::Path::new(dir).is_dir()) {
+        cmd.current_dir(dir);
+    }
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    // The stop command gets at least a few seconds even with a short grace period.
+    let timeout = timeout.max(Duration::from_secs(5));
+    match tokio::time::timeout(timeout, cmd.status()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("exited with {}", status)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("timed out after {} ms", timeout.as_millis())),
+    }
+}
+
+/// Registers a PID resolved by the frontend. New PIDs are persisted with the
+/// action they belong to so they can still be stopped after a restart, and
+/// watched so their exit is reported.
 #[tauri::command]
-pub fn register_tracked_pid_command(pid: u32) {
-    register_tracked_pid(pid);
+pub fn register_tracked_pid_command(
+    app: tauri::AppHandle,
+    pid: u32,
+    run_id: Option<i64>,
+    action_id: Option<i64>,
+    workspace_id: Option<i64>,
+) {
+    if is_pid_registered(pid) {
+        return;
+    }
+    if let Some(tracked) = crate::tracked_processes::track(pid, run_id, action_id, workspace_id) {
+        crate::exit_watcher::watch(app, tracked);
+    }
 }
 
-fn is_pid_registered(pid: u32) -> bool {
+pub fn is_pid_registered(pid: u32) -> bool {
     TRACKED_PIDS
         .lock()
-        .map(|pids| pids.contains(&pid))
+        .map(|pids| pids.contains_key(&pid))
         .unwrap_or(false)
 }
 
+/// For a tracked `pid`, whether the process using it is still the one that
+/// was tracked. A PID nobody uses any more holds, so stopping it reports that
+/// it already exited. `None` if `pid` is not tracked.
+fn tracked_identity_holds(system: &System, pid: u32) -> Option<bool> {
+    let recorded = TRACKED_PIDS.lock().ok()?.get(&pid).cloned()?;
+    let live = system.process(Pid::from_u32(pid)).filter(|p| is_alive(p));
+    Some(match (live, recorded) {
+        (None, _) => true,
+        (Some(proc_info), Some(identity)) => identity.matches(proc_info),
+        (Some(_), None) => false,
+    })
+}
+
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
@@ -45,30 +600,110 @@ pub struct KillProcessResult {
     pub message: String,
     #[serde(default)]
     pub denied: bool,
+    /// The PID is now used by a different process than the one we tracked,
+    /// so nothing was signalled.
+    #[serde(default)]
+    pub identity_mismatch: bool,
+}
+
+fn identity_mismatch_result(pid: u32) -> KillProcessResult {
+    KillProcessResult {
+        success: false,
+        message: format!(
+            "Refusing to kill PID {}: it now belongs to a different process than the one launched (identity mismatch)",
+            pid
+        ),
+        denied: false,
+        identity_mismatch: true,
+    }
 }
 
 #[tauri::command]
 pub async fn kill_process(pid: u32) -> Result<KillProcessResult, String> {
-    if !is_pid_registered(pid) {
+    let (holds, managed) =
+        process_snapshot::query_refreshed(vec![pid], POLL_MAX_AGE, move |system| {
+            (tracked_identity_holds(system, pid), is_managed(system, pid))
+        })
+        .await;
+    if holds == Some(false) {
+        // The tracked process is gone; its PID was reused.
+        forget_process(pid);
+        return Ok(identity_mismatch_result(pid));
+    }
+    if !managed {
         return Err(format!(
             "Refusing to kill PID {}: not launched by WorkspaceLauncher",
             pid
         ));
     }
 
-    if !is_process_running(pid).await.unwrap_or(false) {
-        unregister_tracked_pid(pid);
+    // A frozen process cannot handle the stop signal or run its cleanup.
+    if crate::suspend::currently_suspended(pid).await {
+        let _ = crate::suspend::set_process_suspended(pid, false).await;
+    }
+
+    let watch = crate::exit_watcher::unwatch(pid);
+    let result = stop_process(pid).await;
+    if !matches!(&result, Ok(stopped) if stopped.success) {
+        crate::exit_watcher::rewatch(pid, watch);
+    }
+    result
+}
+
+async fn stop_process(pid: u32) -> Result<KillProcessResult, String> {
+    if !pid_is_alive(pid).await {
+        forget_process(pid);
         return Ok(KillProcessResult {
             success: true,
             message: format!("Process {} already terminated", pid),
             denied: false,
+            identity_mismatch: false,
         });
     }
 
+    let options = stop_options_for(pid);
+    let mut stop_command_error = None;
+    if let Some(command) = &options.stop_command {
+        match run_stop_command(
+            command,
+            options.working_directory.as_deref(),
+            options.grace_period,
+        )
+        .await
+        {
+            Ok(()) => {
+                if wait_until(options.grace_period, || async move { !pid_is_alive(pid).await })
+                    .await
+                {
+                    forget_process(pid);
+                    return Ok(KillProcessResult {
+                        success: true,
+                        message: format!("Process {} stopped by stop command", pid),
+                        denied: false,
+                        identity_mismatch: false,
+                    });
+                }
+            }
+            Err(error) => stop_command_error = Some(error),
+        }
+    }
+
+    let mut result = terminate_process(pid, &options).await?;
+    if let Some(error) = stop_command_error {
+        result.message = format!("{} (stop command failed: {})", result.message, error);
+    }
+    Ok(result)
+}
+
+async fn terminate_process(
+    pid: u32,
+    options: &StopOptions,
+) -> Result<KillProcessResult, String> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
 
+        let _ = options;
         let try_taskkill = |force: bool| {
             let mut cmd = Command::new("taskkill");
             if force {
@@ -83,22 +718,24 @@ pub async fn kill_process(pid: u32) -> Result<KillProcessResult, String> {
 
         match try_taskkill(false) {
             Ok(output) if output.status.success() => {
-                unregister_tracked_pid(pid);
+                forget_process(pid);
                 Ok(KillProcessResult {
                     success: true,
                     message: format!("Process {} terminated", pid),
                     denied: false,
+                    identity_mismatch: false,
                 })
             }
             Ok(output) => {
                 let stderr = String::from_utf8_lossy(&output.stderr).to_string();
                 match try_taskkill(true) {
                     Ok(force_output) if force_output.status.success() => {
-                        unregister_tracked_pid(pid);
+                        forget_process(pid);
                         Ok(KillProcessResult {
                             success: true,
                             message: format!("Process {} killed forcefully", pid),
                             denied: false,
+                            identity_mismatch: false,
                         })
                     }
                     Ok(force_output) => {
@@ -114,6 +751,7 @@ pub async fn kill_process(pid: u32) -> Result<KillProcessResult, String> {
                             Ok(KillProcessResult {
                                 success: false,
                                 denied: true,
+                                identity_mismatch: false,
                                 message: format!(
                                     "Failed to kill process {}: {}. Try running WorkspaceLauncher as Administrator or close it from its parent app.",
                                     pid,
@@ -137,75 +775,36 @@ pub async fn kill_process(pid: u32) -> Result<KillProcessResult, String> {
 
     #[cfg(not(target_os = "windows"))]
     {
-        use nix::sys::signal::{self, Signal};
-        use nix::unistd::Pid as UnixPid;
-
-        let mut system = System::new();
-        system.refresh_processes(ProcessesToUpdate::All, true);
-        let root = Pid::from_u32(pid);
-        let mut to_kill: Vec<Pid> = vec![root];
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for (p_pid, proc_info) in system.processes() {
-                if let Some(parent) = proc_info.parent() {
-                    if to_kill.contains(&parent) && !to_kill.contains(p_pid) {
-                        to_kill.push(*p_pid);
-                        changed = true;
-                    }
-                }
-            }
-        }
-
-        for p in to_kill.iter().rev() {
-            let upid = UnixPid::from_raw(p.as_u32() as i32);
-            let _ = signal::kill(upid, Signal::SIGTERM);
+        if let Some(group) = process_group_for_pid(pid) {
+            return kill_process_group(pid, group.pgid, options).await;
         }
 
-        sleep(Duration::from_millis(500)).await;
-
-        system.refresh_processes(ProcessesToUpdate::All, true);
-        let still_running: Vec<Pid> = to_kill
-            .iter()
-            .copied()
-            .filter(|p| system.process(*p).is_some())
-            .collect();
-
-        let mut any_success = to_kill.len() > still_running.len();
-        for p in still_running.iter().rev() {
-            let upid = UnixPid::from_raw(p.as_u32() as i32);
-            if signal::kill(upid, Signal::SIGKILL).is_ok() {
-                any_success = true;
-            }
-        }
-
-        if any_success {
-            unregister_tracked_pid(pid);
-            Ok(KillProcessResult {
-                success: true,
-                message: format!("Process {} terminated", pid),
-                denied: false,
-            })
-        } else {
-            Ok(KillProcessResult {
-                success: false,
-                denied: true,
-                message: format!(
-                    "Failed to kill process {}. The process may require elevated permissions.",
-                    pid
-                ),
-            })
-        }
+        kill_process_tree(pid, options).await
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProcessIdentity {
     pub pid: u32,
     pub start_time_secs: u64,
     pub name: String,
 }
 
+impl ProcessIdentity {
+    /// Whether `proc_info`, found under this PID, is the process this
+    /// identity was recorded for. The name is not compared because it
+    /// changes when the process execs.
+    pub fn matches(&self, proc_info: &Process) -> bool {
+        proc_info.start_time() == self.start_time_secs
+    }
+
+    /// Whether both identities describe the same process, by PID and start
+    /// time only, like [`ProcessIdentity::matches`].
+    pub fn is_same_process(&self, other: &ProcessIdentity) -> bool {
+        self.pid == other.pid && self.start_time_secs == other.start_time_secs
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VerifyTrackedProcessRequest {
     pub pid: u32,
@@ -213,21 +812,47 @@ pub struct VerifyTrackedProcessRequest {
     pub expected_name: Option<String>,
 }
 
-fn lookup_process_identity(system: &System, pid: u32) -> Option<ProcessIdentity> {
-    let proc_info = system.process(Pid::from_u32(pid))?;
-    Some(ProcessIdentity {
+fn identity_of(pid: u32, proc_info: &Process) -> ProcessIdentity {
+    ProcessIdentity {
         pid,
         start_time_secs: proc_info.start_time(),
         name: proc_info.name().to_string_lossy().to_string(),
+    }
+}
+
+/// Identity of the live process `pid`, if any.
+pub fn current_identity(pid: u32) -> Option<ProcessIdentity> {
+    process_snapshot::with_process(pid, POLL_MAX_AGE, |process| {
+        process
+            .filter(|proc_info| is_alive(proc_info))
+            .map(|proc_info| identity_of(pid, proc_info))
+    })
+}
+
+/// [`current_identity`] for async callers.
+pub async fn query_identity(pid: u32) -> Option<ProcessIdentity> {
+    process_snapshot::query_process(pid, POLL_MAX_AGE, move |process| {
+        process
+            .filter(|proc_info| is_alive(proc_info))
+            .map(|proc_info| identity_of(pid, proc_info))
+    })
+    .await
+}
+
+/// Re-reads just `pid`, for loops waiting on it to exit.
+async fn pid_is_alive(pid: u32) -> bool {
+    process_snapshot::query_refreshed(vec![pid], POLL_MAX_AGE, move |system| {
+        process_snapshot::is_running(system, pid)
     })
+    .await
 }
 
 #[tauri::command]
 pub async fn is_process_running(pid: u32) -> Result<bool, String> {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
-    let target = Pid::from_u32(pid);
-    let exists = system.process(target).is_some();
+    let exists = process_snapshot::query_process(pid, POLL_MAX_AGE, |process| {
+        process.is_some_and(is_alive)
+    })
+    .await;
     if !exists {
         println!("DEBUG: is_process_running({}) -> false", pid);
     }
@@ -236,17 +861,12 @@ pub async fn is_process_running(pid: u32) -> Result<bool, String> {
 
 #[tauri::command]
 pub async fn get_process_identity(pid: u32) -> Result<Option<ProcessIdentity>, String> {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
-    Ok(lookup_process_identity(&system, pid))
+    Ok(query_identity(pid).await)
 }
 
 #[tauri::command]
 pub async fn verify_tracked_process(req: VerifyTrackedProcessRequest) -> Result<bool, String> {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
-
-    let Some(identity) = lookup_process_identity(&system, req.pid) else {
+    let Some(identity) = query_identity(req.pid).await else {
         return Ok(false);
     };
 
@@ -260,7 +880,9 @@ pub async fn verify_tracked_process(req: VerifyTrackedProcessRequest) -> Result<
         }
     }
 
-    if let Some(ref expected_name) = req.expected_name {
+    // PID and start time identify the process; its name is only a fallback
+    // because it changes when the process execs.
+    if let (None, Some(expected_name)) = (req.expected_start_time_secs, &req.expected_name) {
         let needle = expected_name.to_lowercase();
         if !needle.is_empty()
             && !identity.name.to_lowercase().contains(&needle)
@@ -276,7 +898,7 @@ pub async fn verify_tracked_process(req: VerifyTrackedProcessRequest) -> Result<
     Ok(true)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvePidRequest {
     pub parent_pid: u32,
     pub expected_name: Option<String>,
@@ -286,7 +908,7 @@ pub struct ResolvePidRequest {
     pub started_after_secs: Option<u64>,
 }
 
-fn collect_filtered_descendants<'a>(
+pub fn collect_filtered_descendants<'a>(
     system: &'a System,
     root: Pid,
     exclude: &[String],
@@ -307,6 +929,167 @@ fn collect_filtered_descendants<'a>(
     descendants
 }
 
+/// A process and its descendants, as shown in the process tree of an action.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    pub start_time_secs: u64,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    /// Whether the launcher tracks this process itself (rather than only as
+    /// a descendant of a tracked one).
+    pub tracked: bool,
+    /// Stopped by `suspend_action` or a job-control signal.
+    pub suspended: bool,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessTreeRequest {
+    pub run_id: Option<i64>,
+    pub pid: Option<u32>,
+}
+
+fn tree_node(
+    proc_info: &Process,
+    children_of: &HashMap<Pid, Vec<&Process>>,
+) -> ProcessTreeNode {
+    let children = children_of
+        .get(&proc_info.pid())
+        .map(|children| {
+            children
+                .iter()
+                .map(|child| tree_node(child, children_of))
+                .collect()
+        })
+        .unwrap_or_default();
+    ProcessTreeNode {
+        pid: proc_info.pid().as_u32(),
+        parent_pid: proc_info.parent().map(Pid::as_u32),
+        name: proc_info.name().to_string_lossy().to_string(),
+        cmd: proc_info
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+        cwd: proc_info.cwd().map(|cwd| cwd.to_string_lossy().to_string()),
+        start_time_secs: proc_info.start_time(),
+        cpu_usage: proc_info.cpu_usage(),
+        memory: proc_info.memory(),
+        tracked: is_pid_registered(proc_info.pid().as_u32()),
+        suspended: crate::suspend::is_suspended(proc_info.pid().as_u32())
+            || proc_info.status() == ProcessStatus::Stop,
+        children,
+    }
+}
+
+impl ProcessTreeNode {
+    /// The PIDs of this node and all of its descendants.
+    pub fn pids(&self) -> Vec<u32> {
+        let mut pids = vec![self.pid];
+        for child in &self.children {
+            pids.extend(child.pids());
+        }
+        pids
+    }
+}
+
+/// Builds the live tree rooted at `pid`: every descendant, unfiltered, with
+/// siblings in the order they were started.
+pub fn process_tree(system: &System, pid: u32) -> Option<ProcessTreeNode> {
+    let root = system.process(Pid::from_u32(pid)).filter(|p| is_alive(p))?;
+    let mut children_of: HashMap<Pid, Vec<&Process>> = HashMap::new();
+    for proc_info in collect_filtered_descendants(system, root.pid(), &[]) {
+        if let Some(parent) = proc_info.parent().filter(|_| is_alive(proc_info)) {
+            children_of.entry(parent).or_default().push(proc_info);
+        }
+    }
+    for children in children_of.values_mut() {
+        children.sort_by_key(|child| (child.start_time(), child.pid()));
+    }
+    Some(tree_node(root, &children_of))
+}
+
+/// Runs this instance still manages: those of tracked action instances and
+/// of the process groups actions were spawned into, plus runs whose attached
+/// children are still running.
+pub fn live_run_ids() -> HashSet<i64> {
+    let mut runs = HashSet::new();
+    if let Ok(groups) = PROCESS_GROUPS.lock() {
+        runs.extend(groups.values().map(|group| group.run_id));
+    }
+    if let Ok(instances) = ACTION_INSTANCES.lock() {
+        runs.extend(
+            instances
+                .values()
+                .filter(|instance| is_pid_registered(instance.identity.pid))
+                .map(|instance| instance.run_id),
+        );
+    }
+    runs.extend(crate::run_context::active_run_ids());
+    runs
+}
+
+/// The processes a run's tree may be rooted at, best first: the leader of the
+/// process group it was spawned into, then the process tracked for it.
+fn run_roots(run_id: i64) -> Vec<u32> {
+    let mut roots = Vec::new();
+    if let Ok(groups) = PROCESS_GROUPS.lock() {
+        for (&pid, group) in groups.iter().filter(|(_, group)| group.run_id == run_id) {
+            roots.push(group.pgid as u32);
+            roots.push(pid);
+        }
+    }
+    if let Ok(instances) = ACTION_INSTANCES.lock() {
+        roots.extend(
+            instances
+                .values()
+                .filter(|instance| instance.run_id == run_id)
+                .map(|instance| instance.identity.pid),
+        );
+    }
+    roots
+}
+
+/// Whether `pid` is tracked or a live descendant of a tracked process, with
+/// the tracked process still having the identity it was tracked with.
+pub fn is_managed(system: &System, pid: u32) -> bool {
+    let mut current = Some(Pid::from_u32(pid));
+    let mut depth = 0;
+    while let Some(p) = current {
+        if let Some(holds) = tracked_identity_holds(system, p.as_u32()) {
+            return holds;
+        }
+        depth += 1;
+        if depth > 64 {
+            return false;
+        }
+        current = system.process(p).and_then(Process::parent);
+    }
+    false
+}
+
+/// The full process tree of a run (`run_id`) or of any process (`pid`), so
+/// the UI can show everything an action spawned.
+#[tauri::command]
+pub async fn get_action_process_tree(
+    req: ProcessTreeRequest,
+) -> Result<Option<ProcessTreeNode>, String> {
+    let roots = match (req.pid, req.run_id) {
+        (Some(pid), _) => vec![pid],
+        (None, Some(run_id)) => run_roots(run_id),
+        (None, None) => return Err("Either run_id or pid is required".to_string()),
+    };
+    Ok(process_snapshot::query_processes(POLL_MAX_AGE, move |system| {
+        roots.iter().find_map(|&pid| process_tree(system, pid))
+    })
+    .await)
+}
+
 async fn find_server_after_build(
     req: &ResolvePidRequest,
     build_pids: &[u32],
@@ -317,9 +1100,21 @@ async fn find_server_after_build(
     // Give the server a moment to finish starting up.
     sleep(Duration::from_millis(1500)).await;
 
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
+    let req = req.clone();
+    let build_pids = build_pids.to_vec();
+    let exclude = exclude.to_vec();
+    process_snapshot::query_processes(Duration::ZERO, move |system| {
+        select_server_after_build(system, &req, &build_pids, &exclude)
+    })
+    .await
+}
 
+fn select_server_after_build(
+    system: &System,
+    req: &ResolvePidRequest,
+    build_pids: &[u32],
+    exclude: &[String],
+) -> Result<Option<u32>, String> {
     let build_set: std::collections::HashSet<u32> = build_pids.iter().copied().collect();
     let threshold = req.started_after_secs.unwrap_or(0);
 
@@ -395,27 +1190,34 @@ pub async fn resolve_descendant_pid(req: ResolvePidRequest) -> Result<Option<u32
     // Phase 1: poll until at least one descendant appears (tree is being built)
     let mut initial_pids: Vec<u32> = Vec::new();
     loop {
-        let mut system = System::new_all();
-        system.refresh_processes(ProcessesToUpdate::All, true);
-        let parent = Pid::from_u32(req.parent_pid);
-        let descendants = collect_filtered_descendants(&system, parent, &exclude);
+        let parent_pid = req.parent_pid;
+        let exclude = exclude.clone();
+        let descendants = process_snapshot::query_processes(POLL_MAX_AGE, move |system| {
+            let parent = Pid::from_u32(parent_pid);
+            let descendants = collect_filtered_descendants(system, parent, &exclude);
 
-        println!(
-            "DEBUG: resolving descendants for parent: {} — found {}",
-            req.parent_pid,
-            descendants.len()
-        );
-        for d in &descendants {
             println!(
-                "DEBUG: descendant: {} ({:?}) parent: {:?}",
-                d.pid(),
-                d.name(),
-                d.parent()
+                "DEBUG: resolving descendants for parent: {} — found {}",
+                parent_pid,
+                descendants.len()
             );
-        }
+            for d in &descendants {
+                println!(
+                    "DEBUG: descendant: {} ({:?}) parent: {:?}",
+                    d.pid(),
+                    d.name(),
+                    d.parent()
+                );
+            }
+            descendants
+                .iter()
+                .map(|p| p.pid().as_u32())
+                .collect::<Vec<u32>>()
+        })
+        .await;
 
         if !descendants.is_empty() {
-            initial_pids = descendants.iter().map(|p| p.pid().as_u32()).collect();
+            initial_pids = descendants;
             break;
         }
 
@@ -434,32 +1236,51 @@ pub async fn resolve_descendant_pid(req: ResolvePidRequest) -> Result<Option<u32
     sleep(Duration::from_millis(stabilize_ms)).await;
 
     // Phase 3: of the originally captured descendants, keep only those still alive
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
+    let captured = initial_pids.clone();
+    let expected_name = req.expected_name.clone();
+    let selected = process_snapshot::query_processes(Duration::ZERO, move |system| {
+        let initial_pids = captured;
+        let alive_pids: Vec<u32> = initial_pids
+            .iter()
+            .copied()
+            .filter(|&pid| system.process(Pid::from_u32(pid)).is_some())
+            .collect();
 
-    let alive_pids: Vec<u32> = initial_pids
-        .iter()
-        .copied()
-        .filter(|&pid| system.process(Pid::from_u32(pid)).is_some())
-        .collect();
+        println!(
+            "DEBUG: after {}ms stabilization: {}/{} descendants still alive",
+            stabilize_ms,
+            alive_pids.len(),
+            initial_pids.len()
+        );
 
-    println!(
-        "DEBUG: after {}ms stabilization: {}/{} descendants still alive",
-        stabilize_ms,
-        alive_pids.len(),
-        initial_pids.len()
-    );
+        if alive_pids.is_empty() {
+            return None;
+        }
+        Some(select_alive_descendant(
+            system,
+            &alive_pids,
+            expected_name.as_deref(),
+        ))
+    })
+    .await;
 
-    if alive_pids.is_empty() {
+    match selected {
+        Some(pid) => Ok(pid),
         // Phase 4: the entire initial tree was a build/setup phase that exited.
         // Wait for the actual server process to start, then find it.
-        return find_server_after_build(&req, &initial_pids, &exclude).await;
+        None => find_server_after_build(&req, &initial_pids, &exclude).await,
     }
+}
 
+fn select_alive_descendant(
+    system: &System,
+    alive_pids: &[u32],
+    expected_name: Option<&str>,
+) -> Option<u32> {
     // Match by expected name first
-    if let Some(ref name) = req.expected_name {
+    if let Some(name) = expected_name {
         let needle = name.to_lowercase();
-        for &pid in &alive_pids {
+        for &pid in alive_pids {
             if let Some(proc_info) = system.process(Pid::from_u32(pid)) {
                 if proc_info
                     .name()
@@ -472,14 +1293,14 @@ pub async fn resolve_descendant_pid(req: ResolvePidRequest) -> Result<Option<u32
                         pid,
                         proc_info.name()
                     );
-                    return Ok(Some(pid));
+                    return Some(pid);
                 }
             }
         }
     }
 
     // Prefer leaf: an alive process with no alive children
-    for &pid in &alive_pids {
+    for &pid in alive_pids {
         let has_alive_child = alive_pids.iter().any(|&other| {
             system
                 .process(Pid::from_u32(other))
@@ -495,11 +1316,11 @@ pub async fn resolve_descendant_pid(req: ResolvePidRequest) -> Result<Option<u32
                     proc_info.name()
                 );
             }
-            return Ok(Some(pid));
+            return Some(pid);
         }
     }
 
-    Ok(alive_pids.first().copied())
+    alive_pids.first().copied()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -513,14 +1334,19 @@ pub struct FindServerRequest {
 pub async fn find_server_process(req: FindServerRequest) -> Result<Option<u32>, String> {
     let exclude: Vec<String> = req
         .exclude_names
+        .clone()
         .unwrap_or_default()
         .into_iter()
         .map(|s| s.to_lowercase())
         .collect();
 
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
+    Ok(process_snapshot::query_processes(Duration::ZERO, move |system| {
+        select_server(system, &req, &exclude)
+    })
+    .await)
+}
 
+fn select_server(system: &System, req: &FindServerRequest, exclude: &[String]) -> Option<u32> {
     let threshold = req.started_after_secs.unwrap_or(0);
     let wdir_normalized = req.working_directory.as_deref().map(|w| {
         w.to_lowercase()
@@ -566,7 +1392,7 @@ pub async fn find_server_process(req: FindServerRequest) -> Result<Option<u32>,
             );
         }
     }
-    Ok(result)
+    result
 }
 
 #[cfg(test)]
@@ -575,6 +1401,7 @@ mod tests {
     use std::process::Command;
     use tokio::time::{sleep, Duration};
 
+    #[allow(clippy::zombie_processes)]
     async fn spawn_dummy_long_running() -> u32 {
         #[cfg(target_os = "windows")]
         {
@@ -592,7 +1419,7 @@ mod tests {
                 .arg("5")
                 .spawn()
                 .expect("failed to spawn dummy process");
-            return child.id();
+            child.id()
         }
     }
 
@@ -651,4 +1478,320 @@ mod tests {
         register_tracked_pid(pid);
         let _ = kill_process(pid).await;
     }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kill_process_group_reaches_grandchildren() {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args([
+            "-c",
+            "sleep 30 & (sleep 30 &); sh -c 'sleep 30 & (sleep 30 &); wait' & wait",
+        ]);
+        cmd.process_group(0);
+        let mut child = cmd.spawn().expect("spawn script");
+        let pid = child.id().expect("pid");
+        let pgid = pid as i32;
+        sleep(Duration::from_millis(300)).await;
+
+        let members = live_group_members(pgid);
+        assert!(
+            members.len() >= 5,
+            "expected script and its grandchildren in group, got {:?}",
+            members
+        );
+
+        register_tracked_pid(pid);
+        register_process_group(pid, 31, pgid);
+        assert_eq!(process_group_for_run(31), Some(pgid));
+
+        let result = kill_process(pid).await.expect("kill_process failed");
+        assert!(result.success, "{}", result.message);
+        let _ = child.wait().await;
+
+        assert!(
+            live_group_members(pgid).is_empty(),
+            "group members survived: {:?}",
+            live_group_members(pgid)
+        );
+        assert!(process_group_for_pid(pid).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_action_process_tree_and_node_kill() {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "sleep 30 & sh -c 'sleep 30 & wait' & wait"]);
+        cmd.process_group(0);
+        let mut child = cmd.spawn().expect("spawn script");
+        let pid = child.id().expect("pid");
+        sleep(Duration::from_millis(300)).await;
+
+        register_tracked_pid(pid);
+        register_process_group(pid, 44, pid as i32);
+        let tree = get_action_process_tree(ProcessTreeRequest {
+            run_id: Some(44),
+            pid: None,
+        })
+        .await
+        .expect("tree")
+        .expect("run has a tree");
+        assert_eq!(tree.pid, pid);
+        assert!(tree.tracked);
+        assert_eq!(tree.children.len(), 2, "{:?}", tree.children);
+        let nested = tree
+            .children
+            .iter()
+            .find(|node| node.name == "sh")
+            .expect("nested shell");
+        assert_eq!(nested.children.len(), 1);
+        assert!(nested.cmd.iter().any(|arg| arg.contains("sleep 30")));
+
+        // A node of the tree can be stopped on its own; the rest keeps running.
+        let leaf = nested.children[0].pid;
+        assert!(!is_pid_registered(leaf));
+        let result = kill_process(leaf).await.expect("kill node");
+        assert!(result.success, "{}", result.message);
+        assert!(pid_is_alive(pid).await);
+
+        assert!(kill_process(u32::MAX - 5).await.is_err());
+        let _ = kill_process(pid).await;
+        let _ = child.wait().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process_refuses_reused_pid() {
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id().expect("pid");
+        let identity = current_identity(pid).expect("identity");
+
+        // Pretend the tracked process exited and its PID went to this one.
+        register_tracked_identity(ProcessIdentity {
+            start_time_secs: identity.start_time_secs.saturating_sub(60),
+            ..identity.clone()
+        });
+        let result = kill_process(pid).await.expect("kill result");
+        assert!(!result.success);
+        assert!(result.identity_mismatch, "{}", result.message);
+        assert!(pid_is_alive(pid).await, "a reused PID must not be signalled");
+        assert!(!is_pid_registered(pid));
+
+        assert_eq!(register_tracked_pid(pid), Some(identity));
+        let result = kill_process(pid).await.expect("kill result");
+        assert!(result.success, "{}", result.message);
+        assert!(!result.identity_mismatch);
+        let _ = child.wait().await;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn a_process_that_execs_is_still_the_tracked_one() {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "sleep 0.5; exec sleep 30"])
+            .spawn()
+            .expect("spawn shell");
+        let pid = child.id().expect("pid");
+        let identity = register_tracked_pid(pid).expect("identity");
+        assert_eq!(identity.name, "sh");
+
+        sleep(Duration::from_millis(1000)).await;
+        let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).expect("comm");
+        assert_eq!(comm.trim(), "sleep", "the shell exec'd into sleep");
+        let current = query_identity(pid).await.expect("still running");
+        assert!(current.is_same_process(&identity));
+        assert!(verify_tracked_process(VerifyTrackedProcessRequest {
+            pid,
+            expected_start_time_secs: Some(identity.start_time_secs),
+            expected_name: Some(identity.name.clone()),
+        })
+        .await
+        .unwrap());
+
+        let result = kill_process(pid).await.expect("kill result");
+        assert!(result.success, "{}", result.message);
+        assert!(!result.identity_mismatch);
+        let _ = child.wait().await;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_group_kill_reaches_process_that_left_the_group() {
+        let mut shell = tokio::process::Command::new("sh")
+            .args(["-c", "setsid sleep 31 & wait"])
+            .process_group(0)
+            .spawn()
+            .expect("spawn sh");
+        let shell_pid = shell.id().expect("pid");
+        sleep(Duration::from_millis(300)).await;
+        let sleeper = process_snapshot::with_processes(Duration::ZERO, |system| {
+            collect_filtered_descendants(system, Pid::from_u32(shell_pid), &[])
+                .into_iter()
+                .find(|p| p.name() == "sleep")
+                .map(|p| p.pid().as_u32())
+        })
+        .expect("sleep child");
+
+        register_tracked_pid(sleeper).expect("identity");
+        register_process_group(sleeper, -31, shell_pid as i32);
+        let result = kill_process(sleeper).await.expect("kill result");
+        assert!(result.success, "{}", result.message);
+        assert!(!pid_is_alive(sleeper).await, "process outside the group survived");
+        let _ = shell.wait().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_run_group_stops_untracked_process() {
+        assert!(stop_run_group(-3200).await.is_none());
+
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("32")
+            .process_group(0)
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id().expect("pid");
+        register_process_group(pid, -3201, pid as i32);
+        assert!(!is_pid_registered(pid));
+
+        let result = stop_run_group(-3201).await.expect("group recorded");
+        assert!(result.expect("stop").success);
+        let _ = child.wait().await;
+        assert!(process_group_for_run(-3201).is_none());
+    }
+
+    #[test]
+    fn test_stop_options_from_config() {
+        let options = StopOptions::from_config(&serde_json::json!({
+            "stop_command": " docker compose down ",
+            "stop_signal": "SIGINT",
+            "stop_grace_period_ms": 2500,
+        }))
+        .expect("valid stop options");
+        assert_eq!(options.stop_command.as_deref(), Some("docker compose down"));
+        assert_eq!(options.signal, StopSignal::Int);
+        assert_eq!(options.grace_period, Duration::from_millis(2500));
+
+        assert_eq!(
+            StopOptions::from_config(&serde_json::json!({})).unwrap(),
+            StopOptions::default()
+        );
+        assert!(StopOptions::from_config(&serde_json::json!({ "stop_signal": "KILL" })).is_err());
+        assert!(
+            StopOptions::from_config(&serde_json::json!({ "stop_grace_period_ms": -1 })).is_err()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process_uses_stop_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args([
+            "-c",
+            "trap '' TERM; trap 'exit 7' INT; while :; do sleep 0.1; done",
+        ]);
+        cmd.process_group(0);
+        let mut child = cmd.spawn().expect("spawn script");
+        let pid = child.id().expect("pid");
+        sleep(Duration::from_millis(200)).await;
+
+        register_tracked_pid(pid);
+        register_process_group(pid, 32, pid as i32);
+        register_stop_options(
+            pid,
+            StopOptions {
+                signal: StopSignal::Int,
+                grace_period: Duration::from_secs(3),
+                ..StopOptions::default()
+            },
+        );
+
+        let result = kill_process(pid).await.expect("kill_process failed");
+        assert!(result.success, "{}", result.message);
+        let status = child.wait().await.expect("wait");
+        assert_eq!(status.code(), Some(7), "INT trap should run, got {:?}", status.signal());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process_escalates_after_grace_period() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; while :; do sleep 0.1; done"])
+            .spawn()
+            .expect("spawn script");
+        let pid = child.id().expect("pid");
+        sleep(Duration::from_millis(200)).await;
+
+        register_tracked_pid(pid);
+        register_stop_options(
+            pid,
+            StopOptions {
+                grace_period: Duration::from_millis(300),
+                ..StopOptions::default()
+            },
+        );
+
+        let started = std::time::Instant::now();
+        let result = kill_process(pid).await.expect("kill_process failed");
+        assert!(result.success, "{}", result.message);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        let status = child.wait().await.expect("wait");
+        assert_eq!(status.signal(), Some(nix::libc::SIGKILL));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process_runs_stop_command() {
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id().expect("pid");
+
+        register_tracked_pid(pid);
+        register_stop_options(
+            pid,
+            StopOptions {
+                stop_command: Some(format!("kill -INT {}", pid)),
+                grace_period: Duration::from_secs(3),
+                ..StopOptions::default()
+            },
+        );
+
+        let reaper = tokio::spawn(async move { child.wait().await });
+        let result = kill_process(pid).await.expect("kill_process failed");
+        assert!(result.success, "{}", result.message);
+        assert!(result.message.contains("stop command"), "{}", result.message);
+        let _ = reaper.await;
+        assert!(!is_pid_registered(pid));
+    }
+
+    #[tokio::test]
+    async fn test_find_running_action_instance() {
+        let action_id = 28_001;
+        let pid = spawn_dummy_long_running().await;
+        sleep(Duration::from_millis(150)).await;
+
+        assert!(find_running_action_instance(action_id).await.is_none());
+
+        crate::tracked_processes::track(pid, Some(42), Some(action_id), None);
+        let instance = find_running_action_instance(action_id)
+            .await
+            .expect("instance should be running");
+        assert_eq!(instance.identity.pid, pid);
+        assert_eq!(instance.run_id, 42);
+
+        let _ = kill_process(pid).await;
+        sleep(Duration::from_millis(250)).await;
+        assert!(
+            find_running_action_instance(action_id).await.is_none(),
+            "killed instance should no longer be reported"
+        );
+    }
 }
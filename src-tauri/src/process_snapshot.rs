@@ -0,0 +1,290 @@
+//! One process table shared by every command that inspects processes.
+//! Building a `System::new_all()` per call re-reads the whole process table
+//! plus CPU, memory and disk state, which adds up when the UI polls several
+//! PIDs every few seconds. The shared table only refreshes processes, and at
+//! most once per `max_age`, so a burst of queries costs a single refresh.
+//! Loops waiting on known processes re-read only those PIDs instead, and
+//! async callers use the `query_*` forms, which take the lock and refresh on
+//! the blocking pool so a refresh never stalls an async worker.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, UpdateKind};
+
+/// How stale answers to polling queries (is it running, who is it) may be.
+pub const POLL_MAX_AGE: Duration = Duration::from_millis(250);
+
+struct Snapshot {
+    system: System,
+    refreshed_at: Option<Instant>,
+}
+
+static SNAPSHOT: LazyLock<Mutex<Snapshot>> = LazyLock::new(|| {
+    Mutex::new(Snapshot {
+        system: System::new(),
+        refreshed_at: None,
+    })
+});
+
+fn refresh_kind() -> ProcessRefreshKind {
+    // Name, parent, status and start time are always read. The command line,
+    // working directory and executable rarely change, so they are only read
+    // for processes we have not seen yet.
+    ProcessRefreshKind::nothing()
+        .with_cpu()
+        .with_memory()
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_cwd(UpdateKind::OnlyIfNotSet)
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .without_tasks()
+}
+
+/// Also re-reads what a process runs now: its command line, executable and
+/// working directory change when it execs.
+fn commands_refresh_kind() -> ProcessRefreshKind {
+    refresh_kind()
+        .with_cmd(UpdateKind::Always)
+        .with_exe(UpdateKind::Always)
+        .with_cwd(UpdateKind::Always)
+}
+
+/// Also reads the environment of processes whose environment was not read
+/// yet. It is kept afterwards, like the command line.
+fn environ_refresh_kind() -> ProcessRefreshKind {
+    refresh_kind().with_environ(UpdateKind::OnlyIfNotSet)
+}
+
+fn lock() -> MutexGuard<'static, Snapshot> {
+    SNAPSHOT.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn refresh_if_older(snapshot: &mut Snapshot, max_age: Duration) {
+    let fresh = snapshot
+        .refreshed_at
+        .is_some_and(|refreshed_at| refreshed_at.elapsed() < max_age);
+    if !fresh {
+        snapshot
+            .system
+            .refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind());
+        snapshot.refreshed_at = Some(Instant::now());
+    }
+}
+
+/// Runs `f` against the shared process table, refreshing it first when it
+/// is older than `max_age`. `Duration::ZERO` always refreshes.
+pub fn with_processes<T>(max_age: Duration, f: impl FnOnce(&System) -> T) -> T {
+    let mut snapshot = lock();
+    refresh_if_older(&mut snapshot, max_age);
+    f(&snapshot.system)
+}
+
+/// Runs `f` with `pid` looked up in the shared process table. A PID the
+/// table does not know yet (e.g. one that was just spawned) is refreshed on
+/// its own instead of waiting for the next full refresh.
+pub fn with_process<T>(pid: u32, max_age: Duration, f: impl FnOnce(Option<&Process>) -> T) -> T {
+    with_processes_mut(max_age, |system| {
+        let pid = Pid::from_u32(pid);
+        if system.process(pid).is_none() {
+            system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh_kind());
+        }
+        f(system.process(pid))
+    })
+}
+
+fn with_processes_mut<T>(max_age: Duration, f: impl FnOnce(&mut System) -> T) -> T {
+    let mut snapshot = lock();
+    refresh_if_older(&mut snapshot, max_age);
+    f(&mut snapshot.system)
+}
+
+/// Like [`with_processes`], for callers that also refresh system-wide state
+/// (CPU, memory) on the shared `System`.
+pub fn with_system<T>(max_age: Duration, f: impl FnOnce(&mut System) -> T) -> T {
+    with_processes_mut(max_age, f)
+}
+
+/// Runs `f` after re-reading `pids` (dropping those that exited), which
+/// costs a few reads. The rest of the table is only refreshed when it is
+/// older than `max_age`.
+pub fn with_refreshed<T>(pids: &[u32], max_age: Duration, f: impl FnOnce(&System) -> T) -> T {
+    let pids: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid)).collect();
+    let mut snapshot = lock();
+    refresh_if_older(&mut snapshot, max_age);
+    snapshot
+        .system
+        .refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, refresh_kind());
+    f(&snapshot.system)
+}
+
+/// Whether `pid` is running right now, re-reading just that PID.
+pub fn is_running_now(pid: u32) -> bool {
+    with_refreshed(&[pid], POLL_MAX_AGE, |system| is_running(system, pid))
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(error) => std::panic::resume_unwind(error.into_panic()),
+    }
+}
+
+/// [`with_processes`] for async callers.
+pub async fn query_processes<T, F>(max_age: Duration, f: F) -> T
+where
+    F: FnOnce(&System) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_processes(max_age, f)).await
+}
+
+/// [`with_process`] for async callers.
+pub async fn query_process<T, F>(pid: u32, max_age: Duration, f: F) -> T
+where
+    F: FnOnce(Option<&Process>) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_process(pid, max_age, f)).await
+}
+
+/// [`with_system`] for async callers.
+pub async fn query_system<T, F>(max_age: Duration, f: F) -> T
+where
+    F: FnOnce(&mut System) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_system(max_age, f)).await
+}
+
+/// [`with_refreshed`] for async callers.
+pub async fn query_refreshed<T, F>(pids: Vec<u32>, max_age: Duration, f: F) -> T
+where
+    F: FnOnce(&System) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_refreshed(&pids, max_age, f)).await
+}
+
+fn with_full_refresh<T>(kind: ProcessRefreshKind, f: impl FnOnce(&System) -> T) -> T {
+    let mut snapshot = lock();
+    snapshot
+        .system
+        .refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    snapshot.refreshed_at = Some(Instant::now());
+    f(&snapshot.system)
+}
+
+/// Runs `f` after a full refresh that also re-reads command lines,
+/// executables and working directories, for matching processes by what they
+/// run now.
+pub async fn query_fresh_commands<T, F>(f: F) -> T
+where
+    F: FnOnce(&System) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_full_refresh(commands_refresh_kind(), f)).await
+}
+
+/// Runs `f` after a full refresh that also reads process environments.
+pub async fn query_environ<T, F>(f: F) -> T
+where
+    F: FnOnce(&System) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking(move || with_full_refresh(environ_refresh_kind(), f)).await
+}
+
+/// Forces the next query to refresh, e.g. after we stopped a process.
+pub fn invalidate() {
+    lock().refreshed_at = None;
+}
+
+/// Zombies keep their PID until the parent reaps them but are no longer running.
+pub fn is_alive(process: &Process) -> bool {
+    process.status() != ProcessStatus::Zombie
+}
+
+pub fn is_running(system: &System, pid: u32) -> bool {
+    system.process(Pid::from_u32(pid)).is_some_and(is_alive)
+}
+
+/// What the UI polls for each tracked process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ProcessRunState {
+    pub running: bool,
+    /// Frozen by `suspend_action` or otherwise stopped.
+    pub suspended: bool,
+}
+
+/// Batch liveness check answered from a single refresh.
+#[tauri::command]
+pub async fn are_processes_running(
+    pids: Vec<u32>,
+) -> Result<HashMap<u32, ProcessRunState>, String> {
+    Ok(blocking(move || with_processes_mut(POLL_MAX_AGE, |system| {
+        let unknown: Vec<Pid> = pids
+            .iter()
+            .map(|&pid| Pid::from_u32(pid))
+            .filter(|&pid| system.process(pid).is_none())
+            .collect();
+        if !unknown.is_empty() {
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&unknown),
+                true,
+                refresh_kind(),
+            );
+        }
+        pids.iter()
+            .map(|&pid| {
+                let running = is_running(system, pid);
+                let suspended = running && crate::suspend::is_suspended_in(system, pid);
+                (pid, ProcessRunState { running, suspended })
+            })
+            .collect()
+    }))
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn batch_query_reports_each_pid() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id();
+
+        invalidate();
+        let running = are_processes_running(vec![pid, u32::MAX - 1])
+            .await
+            .expect("query");
+        assert!(running.get(&pid).is_some_and(|state| state.running && !state.suspended));
+        assert!(running.get(&(u32::MAX - 1)).is_some_and(|state| !state.running));
+
+        child.kill().expect("kill");
+        child.wait().expect("wait");
+        assert!(!with_process(pid, Duration::ZERO, |process| process.is_some()));
+    }
+
+    #[tokio::test]
+    async fn refreshing_a_pid_sees_its_exit() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id();
+
+        let running = query_refreshed(vec![pid], POLL_MAX_AGE, move |system| {
+            is_running(system, pid)
+        })
+        .await;
+        assert!(running);
+        child.kill().expect("kill");
+        child.wait().expect("wait");
+        assert!(!is_running_now(pid));
+    }
+}
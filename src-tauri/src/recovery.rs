@@ -16,7 +16,10 @@ pub struct AllData {
     pub tables: Vec<TableData>,
 }
 
-pub async fn rescue_data(db_path: &Path) -> Result<AllData, String> {
+/// Dumps every table of the database at `db_path`. Secure variable values
+/// still in plaintext are encrypted with the key in `key_dir` so the dump
+/// never holds them in the clear.
+pub async fn rescue_data(db_path: &Path, key_dir: &Path) -> Result<AllData, String> {
     if !db_path.exists() {
         return Err("Database file does not exist".to_string());
     }
@@ -70,17 +73,46 @@ pub async fn rescue_data(db_path: &Path) -> Result<AllData, String> {
             table_rows.push(row_values);
         }
 
-        all_data.tables.push(TableData {
+        let mut table = TableData {
             name: table_name,
             columns,
             rows: table_rows,
-        });
+        };
+        if matches!(
+            table.name.as_str(),
+            "variables" | "environment_variables" | "global_variables"
+        ) {
+            encrypt_secure_values(&mut table, key_dir);
+        }
+        all_data.tables.push(table);
     }
 
     pool.close().await;
     Ok(all_data)
 }
 
+fn encrypt_secure_values(table: &mut TableData, key_dir: &Path) {
+    let column = |name: &str| table.columns.iter().position(|c| c == name);
+    let (Some(value_index), Some(secure_index)) = (column("value"), column("is_secure")) else {
+        return;
+    };
+    for row in &mut table.rows {
+        if row[secure_index].as_i64() != Some(1) {
+            continue;
+        }
+        let Value::String(value) = &row[value_index] else {
+            continue;
+        };
+        row[value_index] = match crate::secure_store::encrypt(key_dir, value) {
+            Ok(encrypted) => Value::String(encrypted),
+            Err(e) => {
+                println!("Dropping secure value in {} that could not be encrypted: {}", table.name, e);
+                Value::String(String::new())
+            }
+        };
+    }
+}
+
 fn extract_value(row: &SqliteRow, index: usize) -> Value {
     let val_ref = row.try_get_raw(index).unwrap();
     if val_ref.is_null() {
@@ -179,3 +211,45 @@ pub async fn restore_data(db_path: &Path, data: AllData) -> Result<(), String> {
     pool.close().await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    #[tokio::test]
+    async fn rescued_secure_values_stay_encrypted() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        database::run_migrations(&db_path).await.expect("migrate");
+        let pool = database::connect(&db_path).await.expect("connect");
+        let stored = crate::secure_store::encrypt(dir.path(), "stored-secret").expect("encrypt");
+        sqlx::query(
+            "INSERT INTO global_variables (key, value, is_secure) VALUES \
+             ('TOKEN', 'plain-secret', 1), ('DB', ?, 1), ('REGION', 'eu', 0)",
+        )
+        .bind(&stored)
+        .execute(&pool)
+        .await
+        .expect("insert globals");
+        pool.close().await;
+
+        let data = rescue_data(&db_path, dir.path()).await.expect("rescue");
+        let dump = serde_json::to_string(&data).expect("serialize");
+        assert!(!dump.contains("plain-secret"));
+        assert!(dump.contains(&stored));
+        assert!(dump.contains("\"eu\""));
+
+        let table = data.tables.iter().find(|t| t.name == "global_variables").unwrap();
+        let value_index = table.columns.iter().position(|c| c == "value").unwrap();
+        let values: Vec<String> = table
+            .rows
+            .iter()
+            .filter_map(|row| row[value_index].as_str().map(str::to_string))
+            .filter(|value| crate::secure_store::is_encrypted(value))
+            .map(|value| crate::secure_store::decrypt(dir.path(), &value).expect("decrypt"))
+            .collect();
+        assert!(values.contains(&"plain-secret".to_string()));
+        assert!(values.contains(&"stored-secret".to_string()));
+    }
+}
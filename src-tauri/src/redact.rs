@@ -0,0 +1,136 @@
+//! Masks secure variable values in log messages before they are emitted as
+//! `action-log` events. A run's secrets are kept in its
+//! [`RunContext`](crate::run_context::RunContext) and also matched in their
+//! URL-encoded and base64 forms.
+
+use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+
+pub const MASK: &str = "********";
+
+/// Shorter values would mask unrelated output far too often.
+const MIN_SECRET_LEN: usize = 4;
+
+fn url_encode(value: &str, space_as_plus: bool) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' if space_as_plus => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The forms of `secret` to mask, longest first.
+pub fn secret_forms(secret: &str) -> Vec<String> {
+    if secret.len() < MIN_SECRET_LEN {
+        return Vec::new();
+    }
+    let mut forms = vec![
+        secret.to_string(),
+        // As it appears in `{:?}`-formatted argument lists.
+        secret.escape_debug().to_string(),
+        url_encode(secret, false),
+        url_encode(secret, true),
+        STANDARD_NO_PAD.encode(secret),
+        URL_SAFE_NO_PAD.encode(secret),
+    ];
+    forms.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    forms.dedup();
+    forms
+}
+
+/// Replaces every occurrence of `patterns` in `message` with [`MASK`].
+/// `patterns` must be ordered longest first.
+pub fn redact(message: &str, patterns: &[String]) -> String {
+    let mut redacted = message.to_string();
+    for pattern in patterns {
+        if redacted.contains(pattern.as_str()) {
+            redacted = redacted.replace(pattern.as_str(), MASK);
+        }
+    }
+    redacted
+}
+
+/// Every form of every secret in `secrets`, longest first.
+pub fn secret_patterns(secrets: &[String]) -> Vec<String> {
+    let mut patterns: Vec<String> = secrets.iter().flat_map(|s| secret_forms(s)).collect();
+    patterns.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    patterns.dedup();
+    patterns
+}
+
+/// Masks the secrets registered for `run_id` in `message`.
+pub fn redact_run(run_id: i64, message: &str) -> String {
+    match crate::run_context::run_context(run_id) {
+        Some(context) => redact(message, &context.secret_patterns),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_plain_url_encoded_and_base64_forms() {
+        let patterns = secret_forms("p@ss word/1");
+        let message = format!(
+            "raw=p@ss word/1 url=p%40ss%20word%2F1 form=p%40ss+word%2F1 b64={} b64url={}",
+            STANDARD_NO_PAD.encode("p@ss word/1"),
+            URL_SAFE_NO_PAD.encode("p@ss word/1"),
+        );
+        assert_eq!(
+            redact(&message, &patterns),
+            "raw=******** url=******** form=******** b64=******** b64url=********"
+        );
+        let header = format!(
+            "Authorization: Bearer {}",
+            base64::engine::general_purpose::STANDARD.encode("hunter22")
+        );
+        assert_eq!(
+            redact(&header, &secret_forms("hunter22")),
+            "Authorization: Bearer ********="
+        );
+    }
+
+    #[test]
+    fn masks_debug_formatted_arguments() {
+        let args = vec!["--password".to_string(), "say \"hi\"".to_string()];
+        let message = format!("Executing command: curl {:?}", args);
+        assert_eq!(
+            redact(&message, &secret_forms("say \"hi\"")),
+            "Executing command: curl [\"--password\", \"********\"]"
+        );
+    }
+
+    #[test]
+    fn ignores_short_secrets() {
+        assert!(secret_forms("abc").is_empty());
+        assert_eq!(redact("abc", &secret_forms("abc")), "abc");
+    }
+
+    #[test]
+    fn redacts_registered_runs_only() {
+        use crate::run_context::{register_run, release_run, RunContext};
+
+        let run_id = -38;
+        register_run(
+            run_id,
+            RunContext {
+                secret_patterns: secret_patterns(&["token-123".to_string(), "abc".to_string()]),
+                environment_id: None,
+            },
+        );
+        assert_eq!(
+            redact_run(run_id, "Executing: curl -H token-123 abc"),
+            "Executing: curl -H ******** abc"
+        );
+        release_run(run_id);
+        assert_eq!(redact_run(run_id, "token-123"), "token-123");
+    }
+}
@@ -0,0 +1,113 @@
+//! Per-run state read when emitting a run's logs and events: the secret
+//! patterns masked in its logs and the environment it was launched with. A
+//! run is held by its launch and by every child process still attached to
+//! it, and dropped once all of them have released it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+#[derive(Debug, Clone, Default)]
+pub struct RunContext {
+    /// Longest first, see [`crate::redact::secret_patterns`].
+    pub secret_patterns: Vec<String>,
+    pub environment_id: Option<i64>,
+}
+
+struct Entry {
+    context: Arc<RunContext>,
+    holders: usize,
+}
+
+static RUNS: LazyLock<Mutex<HashMap<i64, Entry>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static LAST_RUN_ID: AtomicI64 = AtomicI64::new(0);
+
+/// Allocates the id of a new run: its start time in milliseconds, moved past
+/// the previous id so that runs started in the same millisecond stay apart.
+pub fn new_run_id() -> i64 {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut run_id = now;
+    let _ = LAST_RUN_ID.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+        run_id = now.max(last + 1);
+        Some(run_id)
+    });
+    run_id
+}
+
+/// Sets the context of `run_id` and holds it until the matching
+/// [`release_run`]. A restart reusing the run replaces the context.
+pub fn register_run(run_id: i64, context: RunContext) {
+    if let Ok(mut runs) = RUNS.lock() {
+        let entry = runs.entry(run_id).or_insert(Entry {
+            context: Arc::default(),
+            holders: 0,
+        });
+        entry.context = Arc::new(context);
+        entry.holders += 1;
+    }
+}
+
+/// Keeps the run's context while a child process of it is alive or its
+/// output is still being read.
+pub fn retain_run(run_id: i64) {
+    if let Ok(mut runs) = RUNS.lock() {
+        if let Some(entry) = runs.get_mut(&run_id) {
+            entry.holders += 1;
+        }
+    }
+}
+
+pub fn release_run(run_id: i64) {
+    if let Ok(mut runs) = RUNS.lock() {
+        if let Some(entry) = runs.get_mut(&run_id) {
+            entry.holders = entry.holders.saturating_sub(1);
+            if entry.holders == 0 {
+                runs.remove(&run_id);
+            }
+        }
+    }
+}
+
+pub fn run_context(run_id: i64) -> Option<Arc<RunContext>> {
+    RUNS.lock()
+        .ok()?
+        .get(&run_id)
+        .map(|entry| entry.context.clone())
+}
+
+/// Runs still held by their launch or an attached child process.
+pub fn active_run_ids() -> Vec<i64> {
+    RUNS.lock()
+        .map(|runs| runs.keys().copied().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_context_until_every_holder_releases() {
+        let run_id = -39;
+        register_run(
+            run_id,
+            RunContext {
+                environment_id: Some(2),
+                ..RunContext::default()
+            },
+        );
+        retain_run(run_id);
+        release_run(run_id);
+        assert_eq!(run_context(run_id).and_then(|c| c.environment_id), Some(2));
+        release_run(run_id);
+        assert!(run_context(run_id).is_none());
+    }
+
+    #[test]
+    fn run_ids_are_unique_within_a_millisecond() {
+        let ids: Vec<i64> = (0..100).map(|_| new_run_id()).collect();
+        assert!(ids.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(ids[0] >= chrono::Utc::now().timestamp_millis() - 60_000);
+    }
+}
@@ -0,0 +1,533 @@
+//! Encryption at rest for secure variable values.
+//!
+//! Secure values are stored as `enc:v1:<key id>:<base64(nonce || ciphertext)>`
+//! using XChaCha20-Poly1305, with the key id as associated data. The key lives
+//! either in `secure-store.json` in the app data dir or is derived from a user
+//! passphrase with Argon2id, in which case only a salt and a verifier are
+//! stored and the store must be unlocked once per session. Values are only
+//! decrypted when an action is launched.
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use tauri::Manager;
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEY_FILE_NAME: &str = "secure-store.json";
+const NONCE_LEN: usize = 24;
+const VERIFIER_PLAINTEXT: &str = "workspacelauncher-secure-store";
+const SECURE_TABLES: [&str; 3] = ["variables", "environment_variables", "global_variables"];
+
+type Key = [u8; 32];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum KeyMaterial {
+    /// Random key stored in the key file.
+    File { key: String },
+    /// Key derived from a passphrase; `verifier` is a known value encrypted
+    /// with it, used to reject wrong passphrases.
+    Passphrase { salt: String, verifier: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredKey {
+    id: String,
+    #[serde(flatten)]
+    material: KeyMaterial,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyStore {
+    active: StoredKey,
+    /// Keys kept only while a rotation is in progress.
+    #[serde(default)]
+    previous: Vec<StoredKey>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecureStoreStatus {
+    pub passphrase: bool,
+    pub locked: bool,
+}
+
+/// Passphrase-derived keys unlocked in this session, by key id.
+static UNLOCKED: LazyLock<Mutex<HashMap<String, Key>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+pub fn key_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path().app_local_data_dir().map_err(|e| e.to_string())
+}
+
+fn key_file(dir: &Path) -> PathBuf {
+    dir.join(KEY_FILE_NAME)
+}
+
+fn new_key_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
+
+fn read_store(dir: &Path) -> Result<Option<KeyStore>, String> {
+    let path = key_file(dir);
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("Invalid key file {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read key file {}: {}", path.display(), e)),
+    }
+}
+
+fn load_store(dir: &Path) -> Result<KeyStore, String> {
+    read_store(dir)?.ok_or_else(|| "Secure store has no key".to_string())
+}
+
+/// Writes the key file through a temporary file so a crash never leaves a
+/// truncated key behind.
+fn write_store(dir: &Path, store: &KeyStore) -> Result<(), String> {
+    let path = key_file(dir);
+    let tmp = dir.join(format!("{}.tmp", KEY_FILE_NAME));
+    let contents = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| format!("Failed to write key file {}: {}", tmp.display(), e))?;
+    std::io::Write::write_all(&mut file, contents.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write key file {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to replace key file {}: {}", path.display(), e))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn seal(key: &Key, key_id: &str, plaintext: &str) -> Result<String, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad: key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| "Failed to encrypt value".to_string())?;
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}:{}", ENCRYPTED_PREFIX, key_id, BASE64.encode(payload)))
+}
+
+/// Splits an encrypted value into its key id and decoded payload.
+fn parse(value: &str) -> Result<(&str, Vec<u8>), String> {
+    let body = value
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or("Value is not encrypted")?;
+    let (key_id, payload) = body.split_once(':').ok_or("Malformed encrypted value")?;
+    let payload = BASE64
+        .decode(payload)
+        .map_err(|_| "Malformed encrypted value".to_string())?;
+    if payload.len() < NONCE_LEN {
+        return Err("Malformed encrypted value".to_string());
+    }
+    Ok((key_id, payload))
+}
+
+fn open(key: &Key, value: &str) -> Result<String, String> {
+    let (key_id, payload) = parse(value)?;
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| "Failed to decrypt value: wrong key or corrupted data".to_string())?;
+    String::from_utf8(plaintext).map_err(|_| "Decrypted value is not valid UTF-8".to_string())
+}
+
+fn new_file_key() -> (StoredKey, Key) {
+    let key: Key = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+    let stored = StoredKey {
+        id: new_key_id(),
+        material: KeyMaterial::File {
+            key: BASE64.encode(key),
+        },
+    };
+    (stored, key)
+}
+
+fn new_passphrase_key(passphrase: &str) -> Result<(StoredKey, Key), String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let id = new_key_id();
+    let verifier = seal(&key, &id, VERIFIER_PLAINTEXT)?;
+    Ok((
+        StoredKey {
+            id,
+            material: KeyMaterial::Passphrase {
+                salt: BASE64.encode(salt),
+                verifier,
+            },
+        },
+        key,
+    ))
+}
+
+fn remember(id: &str, key: Key) {
+    if let Ok(mut unlocked) = UNLOCKED.lock() {
+        unlocked.insert(id.to_string(), key);
+    }
+}
+
+fn resolve_key(stored: &StoredKey) -> Result<Key, String> {
+    match &stored.material {
+        KeyMaterial::File { key } => BASE64
+            .decode(key)
+            .ok()
+            .and_then(|bytes| Key::try_from(bytes).ok())
+            .ok_or_else(|| format!("Invalid key {} in key file", stored.id)),
+        KeyMaterial::Passphrase { .. } => UNLOCKED
+            .lock()
+            .ok()
+            .and_then(|unlocked| unlocked.get(&stored.id).copied())
+            .ok_or_else(|| "Secure store is locked; unlock it with your passphrase".to_string()),
+    }
+}
+
+fn key_by_id(store: &KeyStore, id: &str) -> Result<Key, String> {
+    std::iter::once(&store.active)
+        .chain(&store.previous)
+        .find(|stored| stored.id == id)
+        .ok_or_else(|| format!("Unknown encryption key {}", id))
+        .and_then(resolve_key)
+}
+
+/// Creates a key file with a random key if none exists yet.
+pub fn ensure_key(dir: &Path) -> Result<(), String> {
+    if read_store(dir)?.is_some() {
+        return Ok(());
+    }
+    let (active, _) = new_file_key();
+    write_store(
+        dir,
+        &KeyStore {
+            active,
+            previous: Vec::new(),
+        },
+    )
+}
+
+/// Encrypts `plaintext` with the active key. Already encrypted values are
+/// returned unchanged.
+pub fn encrypt(dir: &Path, plaintext: &str) -> Result<String, String> {
+    if is_encrypted(plaintext) {
+        return Ok(plaintext.to_string());
+    }
+    ensure_key(dir)?;
+    let store = load_store(dir)?;
+    seal(&resolve_key(&store.active)?, &store.active.id, plaintext)
+}
+
+/// Decrypts `value`; values without the encryption prefix are returned as-is.
+pub fn decrypt(dir: &Path, value: &str) -> Result<String, String> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+    let (key_id, _) = parse(value)?;
+    open(&key_by_id(&load_store(dir)?, key_id)?, value)
+}
+
+/// Derives the passphrase keys in the key file and keeps them for this
+/// session.
+pub fn unlock(dir: &Path, passphrase: &str) -> Result<(), String> {
+    let store = load_store(dir)?;
+    let mut any = false;
+    for stored in std::iter::once(&store.active).chain(&store.previous) {
+        let KeyMaterial::Passphrase { salt, verifier } = &stored.material else {
+            continue;
+        };
+        any = true;
+        let salt = BASE64
+            .decode(salt)
+            .map_err(|_| format!("Invalid salt for key {}", stored.id))?;
+        let key = derive_key(passphrase, &salt)?;
+        if open(&key, verifier).ok().as_deref() != Some(VERIFIER_PLAINTEXT) {
+            return Err("Wrong passphrase".to_string());
+        }
+        remember(&stored.id, key);
+    }
+    if !any {
+        return Err("Secure store does not use a passphrase".to_string());
+    }
+    Ok(())
+}
+
+pub fn status(dir: &Path) -> Result<SecureStoreStatus, String> {
+    let Some(store) = read_store(dir)? else {
+        return Ok(SecureStoreStatus {
+            passphrase: false,
+            locked: false,
+        });
+    };
+    Ok(SecureStoreStatus {
+        passphrase: matches!(store.active.material, KeyMaterial::Passphrase { .. }),
+        locked: resolve_key(&store.active).is_err(),
+    })
+}
+
+async fn secure_values(pool: &SqlitePool) -> Result<Vec<(&'static str, i64, String)>, String> {
+    let mut values = Vec::new();
+    for table in SECURE_TABLES {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as(&format!("SELECT id, value FROM {} WHERE is_secure = 1", table))
+                .fetch_all(pool)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+        values.extend(rows.into_iter().map(|(id, value)| (table, id, value)));
+    }
+    Ok(values)
+}
+
+async fn write_values(
+    pool: &SqlitePool,
+    values: Vec<(&'static str, i64, String)>,
+) -> Result<usize, String> {
+    let count = values.len();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for (table, id, value) in values {
+        sqlx::query(&format!("UPDATE {} SET value = ? WHERE id = ?", table))
+            .bind(value)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to update {} {}: {}", table, id, e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    Ok(count)
+}
+
+/// Encrypts secure values still stored in plaintext, e.g. rows written
+/// before encryption at rest existed. Returns how many were encrypted.
+pub async fn encrypt_plaintext_values(dir: &Path, pool: &SqlitePool) -> Result<usize, String> {
+    let mut updates = Vec::new();
+    for (table, id, value) in secure_values(pool).await? {
+        if !is_encrypted(&value) {
+            updates.push((table, id, encrypt(dir, &value)?));
+        }
+    }
+    if updates.is_empty() {
+        return Ok(0);
+    }
+    write_values(pool, updates).await
+}
+
+/// Replaces the active key with a new random key, or one derived from
+/// `passphrase`, and re-encrypts every secure value. The old key stays in the
+/// key file until the database has been updated, so an interrupted rotation
+/// can simply be retried.
+pub async fn rotate(
+    dir: &Path,
+    pool: &SqlitePool,
+    passphrase: Option<&str>,
+) -> Result<usize, String> {
+    ensure_key(dir)?;
+    let store = load_store(dir)?;
+    let current = secure_values(pool).await?;
+    let plaintexts = current
+        .into_iter()
+        .map(|(table, id, value)| {
+            let (key_id, _) = match is_encrypted(&value) {
+                true => parse(&value)?,
+                false => return Ok((table, id, value)),
+            };
+            let plaintext = open(&key_by_id(&store, key_id)?, &value)?;
+            Ok((table, id, plaintext))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (active, key) = match passphrase {
+        Some(passphrase) => new_passphrase_key(passphrase)?,
+        None => new_file_key(),
+    };
+    remember(&active.id, key);
+    let mut previous = vec![store.active];
+    previous.extend(store.previous);
+    write_store(
+        dir,
+        &KeyStore {
+            active: active.clone(),
+            previous,
+        },
+    )?;
+
+    let updates = plaintexts
+        .into_iter()
+        .map(|(table, id, plaintext)| Ok((table, id, seal(&key, &active.id, &plaintext)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let count = write_values(pool, updates).await?;
+
+    write_store(
+        dir,
+        &KeyStore {
+            active,
+            previous: Vec::new(),
+        },
+    )?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn encrypt_secure_value(app: tauri::AppHandle, value: String) -> Result<String, String> {
+    encrypt(&key_dir(&app)?, &value)
+}
+
+#[tauri::command]
+pub fn unlock_secure_store(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    unlock(&key_dir(&app)?, &passphrase)
+}
+
+#[tauri::command]
+pub fn get_secure_store_status(app: tauri::AppHandle) -> Result<SecureStoreStatus, String> {
+    status(&key_dir(&app)?)
+}
+
+/// Rotates the encryption key; with a passphrase the new key is derived from
+/// it, otherwise a random key is stored in the key file.
+#[tauri::command]
+pub async fn rotate_secure_key(
+    app: tauri::AppHandle,
+    passphrase: Option<String>,
+) -> Result<usize, String> {
+    let dir = key_dir(&app)?;
+    let pool = crate::database::connect_app_db(&app).await?;
+    let result = rotate(&dir, &pool, passphrase.as_deref()).await;
+    pool.close().await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    #[test]
+    fn round_trips_and_rejects_tampering() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let encrypted = encrypt(dir.path(), "hunter2").expect("encrypt");
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("hunter2"));
+        assert_ne!(encrypt(dir.path(), "hunter2").unwrap(), encrypted);
+        assert_eq!(encrypt(dir.path(), &encrypted).unwrap(), encrypted);
+        assert_eq!(decrypt(dir.path(), &encrypted).unwrap(), "hunter2");
+        assert_eq!(decrypt(dir.path(), "plain").unwrap(), "plain");
+
+        let (key_id, mut payload) = parse(&encrypted).unwrap();
+        let last = payload.len() - 1;
+        payload[last] ^= 1;
+        let tampered = format!("{}{}:{}", ENCRYPTED_PREFIX, key_id, BASE64.encode(&payload));
+        assert!(decrypt(dir.path(), &tampered).unwrap_err().contains("wrong key"));
+
+        let other = tempfile::tempdir().expect("tempdir");
+        assert!(decrypt(other.path(), &encrypted).is_err());
+    }
+
+    #[tokio::test]
+    async fn rotates_keys_and_re_encrypts_database_values() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        database::run_migrations(&db_path).await.expect("migrate");
+        let pool = database::connect(&db_path).await.expect("connect");
+        sqlx::query("INSERT INTO workspaces (id, name) VALUES (1, 'api')")
+            .execute(&pool)
+            .await
+            .expect("insert workspace");
+        let old = encrypt(dir.path(), "db-password").expect("encrypt");
+        sqlx::query(
+            "INSERT INTO variables (workspace_id, key, value, is_secure) VALUES \
+             (1, 'DB', ?, 1), (1, 'LEGACY', 'plain-token', 1), (1, 'PORT', '8080', 0)",
+        )
+        .bind(&old)
+        .execute(&pool)
+        .await
+        .expect("insert variables");
+
+        assert_eq!(rotate(dir.path(), &pool, Some("correct horse")).await.unwrap(), 2);
+        let values: Vec<(String, String)> =
+            sqlx::query_as("SELECT key, value FROM variables ORDER BY key")
+                .fetch_all(&pool)
+                .await
+                .expect("select");
+        pool.close().await;
+
+        let store = load_store(dir.path()).expect("store");
+        assert!(store.previous.is_empty());
+        assert!(status(dir.path()).unwrap().passphrase);
+        let value = |key: &str| values.iter().find(|(k, _)| k == key).unwrap().1.clone();
+        assert_ne!(value("DB"), old);
+        assert!(decrypt(dir.path(), &old).unwrap_err().contains("Unknown encryption key"));
+        assert_eq!(decrypt(dir.path(), &value("DB")).unwrap(), "db-password");
+        assert_eq!(decrypt(dir.path(), &value("LEGACY")).unwrap(), "plain-token");
+        assert_eq!(value("PORT"), "8080");
+
+        assert_eq!(unlock(dir.path(), "wrong").unwrap_err(), "Wrong passphrase");
+        unlock(dir.path(), "correct horse").expect("unlock");
+    }
+
+    #[test]
+    fn passphrase_keys_need_unlocking() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let (active, key) = new_passphrase_key("s3cret").expect("key");
+        let encrypted = seal(&key, &active.id, "value").expect("seal");
+        write_store(
+            dir.path(),
+            &KeyStore {
+                active,
+                previous: Vec::new(),
+            },
+        )
+        .expect("write");
+
+        assert!(status(dir.path()).unwrap().locked);
+        assert!(decrypt(dir.path(), &encrypted).unwrap_err().contains("locked"));
+        unlock(dir.path(), "s3cret").expect("unlock");
+        assert!(!status(dir.path()).unwrap().locked);
+        assert_eq!(decrypt(dir.path(), &encrypted).unwrap(), "value");
+    }
+}
@@ -0,0 +1,248 @@
+//! Pausing an action without stopping it: SIGSTOP freezes its process group
+//! (or, without a recorded group, every process of its tree) and SIGCONT
+//! lets it carry on where it was. Only supported on Unix.
+
+use crate::process::{self, ProcessIdentity};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+
+/// Tracked PIDs whose action is currently suspended.
+static SUSPENDED: LazyLock<Mutex<HashSet<u32>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspendResult {
+    pub action_id: i64,
+    pub pid: u32,
+    pub suspended: bool,
+    pub message: String,
+}
+
+pub fn is_suspended(pid: u32) -> bool {
+    SUSPENDED
+        .lock()
+        .map(|suspended| suspended.contains(&pid))
+        .unwrap_or(false)
+}
+
+/// Drops the suspended state of a process that was stopped or exited.
+pub fn forget(pid: u32) {
+    if let Ok(mut suspended) = SUSPENDED.lock() {
+        suspended.remove(&pid);
+    }
+}
+
+/// Also true for a process stopped before a restart or by job control.
+pub fn is_suspended_in(system: &sysinfo::System, pid: u32) -> bool {
+    is_suspended(pid)
+        || system
+            .process(sysinfo::Pid::from_u32(pid))
+            .is_some_and(|p| p.status() == sysinfo::ProcessStatus::Stop)
+}
+
+/// [`is_suspended_in`] for async callers.
+pub async fn currently_suspended(pid: u32) -> bool {
+    use crate::process_snapshot::{self, POLL_MAX_AGE};
+
+    process_snapshot::query_processes(POLL_MAX_AGE, move |system| is_suspended_in(system, pid))
+        .await
+}
+
+fn set_suspended(pid: u32, suspended: bool) {
+    if let Ok(mut pids) = SUSPENDED.lock() {
+        if suspended {
+            pids.insert(pid);
+        } else {
+            pids.remove(&pid);
+        }
+    }
+}
+
+/// The tracked process and its live descendants, with identities so that a
+/// PID reused in the meantime is not signalled.
+#[cfg(unix)]
+async fn tree_identities(pid: u32) -> Vec<ProcessIdentity> {
+    use crate::process_snapshot::{self, is_alive};
+    use sysinfo::Pid;
+
+    process_snapshot::query_processes(std::time::Duration::ZERO, move |system| {
+        let root = Pid::from_u32(pid);
+        system
+            .process(root)
+            .into_iter()
+            .chain(process::collect_filtered_descendants(system, root, &[]))
+            .filter(|p| is_alive(p))
+            .map(|p| ProcessIdentity {
+                pid: p.pid().as_u32(),
+                start_time_secs: p.start_time(),
+                name: p.name().to_string_lossy().to_string(),
+            })
+            .collect()
+    })
+    .await
+}
+
+/// Sends `signal` to the process group `pid` was spawned into and to each
+/// process of its tree. The group alone misses a tracked process that left
+/// it (setsid); repeating SIGSTOP or SIGCONT is harmless.
+#[cfg(unix)]
+async fn signal_action(pid: u32, signal: nix::sys::signal::Signal) -> Result<(), String> {
+    use nix::sys::signal::{kill, killpg};
+    use nix::unistd::Pid as UnixPid;
+
+    let group_result = process::process_group_for_pid(pid).map(|group| {
+        killpg(UnixPid::from_raw(group.pgid), signal)
+            .map_err(|e| format!("Failed to signal process group {}: {}", group.pgid, e))
+    });
+    let mut tree = tree_identities(pid).await;
+    // Stop parents before their children so nothing is respawned meanwhile,
+    // and continue children before their parents.
+    if signal == nix::sys::signal::Signal::SIGCONT {
+        tree.reverse();
+    }
+    let mut signalled = 0;
+    for identity in &tree {
+        let same = process::query_identity(identity.pid)
+            .await
+            .is_some_and(|current| current.is_same_process(identity));
+        if same && kill(UnixPid::from_raw(identity.pid as i32), signal).is_ok() {
+            signalled += 1;
+        }
+    }
+    match group_result {
+        Some(Ok(())) => Ok(()),
+        _ if signalled > 0 => Ok(()),
+        Some(Err(error)) => Err(error),
+        None if tree.is_empty() => Err(format!("Process {} is not running", pid)),
+        None => Err(format!("Failed to signal process {}", pid)),
+    }
+}
+
+/// Freezes (`suspend`) or continues the tracked process `pid` and its tree.
+#[cfg(unix)]
+pub async fn set_process_suspended(pid: u32, suspend: bool) -> Result<(), String> {
+    use nix::sys::signal::Signal;
+
+    let signal = if suspend { Signal::SIGSTOP } else { Signal::SIGCONT };
+    signal_action(pid, signal).await?;
+    set_suspended(pid, suspend);
+    crate::process_snapshot::invalidate();
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn set_process_suspended(_pid: u32, _suspend: bool) -> Result<(), String> {
+    Err("Suspending actions is only supported on Unix".to_string())
+}
+
+async fn set_action_suspended(action_id: i64, suspend: bool) -> Result<SuspendResult, String> {
+    let instance = process::find_running_action_instance(action_id)
+        .await
+        .ok_or_else(|| format!("Action {} has no running process", action_id))?;
+    let pid = instance.identity.pid;
+    if currently_suspended(pid).await == suspend {
+        return Ok(SuspendResult {
+            action_id,
+            pid,
+            suspended: suspend,
+            message: format!(
+                "Process {} is already {}",
+                pid,
+                if suspend { "suspended" } else { "running" }
+            ),
+        });
+    }
+    set_process_suspended(pid, suspend).await?;
+    Ok(SuspendResult {
+        action_id,
+        pid,
+        suspended: suspend,
+        message: format!(
+            "Process {} {}",
+            pid,
+            if suspend { "suspended" } else { "resumed" }
+        ),
+    })
+}
+
+/// Pauses the running instance of an action and everything it spawned.
+#[tauri::command]
+pub async fn suspend_action(action_id: i64) -> Result<SuspendResult, String> {
+    set_action_suspended(action_id, true).await
+}
+
+/// Continues an action paused by [`suspend_action`].
+#[tauri::command]
+pub async fn resume_action(action_id: i64) -> Result<SuspendResult, String> {
+    set_action_suspended(action_id, false).await
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::process::ActionInstance;
+    use sysinfo::{Pid, ProcessStatus};
+    use tokio::time::{sleep, Duration};
+
+    fn status_of(pid: u32) -> Option<ProcessStatus> {
+        crate::process_snapshot::with_refreshed(&[pid], Duration::MAX, |system| {
+            system.process(Pid::from_u32(pid)).map(|p| p.status())
+        })
+    }
+
+    #[tokio::test]
+    async fn suspends_and_resumes_a_sleeping_child() {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "sleep 30 & wait"])
+            .spawn()
+            .expect("spawn script");
+        let pid = child.id().expect("pid");
+        sleep(Duration::from_millis(200)).await;
+
+        let action_id = -49;
+        let identity = process::register_tracked_pid(pid).expect("identity");
+        process::insert_action_instance(ActionInstance {
+            action_id,
+            run_id: -49,
+            identity,
+        });
+
+        let result = suspend_action(action_id).await.expect("suspend");
+        assert!(result.suspended);
+        assert!(is_suspended(pid));
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(status_of(pid), Some(ProcessStatus::Stop));
+        let sleeper = tree_identities(pid)
+            .await
+            .into_iter()
+            .find(|identity| identity.pid != pid)
+            .expect("sleeping child");
+        assert_eq!(status_of(sleeper.pid), Some(ProcessStatus::Stop));
+
+        let polled = crate::process_snapshot::are_processes_running(vec![pid])
+            .await
+            .expect("poll");
+        assert!(polled[&pid].suspended, "the polled status reports the suspension");
+
+        let again = suspend_action(action_id).await.expect("suspend again");
+        assert!(again.message.contains("already suspended"), "{}", again.message);
+
+        let result = resume_action(action_id).await.expect("resume");
+        assert!(!result.suspended);
+        assert!(!is_suspended(pid));
+        sleep(Duration::from_millis(100)).await;
+        assert_ne!(status_of(pid), Some(ProcessStatus::Stop));
+        assert_ne!(status_of(sleeper.pid), Some(ProcessStatus::Stop));
+
+        // A suspended action can still be stopped.
+        suspend_action(action_id).await.expect("suspend");
+        let stopped = process::kill_process(pid).await.expect("kill");
+        assert!(stopped.success, "{}", stopped.message);
+        assert!(!is_suspended(pid));
+        let _ = child.wait().await;
+        let _ = nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(sleeper.pid as i32),
+            nix::sys::signal::Signal::SIGKILL,
+        );
+    }
+}
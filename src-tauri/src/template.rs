@@ -0,0 +1,363 @@
+//! `${...}` variable substitution for action commands, arguments, working
+//! directories and URLs.
+//!
+//! Supported forms:
+//! - `${NAME}`: value of `NAME`; values may reference other variables.
+//! - `${NAME:-default}`: `default` when `NAME` is unset or empty.
+//! - `${NAME:?message}`: fails with `message` when `NAME` is unset or empty.
+//! - `${env:NAME}`: the launcher's own environment variable `NAME`.
+//! - `$${...}`: a literal `${...}`.
+//!
+//! Unknown names are left untouched unless `strict` is set, in which case the
+//! render fails listing every unresolved name.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Reference {
+        name: String,
+        operator: Option<Operator>,
+        raw: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operator {
+    Default(Vec<Segment>),
+    Required(String),
+}
+
+/// Source of raw variable values. Values are templates themselves and may
+/// reference other variables.
+pub trait VariableLookup: Sync {
+    fn lookup(&self, name: &str) -> Option<String>;
+}
+
+impl VariableLookup for HashMap<String, String> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// Process environment variable `name`, escaped so it is taken literally.
+pub fn process_env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|value| escape(&value))
+}
+
+/// Escapes `input` so it renders back to itself.
+pub fn escape(input: &str) -> String {
+    input.replace("${", "$${")
+}
+
+pub fn render(
+    input: &str,
+    variables: &dyn VariableLookup,
+    strict: bool,
+) -> Result<String, String> {
+    let mut renderer = Renderer {
+        variables,
+        strict,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    let segments = parse(input, strict)?;
+    let output = renderer.render_segments(&segments)?;
+    if strict && !renderer.unresolved.is_empty() {
+        return Err(format!(
+            "Unresolved variables: {}",
+            renderer.unresolved.join(", ")
+        ));
+    }
+    Ok(output)
+}
+
+fn push_text(segments: &mut Vec<Segment>, text: &str) {
+    if let Some(Segment::Text(last)) = segments.last_mut() {
+        last.push_str(text);
+    } else if !text.is_empty() {
+        segments.push(Segment::Text(text.to_string()));
+    }
+}
+
+/// Byte length of the `${...}` body starting at `body` (just after `${`),
+/// or `None` if it is never closed. Nested `${` / `$${` pair with their own
+/// closing brace.
+pub fn find_closing_brace(body: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut index = 0;
+    while index < body.len() {
+        let rest = &body[index..];
+        if rest.starts_with("$${") {
+            depth += 1;
+            index += 3;
+        } else if rest.starts_with("${") {
+            depth += 1;
+            index += 2;
+        } else if rest.starts_with('}') {
+            depth -= 1;
+            if depth == 0 {
+                return Some(index);
+            }
+            index += 1;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+fn parse(input: &str, strict: bool) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = input;
+
+    while let Some(dollar) = rest.find('$') {
+        push_text(&mut segments, &rest[..dollar]);
+        rest = &rest[dollar..];
+
+        if rest.starts_with("$${") {
+            push_text(&mut segments, "${");
+            rest = &rest[3..];
+            continue;
+        }
+        if !rest.starts_with("${") {
+            push_text(&mut segments, "$");
+            rest = &rest[1..];
+            continue;
+        }
+
+        let Some(len) = find_closing_brace(&rest[2..]) else {
+            if strict {
+                return Err(format!("Unterminated ${{ in \"{}\"", input));
+            }
+            push_text(&mut segments, rest);
+            rest = "";
+            break;
+        };
+        let raw = &rest[..len + 3];
+        let body = &rest[2..len + 2];
+        rest = &rest[len + 3..];
+
+        let (name, operator) = match (body.find(":-"), body.find(":?")) {
+            (Some(d), q) if q.is_none_or(|q| d < q) => (
+                &body[..d],
+                Some(Operator::Default(parse(&body[d + 2..], strict)?)),
+            ),
+            (_, Some(q)) => (&body[..q], Some(Operator::Required(body[q + 2..].to_string()))),
+            _ => (body, None),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            if strict {
+                return Err(format!("Empty variable name in {}", raw));
+            }
+            push_text(&mut segments, raw);
+            continue;
+        }
+        segments.push(Segment::Reference {
+            name: name.to_string(),
+            operator,
+            raw: raw.to_string(),
+        });
+    }
+    push_text(&mut segments, rest);
+    Ok(segments)
+}
+
+struct Renderer<'a> {
+    variables: &'a dyn VariableLookup,
+    strict: bool,
+    resolved: HashMap<String, String>,
+    stack: Vec<String>,
+    unresolved: Vec<String>,
+}
+
+impl Renderer<'_> {
+    fn render_segments(&mut self, segments: &[Segment]) -> Result<String, String> {
+        let mut output = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Reference {
+                    name,
+                    operator,
+                    raw,
+                } => {
+                    let value = self.lookup(name)?;
+                    let is_set = value.as_ref().is_some_and(|value| !value.is_empty());
+                    match (value, operator) {
+                        (Some(value), None) => output.push_str(&value),
+                        (Some(value), Some(_)) if is_set => output.push_str(&value),
+                        (_, Some(Operator::Default(default))) => {
+                            output.push_str(&self.render_segments(default)?)
+                        }
+                        (_, Some(Operator::Required(message))) => {
+                            return Err(if message.trim().is_empty() {
+                                format!("Variable {} is required", name)
+                            } else {
+                                format!("{}: {}", name, message.trim())
+                            });
+                        }
+                        (None, None) => {
+                            if !self.unresolved.contains(name) {
+                                self.unresolved.push(name.clone());
+                            }
+                            output.push_str(raw);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Fully rendered value of `name`, or `None` if it is not defined.
+    fn lookup(&mut self, name: &str) -> Result<Option<String>, String> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        if let Some(env_name) = name.strip_prefix("env:") {
+            return Ok(std::env::var(env_name).ok());
+        }
+        let Some(raw) = self.variables.lookup(name) else {
+            return Ok(None);
+        };
+        if let Some(start) = self.stack.iter().position(|entry| entry == name) {
+            let mut cycle = self.stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("Variable cycle detected: {}", cycle.join(" -> ")));
+        }
+
+        self.stack.push(name.to_string());
+        let segments = parse(&raw, self.strict)?;
+        let value = self.render_segments(&segments);
+        self.stack.pop();
+        let value = value?;
+        self.resolved.insert(name.to_string(), value.clone());
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_defaults_and_required() {
+        let variables = vars(&[("PORT", "3000"), ("EMPTY", "")]);
+        assert_eq!(
+            render("serve --port ${PORT:-8080} --host ${HOST:-localhost}", &variables, false).unwrap(),
+            "serve --port 3000 --host localhost"
+        );
+        assert_eq!(render("${EMPTY:-fallback}", &variables, false).unwrap(), "fallback");
+        assert_eq!(render("${HOST:-${PORT}}", &variables, false).unwrap(), "3000");
+
+        let err = render("${TOKEN:?set TOKEN in workspace variables}", &variables, false).unwrap_err();
+        assert_eq!(err, "TOKEN: set TOKEN in workspace variables");
+        assert_eq!(render("${PORT:?unused}", &variables, false).unwrap(), "3000");
+    }
+
+    #[test]
+    fn escapes_literal_references() {
+        let variables = vars(&[("HOME", "/home/me")]);
+        assert_eq!(
+            render("echo $${HOME} is ${HOME}", &variables, true).unwrap(),
+            "echo ${HOME} is /home/me"
+        );
+        assert_eq!(render("cost: $5 and $$", &variables, true).unwrap(), "cost: $5 and $$");
+    }
+
+    #[test]
+    fn resolves_nested_references_independent_of_order() {
+        let variables = vars(&[
+            ("ROOT", "/srv"),
+            ("APP_DIR", "${ROOT}/app"),
+            ("LOG_DIR", "${APP_DIR}/logs"),
+        ]);
+        assert_eq!(render("${LOG_DIR}", &variables, true).unwrap(), "/srv/app/logs");
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let variables = vars(&[("A", "${B}"), ("B", "x${A}"), ("SELF", "${SELF}")]);
+        let err = render("${A}", &variables, false).unwrap_err();
+        assert_eq!(err, "Variable cycle detected: A -> B -> A");
+        assert!(render("${SELF}", &variables, false).unwrap_err().contains("SELF -> SELF"));
+    }
+
+    #[test]
+    fn strict_mode_lists_unresolved_names() {
+        let variables = vars(&[("NESTED", "${MISSING_TOO}")]);
+        assert_eq!(
+            render("${MISSING} ${NESTED} ${MISSING}", &variables, false).unwrap(),
+            "${MISSING} ${MISSING_TOO} ${MISSING}"
+        );
+        let err = render("${MISSING} ${NESTED}", &variables, true).unwrap_err();
+        assert_eq!(err, "Unresolved variables: MISSING, MISSING_TOO");
+        assert!(render("oops ${UNCLOSED", &variables, true).is_err());
+        assert_eq!(render("oops ${UNCLOSED", &variables, false).unwrap(), "oops ${UNCLOSED");
+    }
+
+    #[test]
+    fn reads_process_environment() {
+        std::env::set_var("WSL_TEMPLATE_TEST_ENV", "from-env ${NOT_A_VAR}");
+        let variables = vars(&[("LOCAL", "local")]);
+        assert_eq!(
+            render("${env:WSL_TEMPLATE_TEST_ENV}", &variables, true).unwrap(),
+            "from-env ${NOT_A_VAR}"
+        );
+        assert_eq!(
+            render("${env:WSL_TEMPLATE_TEST_UNSET:-3000}", &variables, true).unwrap(),
+            "3000"
+        );
+        assert!(render("${WSL_TEMPLATE_TEST_ENV}", &variables, true).is_err());
+        assert_eq!(
+            render(
+                &process_env_value("WSL_TEMPLATE_TEST_ENV").unwrap(),
+                &variables,
+                true
+            )
+            .unwrap(),
+            "from-env ${NOT_A_VAR}"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn text_without_dollar_is_unchanged(input in "[^$]*") {
+            prop_assert_eq!(render(&input, &HashMap::new(), true).unwrap(), input);
+        }
+
+        #[test]
+        fn escaped_input_renders_to_itself(input in ".*") {
+            let variables = vars(&[("A", "x")]);
+            prop_assert_eq!(render(&escape(&input), &variables, true).unwrap(), input);
+        }
+
+        #[test]
+        fn substitutes_plain_values(
+            values in proptest::collection::hash_map("[A-Z_]{1,8}", "[^$]*", 1..6),
+        ) {
+            for (name, value) in &values {
+                let input = format!("<${{{}}}>", name);
+                prop_assert_eq!(render(&input, &values, true).unwrap(), format!("<{}>", value));
+            }
+        }
+
+        #[test]
+        fn never_panics(input in "[$a-z{}:?\\-]*", strict in any::<bool>()) {
+            let variables = vars(&[("a", "${b}"), ("b", "z"), ("c", "${c}")]);
+            let _ = render(&input, &variables, strict);
+        }
+    }
+}
@@ -4,4 +4,7 @@ pub use crate::process::{
     verify_tracked_process, KillProcessResult, ProcessIdentity, VerifyTrackedProcessRequest,
 };
 pub use crate::process::{FindServerRequest, ResolvePidRequest};
+pub use crate::process_snapshot::{
+    are_processes_running, invalidate as invalidate_process_snapshot,
+};
 pub use crate::launcher_utils::{replace_variables, spawn_hidden_process};
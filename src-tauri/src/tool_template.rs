@@ -0,0 +1,365 @@
+use crate::template::{self, VariableLookup};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaceholderDefinition {
+    #[serde(alias = "name")]
+    pub key: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "default_placeholder_type", rename = "type")]
+    pub placeholder_type: String,
+    #[serde(default = "default_required")]
+    pub required: bool,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+fn default_placeholder_type() -> String {
+    "text".to_string()
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl PlaceholderDefinition {
+    fn display_name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.key)
+    }
+}
+
+pub fn parse_placeholders(schema: &str) -> Result<Vec<PlaceholderDefinition>, String> {
+    if schema.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(schema).map_err(|e| format!("Invalid placeholder schema: {}", e))
+}
+
+fn validate_value(def: &PlaceholderDefinition, value: &str) -> Result<(), String> {
+    match def.placeholder_type.as_str() {
+        "select" if !def.options.is_empty() && !def.options.iter().any(|o| o == value) => {
+            Err(format!(
+                "{} must be one of [{}], got \"{}\"",
+                def.display_name(),
+                def.options.join(", "),
+                value
+            ))
+        }
+        "number" if value.trim().parse::<f64>().is_err() => Err(format!(
+            "{} must be a number, got \"{}\"",
+            def.display_name(),
+            value
+        )),
+        "url" if !value.contains("://") => Err(format!(
+            "{} must be a URL, got \"{}\"",
+            def.display_name(),
+            value
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Resolves the value for every declared placeholder, applying defaults and
+/// validating required fields and typed values. All violations are reported
+/// together so the user can fix them in one go.
+pub fn resolve_placeholder_values(
+    definitions: &[PlaceholderDefinition],
+    provided: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut resolved = HashMap::new();
+    let mut errors = Vec::new();
+
+    for def in definitions {
+        let value = provided
+            .get(&def.key)
+            .filter(|v| !v.trim().is_empty())
+            .or(def.default.as_ref())
+            .cloned();
+        match value {
+            Some(value) => match validate_value(def, &value) {
+                Ok(()) => {
+                    resolved.insert(def.key.clone(), value);
+                }
+                Err(error) => errors.push(error),
+            },
+            None if def.required => errors.push(format!("{} is required", def.display_name())),
+            None => {
+                resolved.insert(def.key.clone(), String::new());
+            }
+        }
+    }
+
+    for (key, value) in provided {
+        resolved.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+
+    if errors.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(format!("Invalid tool parameters: {}", errors.join("; ")))
+    }
+}
+
+/// Replaces `{{name}}` placeholders and `${...}` variables in `template`.
+/// Substituted values are escaped when they sit inside quotes, and variable
+/// values outside quotes are single-quoted, so a value containing spaces or
+/// quotes stays one word when the result is split. Placeholder values outside
+/// quotes may still expand to several words.
+pub fn render_template(
+    template: &str,
+    values: &HashMap<String, String>,
+    variables: &dyn VariableLookup,
+    strict: bool,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    render_into(&mut output, template, Some(values), variables, strict)?;
+    Ok(output)
+}
+
+/// `values` is `None` while rendering a placeholder value, whose `{{` is text.
+fn render_into(
+    output: &mut String,
+    input: &str,
+    values: Option<&HashMap<String, String>>,
+    variables: &dyn VariableLookup,
+    strict: bool,
+) -> Result<(), String> {
+    let mut in_double = false;
+    let mut in_single = false;
+    let mut rest = input;
+
+    while let Some(ch) = rest.chars().next() {
+        if let Some(values) = values.filter(|_| rest.starts_with("{{")) {
+            let end = rest
+                .find("}}")
+                .ok_or_else(|| "Unterminated {{ in tool template".to_string())?;
+            let name = rest[2..end].trim();
+            let value = values
+                .get(name)
+                .ok_or_else(|| format!("Unknown placeholder {{{{{}}}}} in tool template", name))?;
+            if in_double || in_single {
+                let value = template::render(value, variables, strict)?;
+                push_escaped(output, &value, in_double);
+            } else {
+                render_into(output, value, None, variables, strict)?;
+            }
+            rest = &rest[end + 2..];
+            continue;
+        }
+        if rest.starts_with("$${") {
+            output.push_str("${");
+            rest = &rest[3..];
+            continue;
+        }
+        if rest.starts_with("${") {
+            let len = template::find_closing_brace(&rest[2..])
+                .ok_or_else(|| format!("Unterminated ${{ in \"{}\"", input))?;
+            let value = template::render(&rest[..len + 3], variables, strict)?;
+            if in_double || in_single {
+                push_escaped(output, &value, in_double);
+            } else {
+                output.push('\'');
+                push_escaped(output, &value, false);
+                output.push('\'');
+            }
+            rest = &rest[len + 3..];
+            continue;
+        }
+        match ch {
+            '"' if !in_single => in_double = !in_double,
+            '\'' if !in_double => in_single = !in_single,
+            _ => {}
+        }
+        output.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    Ok(())
+}
+
+/// Appends `value` inside an open double (`in_double`) or single quote.
+fn push_escaped(output: &mut String, value: &str, in_double: bool) {
+    if in_double {
+        for c in value.chars() {
+            if matches!(c, '"' | '\\') {
+                output.push('\\');
+            }
+            output.push(c);
+        }
+    } else {
+        output.push_str(&value.replace('\'', r#"'"'"'"#));
+    }
+}
+
+/// Splits a command line into words, honouring single quotes, double quotes
+/// and backslash escapes inside double quotes.
+pub fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut has_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                has_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote in command".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                has_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                            current.push(chars.next().unwrap_or('\\'));
+                        }
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote in command".to_string()),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            }
+            c => {
+                has_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if has_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_docker_compose_template() {
+        let defs = parse_placeholders(
+            r#"[{"key":"compose_file","label":"Compose File Path","type":"text"},{"key":"command","label":"Command (up/down/restart)","type":"text"}]"#,
+        )
+        .expect("schema");
+        let resolved = resolve_placeholder_values(
+            &defs,
+            &values(&[("compose_file", "/srv/my stack/compose.yml"), ("command", "up -d")]),
+        )
+        .expect("values");
+        let rendered = render_template(
+            r#"docker-compose -f "{{compose_file}}" {{command}}"#,
+            &resolved,
+            &HashMap::new(),
+            false,
+        )
+        .expect("render");
+        let words = split_words(&rendered).expect("split");
+        assert_eq!(
+            words,
+            vec!["docker-compose", "-f", "/srv/my stack/compose.yml", "up", "-d"]
+        );
+    }
+
+    #[test]
+    fn quoted_values_are_escaped() {
+        let rendered = render_template(
+            r#"ssh {{host}} "{{command}}""#,
+            &values(&[("host", "box"), ("command", r#"echo "hi" \ there"#)]),
+            &HashMap::new(),
+            false,
+        )
+        .expect("render");
+        let words = split_words(&rendered).expect("split");
+        assert_eq!(words, vec!["ssh", "box", r#"echo "hi" \ there"#]);
+    }
+
+    #[test]
+    fn reports_missing_required_and_invalid_select() {
+        let defs = parse_placeholders(
+            r#"[{"key":"url","label":"URL","type":"text"},{"key":"method","label":"Method","type":"select","options":["GET","POST"]}]"#,
+        )
+        .expect("schema");
+        let err = resolve_placeholder_values(&defs, &values(&[("method", "PATCH")])).unwrap_err();
+        assert!(err.contains("URL is required"), "{}", err);
+        assert!(err.contains("Method must be one of [GET, POST]"), "{}", err);
+    }
+
+    #[test]
+    fn supports_frontend_schema_with_defaults() {
+        let defs = parse_placeholders(
+            r#"[{"name":"port","description":"","required":true,"type":"number","default":"3000"},{"name":"flags","description":"","required":false,"type":"text"}]"#,
+        )
+        .expect("schema");
+        let resolved = resolve_placeholder_values(&defs, &HashMap::new()).expect("values");
+        assert_eq!(resolved.get("port").map(String::as_str), Some("3000"));
+        assert_eq!(resolved.get("flags").map(String::as_str), Some(""));
+
+        let err = resolve_placeholder_values(&defs, &values(&[("port", "abc")])).unwrap_err();
+        assert!(err.contains("port must be a number"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_placeholder_is_an_error() {
+        let err = render_template("git {{command}}", &HashMap::new(), &HashMap::new(), false)
+            .unwrap_err();
+        assert!(err.contains("{{command}}"), "{}", err);
+    }
+
+    #[test]
+    fn variable_values_stay_single_words() {
+        let variables = values(&[("NOTE", "it's a test"), ("DIR", "/srv/my app")]);
+        let rendered = render_template(
+            r#"git commit -m ${NOTE} -C "${DIR}" '${NOTE}' {{extra}}"#,
+            &values(&[("extra", "--author ${NOTE}")]),
+            &variables,
+            true,
+        )
+        .expect("render");
+        let words = split_words(&rendered).expect("split");
+        assert_eq!(
+            words,
+            vec![
+                "git",
+                "commit",
+                "-m",
+                "it's a test",
+                "-C",
+                "/srv/my app",
+                "it's a test",
+                "--author",
+                "it's a test",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_words_handles_quotes() {
+        assert_eq!(
+            split_words(r#"a 'b c' "d \"e\"" f"g"h ''"#).expect("split"),
+            vec!["a", "b c", r#"d "e""#, "fgh", ""]
+        );
+        assert!(split_words("echo \"oops").is_err());
+    }
+}
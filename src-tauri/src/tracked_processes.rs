@@ -0,0 +1,450 @@
+//! Tracked processes are persisted to SQLite so the servers an action started
+//! can still be stopped after the launcher restarts or updates itself. On
+//! startup each stored process is checked against the live process table by
+//! its identity (PID, start time and name); survivors are registered again,
+//! together with their process group and stop options, and the UI is told
+//! about them with `action-reattached` events.
+
+use crate::database;
+use crate::process::{
+    self, ActionInstance, ProcessIdentity, StopOptions, VerifyTrackedProcessRequest,
+};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use tauri::Emitter;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedProcess {
+    pub identity: ProcessIdentity,
+    pub run_id: Option<i64>,
+    pub action_id: Option<i64>,
+    pub workspace_id: Option<i64>,
+    pub environment_id: Option<i64>,
+    /// Process group the process was spawned into, signalled on stop.
+    pub pgid: Option<i32>,
+    pub stop_options: Option<StopOptions>,
+}
+
+#[derive(FromRow)]
+struct TrackedProcessRow {
+    pid: i64,
+    start_time_secs: i64,
+    name: String,
+    run_id: Option<i64>,
+    action_id: Option<i64>,
+    workspace_id: Option<i64>,
+    environment_id: Option<i64>,
+    pgid: Option<i64>,
+    stop_options: Option<String>,
+}
+
+impl From<TrackedProcessRow> for TrackedProcess {
+    fn from(row: TrackedProcessRow) -> Self {
+        let stop_options = row.stop_options.and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| e.to_string())
+                .and_then(|config| StopOptions::from_config(&config))
+                .inspect_err(|e| eprintln!("Ignoring stop options of process {}: {e}", row.pid))
+                .ok()
+        });
+        Self {
+            identity: ProcessIdentity {
+                pid: row.pid as u32,
+                start_time_secs: row.start_time_secs as u64,
+                name: row.name,
+            },
+            run_id: row.run_id,
+            action_id: row.action_id,
+            workspace_id: row.workspace_id,
+            environment_id: row.environment_id,
+            pgid: row.pgid.map(|pgid| pgid as i32),
+            stop_options,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionReattachedEvent {
+    pub action_id: i64,
+    pub action_name: String,
+    pub workspace_id: i64,
+    pub run_id: Option<i64>,
+    pub environment_id: Option<i64>,
+    pub process_id: u32,
+    pub process_start_time_secs: u64,
+    pub process_name: String,
+}
+
+enum Change {
+    Track(TrackedProcess),
+    Group(u32, i32),
+    Stop(u32, Option<StopOptions>),
+    Forget(u32),
+}
+
+/// Changes are written by a single background task so tracking a process
+/// never waits on the database. Before [`start_persisting`] runs (and in
+/// tests) changes are only kept in memory.
+static CHANGES: OnceLock<mpsc::UnboundedSender<Change>> = OnceLock::new();
+
+/// Processes restored at startup that the UI has not been told about yet.
+static REATTACHED: LazyLock<Mutex<Vec<TrackedProcess>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+pub async fn save(pool: &SqlitePool, process: &TrackedProcess) -> Result<(), String> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO tracked_processes \
+         (pid, start_time_secs, name, run_id, action_id, workspace_id, environment_id, \
+          pgid, stop_options) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(process.identity.pid as i64)
+    .bind(process.identity.start_time_secs as i64)
+    .bind(&process.identity.name)
+    .bind(process.run_id)
+    .bind(process.action_id)
+    .bind(process.workspace_id)
+    .bind(process.environment_id)
+    .bind(process.pgid)
+    .bind(process.stop_options.as_ref().map(stop_options_json))
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to save tracked process {}: {}", process.identity.pid, e))?;
+    Ok(())
+}
+
+fn stop_options_json(options: &StopOptions) -> String {
+    options.to_config().to_string()
+}
+
+pub async fn save_process_group(pool: &SqlitePool, pid: u32, pgid: i32) -> Result<(), String> {
+    sqlx::query("UPDATE tracked_processes SET pgid = ? WHERE pid = ?")
+        .bind(pgid)
+        .bind(pid as i64)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to save process group of {}: {}", pid, e))?;
+    Ok(())
+}
+
+pub async fn save_stop_options(
+    pool: &SqlitePool,
+    pid: u32,
+    options: Option<&StopOptions>,
+) -> Result<(), String> {
+    sqlx::query("UPDATE tracked_processes SET stop_options = ? WHERE pid = ?")
+        .bind(options.map(stop_options_json))
+        .bind(pid as i64)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to save stop options of {}: {}", pid, e))?;
+    Ok(())
+}
+
+pub async fn remove(pool: &SqlitePool, pid: u32) -> Result<(), String> {
+    sqlx::query("DELETE FROM tracked_processes WHERE pid = ?")
+        .bind(pid as i64)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to remove tracked process {}: {}", pid, e))?;
+    Ok(())
+}
+
+pub async fn load(pool: &SqlitePool) -> Result<Vec<TrackedProcess>, String> {
+    let rows = sqlx::query_as::<_, TrackedProcessRow>(
+        "SELECT pid, start_time_secs, name, run_id, action_id, workspace_id, environment_id, \
+         pgid, stop_options FROM tracked_processes ORDER BY created_at, pid",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load tracked processes: {}", e))?;
+    Ok(rows.into_iter().map(TrackedProcess::from).collect())
+}
+
+fn send(change: Change) {
+    if let Some(sender) = CHANGES.get() {
+        let _ = sender.send(change);
+    }
+}
+
+/// Tracks `pid` for stopping and persists it with the run it belongs to.
+/// Returns the record unless the process is already gone.
+pub fn track(
+    pid: u32,
+    run_id: Option<i64>,
+    action_id: Option<i64>,
+    workspace_id: Option<i64>,
+) -> Option<TrackedProcess> {
+    if pid == 0 {
+        return None;
+    }
+    let identity = process::register_tracked_pid(pid)?;
+    if let (Some(action_id), Some(run_id)) = (action_id, run_id) {
+        if action_id > 0 {
+            process::insert_action_instance(ActionInstance {
+                action_id,
+                run_id,
+                identity: identity.clone(),
+            });
+        }
+    }
+    let environment_id = run_id
+        .and_then(crate::run_context::run_context)
+        .and_then(|context| context.environment_id);
+    let tracked = TrackedProcess {
+        identity,
+        run_id,
+        action_id,
+        workspace_id,
+        environment_id,
+        pgid: None,
+        stop_options: None,
+    };
+    send(Change::Track(tracked.clone()));
+    Some(tracked)
+}
+
+/// Persists the process group of a tracked process. Untracked PIDs are
+/// ignored.
+pub fn record_process_group(pid: u32, pgid: i32) {
+    send(Change::Group(pid, pgid));
+}
+
+/// Persists how a tracked process is stopped; `None` for the defaults.
+pub fn record_stop_options(pid: u32, options: Option<&StopOptions>) {
+    send(Change::Stop(pid, options.cloned()));
+}
+
+/// Drops the persisted record of a stopped process.
+pub fn forget(pid: u32) {
+    send(Change::Forget(pid));
+}
+
+async fn apply(pool: &SqlitePool, change: Change) -> Result<(), String> {
+    match change {
+        Change::Track(process) => save(pool, &process).await,
+        Change::Group(pid, pgid) => save_process_group(pool, pid, pgid).await,
+        Change::Stop(pid, options) => save_stop_options(pool, pid, options.as_ref()).await,
+        Change::Forget(pid) => remove(pool, pid).await,
+    }
+}
+
+/// Starts writing tracking changes to the database at `db_path`.
+pub fn start_persisting(db_path: PathBuf) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    if CHANGES.set(sender).is_err() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        while let Some(change) = receiver.recv().await {
+            let pool = match database::connect(&db_path).await {
+                Ok(pool) => pool,
+                Err(e) => {
+                    eprintln!("Failed to open database for tracked processes: {e}");
+                    continue;
+                }
+            };
+            let mut next = Some(change);
+            while let Some(change) = next {
+                if let Err(e) = apply(&pool, change).await {
+                    eprintln!("{e}");
+                }
+                next = receiver.try_recv().ok();
+            }
+            pool.close().await;
+        }
+    });
+}
+
+/// Re-registers the stored processes that are still running with the same
+/// identity and deletes the rest. Returns the survivors.
+pub async fn restore(pool: &SqlitePool) -> Result<Vec<TrackedProcess>, String> {
+    let mut survivors = Vec::new();
+    for tracked in load(pool).await? {
+        let identity = &tracked.identity;
+        let alive = process::verify_tracked_process(VerifyTrackedProcessRequest {
+            pid: identity.pid,
+            expected_start_time_secs: Some(identity.start_time_secs),
+            expected_name: None,
+        })
+        .await
+        .unwrap_or(false);
+        if !alive {
+            remove(pool, identity.pid).await?;
+            continue;
+        }
+
+        process::register_tracked_identity(identity.clone());
+        if let (Some(action_id), Some(run_id)) = (tracked.action_id, tracked.run_id) {
+            process::insert_action_instance(ActionInstance {
+                action_id,
+                run_id,
+                identity: identity.clone(),
+            });
+        }
+        if let (Some(pgid), Some(run_id)) = (tracked.pgid, tracked.run_id) {
+            if still_in_group(identity.pid, pgid) {
+                process::register_process_group(identity.pid, run_id, pgid);
+            }
+        }
+        if let Some(options) = &tracked.stop_options {
+            process::register_stop_options(identity.pid, options.clone());
+        }
+        survivors.push(tracked);
+    }
+    Ok(survivors)
+}
+
+/// A stored group is only signalled again while the process is still in it,
+/// since the group ID may have been reused.
+#[cfg(unix)]
+fn still_in_group(pid: u32, pgid: i32) -> bool {
+    // SAFETY: getpgid only reads the process table.
+    unsafe { nix::libc::getpgid(pid as nix::libc::pid_t) == pgid }
+}
+
+#[cfg(not(unix))]
+fn still_in_group(_pid: u32, _pgid: i32) -> bool {
+    false
+}
+
+/// Startup step: restores the processes tracked before the last exit,
+/// watches them for exit and keeps them until the UI asks for them via
+/// [`emit_reattached_processes`].
+pub async fn restore_tracked_processes(app: &tauri::AppHandle, db_path: &std::path::Path) {
+    let pool = match database::connect(db_path).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to open database for tracked processes: {e}");
+            return;
+        }
+    };
+    match restore(&pool).await {
+        Ok(survivors) => {
+            if !survivors.is_empty() {
+                println!("Re-attached {} tracked processes.", survivors.len());
+            }
+            for tracked in &survivors {
+                crate::exit_watcher::watch(app.clone(), tracked.clone());
+            }
+            if let Ok(mut reattached) = REATTACHED.lock() {
+                *reattached = survivors;
+            }
+        }
+        Err(e) => eprintln!("Failed to restore tracked processes: {e}"),
+    }
+    pool.close().await;
+}
+
+/// Emits an `action-reattached` event for every process restored at startup
+/// whose action still exists. Called by the UI once it listens for events.
+#[tauri::command]
+pub async fn emit_reattached_processes(app: tauri::AppHandle) -> Result<usize, String> {
+    let reattached = REATTACHED
+        .lock()
+        .map(|mut reattached| std::mem::take(&mut *reattached))
+        .unwrap_or_default();
+    if reattached.is_empty() {
+        return Ok(0);
+    }
+
+    let pool = database::connect_app_db(&app).await?;
+    let result = async {
+        let mut events = Vec::new();
+        for tracked in reattached {
+            let Some(action_id) = tracked.action_id else {
+                continue;
+            };
+            let Some(action) = database::get_action(&pool, action_id).await? else {
+                continue;
+            };
+            events.push(ActionReattachedEvent {
+                action_id,
+                action_name: action.name,
+                workspace_id: tracked.workspace_id.unwrap_or(action.workspace_id),
+                run_id: tracked.run_id,
+                environment_id: tracked.environment_id,
+                process_id: tracked.identity.pid,
+                process_start_time_secs: tracked.identity.start_time_secs,
+                process_name: tracked.identity.name,
+            });
+        }
+        Ok::<_, String>(events)
+    }
+    .await;
+    pool.close().await;
+
+    let events = result?;
+    for event in &events {
+        app.emit("action-reattached", event)
+            .map_err(|e| format!("Failed to emit action-reattached event: {}", e))?;
+    }
+    Ok(events.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn restores_live_processes_and_drops_stale_ones() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        database::run_migrations(&db_path).await.expect("migrate");
+        let pool = database::connect(&db_path).await.expect("connect");
+
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id().expect("pid");
+        let identity = process::current_identity(pid).expect("identity");
+        let live = TrackedProcess {
+            identity: identity.clone(),
+            run_id: Some(7),
+            action_id: None,
+            workspace_id: None,
+            environment_id: None,
+            pgid: Some(pid as i32),
+            stop_options: Some(StopOptions {
+                signal: process::StopSignal::Int,
+                grace_period: std::time::Duration::from_secs(3),
+                ..StopOptions::default()
+            }),
+        };
+        let exited = TrackedProcess {
+            identity: ProcessIdentity {
+                pid: u32::MAX - 1,
+                start_time_secs: identity.start_time_secs,
+                name: identity.name.clone(),
+            },
+            ..live.clone()
+        };
+        save(&pool, &live).await.expect("save live");
+        save(&pool, &exited).await.expect("save stale");
+
+        assert!(!process::is_pid_registered(pid));
+        let survivors = restore(&pool).await.expect("restore");
+        assert_eq!(survivors, vec![live.clone()]);
+        assert!(process::is_pid_registered(pid));
+        assert_eq!(
+            process::process_group_for_pid(pid).map(|group| group.pgid),
+            live.pgid
+        );
+        assert_eq!(load(&pool).await.expect("load").len(), 1);
+
+        process::unregister_tracked_pid(pid);
+        process::unregister_process_group(pid);
+        let _ = child.kill().await;
+        let _ = child.wait().await;
+        crate::process_snapshot::invalidate();
+        assert!(restore(&pool).await.expect("restore").is_empty());
+        assert!(load(&pool).await.expect("load").is_empty());
+        pool.close().await;
+    }
+}
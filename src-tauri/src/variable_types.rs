@@ -0,0 +1,225 @@
+//! Typed variables. A variable may declare a type (`path`, `directory`,
+//! `file`, `url`, `integer`, `port`, `enum` or `pattern`); its rendered value
+//! is checked when it is saved and again before an action using it launches,
+//! so a typo in a path fails with a reason instead of opening an empty editor.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableType {
+    #[default]
+    String,
+    /// A file or directory that must exist.
+    Path,
+    Directory,
+    File,
+    Url,
+    Integer,
+    Port,
+    /// One of `type_options.values`.
+    Enum,
+    /// Matches the regular expression `type_options.pattern`.
+    Pattern,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TypeOptions {
+    #[serde(default)]
+    values: Vec<String>,
+    #[serde(default)]
+    pattern: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableSchema {
+    pub value_type: VariableType,
+    values: Vec<String>,
+    pattern: Option<regex::Regex>,
+}
+
+impl PartialEq for VariableSchema {
+    fn eq(&self, other: &Self) -> bool {
+        self.value_type == other.value_type
+            && self.values == other.values
+            && self.pattern.as_ref().map(regex::Regex::as_str)
+                == other.pattern.as_ref().map(regex::Regex::as_str)
+    }
+}
+
+impl Eq for VariableSchema {}
+
+impl VariableSchema {
+    /// Parses the `value_type` / `type_options` columns. Plain strings have no
+    /// schema.
+    pub fn parse(value_type: &str, type_options: Option<&str>) -> Result<Option<Self>, String> {
+        let value_type: VariableType =
+            serde_json::from_value(serde_json::Value::String(value_type.to_string()))
+                .map_err(|_| format!("Unknown variable type: {}", value_type))?;
+        if value_type == VariableType::String {
+            return Ok(None);
+        }
+        let options: TypeOptions = match type_options.map(str::trim) {
+            Some(options) if !options.is_empty() => serde_json::from_str(options)
+                .map_err(|e| format!("Invalid type options: {}", e))?,
+            _ => TypeOptions::default(),
+        };
+
+        let pattern = match value_type {
+            VariableType::Pattern => {
+                let pattern = options
+                    .pattern
+                    .filter(|pattern| !pattern.is_empty())
+                    .ok_or("Pattern variables need a pattern")?;
+                let regex = regex::Regex::new(&pattern)
+                    .map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+                Some(regex)
+            }
+            _ => None,
+        };
+        if value_type == VariableType::Enum && options.values.is_empty() {
+            return Err("Enum variables need at least one allowed value".to_string());
+        }
+        Ok(Some(Self {
+            value_type,
+            values: options.values,
+            pattern,
+        }))
+    }
+
+    /// Checks a fully rendered value, returning the reason it is invalid.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self.value_type {
+            VariableType::String => Ok(()),
+            VariableType::Path => match Path::new(value).exists() {
+                true => Ok(()),
+                false => Err(format!("path {} does not exist", value)),
+            },
+            VariableType::Directory => match Path::new(value).is_dir() {
+                true => Ok(()),
+                false => Err(format!("{} is not an existing directory", value)),
+            },
+            VariableType::File => match Path::new(value).is_file() {
+                true => Ok(()),
+                false => Err(format!("{} is not an existing file", value)),
+            },
+            VariableType::Url => {
+                let url = url::Url::parse(value)
+                    .map_err(|e| format!("{} is not a valid URL: {}", value, e))?;
+                match url.has_host() || url.scheme() == "file" {
+                    true => Ok(()),
+                    false => Err(format!("{} is not a valid URL: missing host", value)),
+                }
+            }
+            VariableType::Integer => value
+                .trim()
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| format!("{} is not an integer", value)),
+            VariableType::Port => match value.trim().parse::<u16>() {
+                Ok(port) if port > 0 => Ok(()),
+                _ => Err(format!("{} is not a port between 1 and 65535", value)),
+            },
+            VariableType::Enum => match self.values.iter().any(|allowed| allowed == value) {
+                true => Ok(()),
+                false => Err(format!(
+                    "{} is not one of: {}",
+                    value,
+                    self.values.join(", ")
+                )),
+            },
+            VariableType::Pattern => {
+                let pattern = self.pattern.as_ref().ok_or("Missing pattern")?;
+                match pattern.is_match(value) {
+                    true => Ok(()),
+                    false => Err(format!("{} does not match {}", value, pattern.as_str())),
+                }
+            }
+        }
+    }
+}
+
+/// Save-time check used by the variable editors: the type options must be
+/// valid and, unless the value references other variables or is computed by
+/// a command, the value itself must pass validation.
+#[tauri::command]
+pub fn check_variable_value(
+    value_type: String,
+    type_options: Option<String>,
+    value: String,
+    value_source: Option<String>,
+) -> Result<(), String> {
+    let Some(schema) = VariableSchema::parse(&value_type, type_options.as_deref())? else {
+        return Ok(());
+    };
+    let deferred = value.contains("${")
+        || value_source.as_deref() == Some("command")
+        || crate::secure_store::is_encrypted(&value);
+    if deferred {
+        return Ok(());
+    }
+    schema.validate(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(value_type: &str, options: Option<&str>) -> VariableSchema {
+        VariableSchema::parse(value_type, options)
+            .expect("parse")
+            .expect("schema")
+    }
+
+    #[test]
+    fn validates_each_type() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let file = dir.path().join("settings.json");
+        std::fs::write(&file, "{}").expect("write");
+        let dir_str = dir.path().to_str().unwrap();
+        let file_str = file.to_str().unwrap();
+
+        assert!(VariableSchema::parse("string", None).unwrap().is_none());
+        assert!(schema("path", None).validate(file_str).is_ok());
+        assert!(schema("path", None).validate(&format!("{}/missing", dir_str)).is_err());
+        assert!(schema("directory", None).validate(dir_str).is_ok());
+        assert!(schema("directory", None).validate(file_str).is_err());
+        assert!(schema("file", None).validate(file_str).is_ok());
+        assert!(schema("url", None).validate("https://staging.example.com/api").is_ok());
+        assert!(schema("url", None).validate("staging.example.com").is_err());
+        assert!(schema("integer", None).validate("-42").is_ok());
+        assert!(schema("integer", None).validate("4.2").is_err());
+        assert!(schema("port", None).validate("8080").is_ok());
+        assert_eq!(
+            schema("port", None).validate("70000").unwrap_err(),
+            "70000 is not a port between 1 and 65535"
+        );
+
+        let env = schema("enum", Some(r#"{"values": ["dev", "prod"]}"#));
+        assert!(env.validate("dev").is_ok());
+        assert_eq!(env.validate("qa").unwrap_err(), "qa is not one of: dev, prod");
+        let version = schema("pattern", Some(r#"{"pattern": "^v[0-9]+$"}"#));
+        assert!(version.validate("v12").is_ok());
+        assert!(version.validate("12").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_schemas() {
+        assert!(VariableSchema::parse("colour", None).is_err());
+        assert!(VariableSchema::parse("enum", None).is_err());
+        assert!(VariableSchema::parse("pattern", Some(r#"{"pattern": "("}"#)).is_err());
+    }
+
+    #[test]
+    fn save_time_check_defers_templates_and_commands() {
+        let check = |value: &str, source: Option<&str>| {
+            check_variable_value("port".into(), None, value.into(), source.map(String::from))
+        };
+        assert!(check("8080", None).is_ok());
+        assert!(check("80a", None).is_err());
+        assert!(check("${BASE_PORT}", None).is_ok());
+        assert!(check("echo 8080", Some("command")).is_ok());
+        assert!(check_variable_value("enum".into(), None, "x".into(), None).is_err());
+    }
+}
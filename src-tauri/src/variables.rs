@@ -0,0 +1,615 @@
+//! Resolves the variables available to an action from its layers, highest
+//! priority first: action overrides (`config.variables`), the variables of
+//! the selected environment, workspace variables, values sent with the launch
+//! request (a parent workspace's variables), then global variables. Names missing from
+//! every layer fall back to the built-ins and then the launcher's process
+//! environment at render time, and `${env:NAME}` always reads it directly.
+
+use crate::builtins::{BuiltinContext, LaunchVariables};
+use crate::command_variables::CommandVariable;
+use crate::database;
+use crate::template::{self, VariableLookup};
+use crate::variable_types::{VariableSchema, VariableType};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableSource {
+    Action,
+    Environment,
+    Workspace,
+    Request,
+    Global,
+    Env,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerValue {
+    pub value: String,
+    pub secure: bool,
+    /// Set when `value` is a command to run at launch time.
+    pub command: Option<CommandVariable>,
+    pub schema: Option<VariableSchema>,
+}
+
+pub type VariableLayer = (VariableSource, HashMap<String, LayerValue>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariableProvenance {
+    pub source: VariableSource,
+    pub secure: bool,
+    pub command: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedVariables {
+    pub values: HashMap<String, String>,
+    pub provenance: HashMap<String, VariableProvenance>,
+    /// Command-valued variables; `values` holds their command text until they
+    /// are evaluated.
+    pub commands: HashMap<String, CommandVariable>,
+    /// Declared types. Overrides without a type keep the type declared by a
+    /// lower layer.
+    pub schemas: HashMap<String, VariableSchema>,
+}
+
+/// A variable whose rendered value does not match its declared type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariableViolation {
+    pub key: String,
+    pub source: VariableSource,
+    pub value_type: VariableType,
+    pub reason: String,
+}
+
+/// Debug view returned to the frontend; secure values are masked.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariableResolution {
+    pub values: HashMap<String, String>,
+    pub provenance: HashMap<String, VariableProvenance>,
+}
+
+fn plain_layer(
+    values: &HashMap<String, String>,
+    secure: &HashSet<String>,
+) -> HashMap<String, LayerValue> {
+    values
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.clone(),
+                LayerValue {
+                    value: value.clone(),
+                    secure: secure.contains(key),
+                    command: None,
+                    schema: None,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Merges `layers`, ordered highest priority first.
+pub fn merge_layers(layers: &[VariableLayer]) -> ResolvedVariables {
+    let mut resolved = ResolvedVariables::default();
+    for (source, layer) in layers.iter().rev() {
+        for (key, entry) in layer {
+            resolved.values.insert(key.clone(), entry.value.clone());
+            resolved.provenance.insert(
+                key.clone(),
+                VariableProvenance {
+                    source: *source,
+                    secure: entry.secure,
+                    command: entry.command.is_some(),
+                },
+            );
+            match &entry.command {
+                Some(command) => resolved.commands.insert(key.clone(), command.clone()),
+                None => resolved.commands.remove(key),
+            };
+            if let Some(schema) = &entry.schema {
+                resolved.schemas.insert(key.clone(), schema.clone());
+            }
+        }
+    }
+    resolved
+}
+
+/// Decrypts secure values that are encrypted at rest, including the command
+/// text of command variables, limited to `names` when given.
+pub fn decrypt_values(
+    resolved: &mut ResolvedVariables,
+    key_dir: &Path,
+    names: Option<&HashSet<String>>,
+) -> Result<(), String> {
+    let wanted = |key: &String| names.is_none_or(|names| names.contains(key));
+    for (key, value) in resolved.values.iter_mut() {
+        if wanted(key) && crate::secure_store::is_encrypted(value) {
+            *value = crate::secure_store::decrypt(key_dir, value)
+                .map_err(|e| format!("Failed to decrypt variable {}: {}", key, e))?;
+        }
+    }
+    for (key, command) in resolved.commands.iter_mut() {
+        if wanted(key) && crate::secure_store::is_encrypted(&command.command) {
+            command.command = crate::secure_store::decrypt(key_dir, &command.command)
+                .map_err(|e| format!("Failed to decrypt variable {}: {}", key, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Decrypts the secure values of typed variables and the secure values they
+/// reference, which validation has to render. Other secure values stay
+/// encrypted.
+pub fn decrypt_typed_values(
+    resolved: &mut ResolvedVariables,
+    key_dir: &Path,
+    builtins: &BuiltinContext,
+) -> Result<(), String> {
+    let typed: HashSet<String> = resolved.schemas.keys().cloned().collect();
+    decrypt_values(resolved, key_dir, Some(&typed))?;
+    // A decrypted value can reference further secure values, so repeat
+    // until nothing new is found.
+    let mut decrypted = typed;
+    loop {
+        let templates: Vec<&str> = resolved
+            .schemas
+            .keys()
+            .filter_map(|key| resolved.values.get(key).map(String::as_str))
+            .collect();
+        let referenced = crate::command_variables::referenced_names(
+            &Value::Null,
+            &templates,
+            &LaunchVariables {
+                variables: &resolved.values,
+                builtins,
+            },
+            &resolved.commands,
+        );
+        if referenced.is_subset(&decrypted) {
+            return Ok(());
+        }
+        decrypt_values(resolved, key_dir, Some(&referenced))?;
+        decrypted.extend(referenced);
+    }
+}
+
+/// Reads action-level overrides from `config.variables`.
+pub fn action_overrides(config: &Value) -> Result<HashMap<String, String>, String> {
+    let Some(value) = config.get("variables") else {
+        return Ok(HashMap::new());
+    };
+    if value.is_null() {
+        return Ok(HashMap::new());
+    }
+    let entries = value
+        .as_object()
+        .ok_or("variables must be an object of name/value pairs")?;
+    entries
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return Err(format!("Variable {} must be a string", key)),
+            };
+            Ok((key.clone(), value))
+        })
+        .collect()
+}
+
+/// Columns shared by the `variables`, `environment_variables` and
+/// `global_variables` tables.
+struct StoredVariable {
+    key: String,
+    value: String,
+    secure: bool,
+    enabled: bool,
+    value_source: String,
+    cache_ttl_secs: Option<i64>,
+    value_type: String,
+    type_options: Option<String>,
+}
+
+macro_rules! stored_variable_from {
+    ($($row:ty),*) => {$(
+        impl From<$row> for StoredVariable {
+            fn from(variable: $row) -> Self {
+                StoredVariable {
+                    key: variable.key,
+                    value: variable.value,
+                    secure: variable.is_secure,
+                    enabled: variable.enabled,
+                    value_source: variable.value_source,
+                    cache_ttl_secs: variable.cache_ttl_secs,
+                    value_type: variable.value_type,
+                    type_options: variable.type_options,
+                }
+            }
+        }
+    )*};
+}
+
+stored_variable_from!(
+    database::Variable,
+    database::EnvironmentVariable,
+    database::GlobalVariable
+);
+
+/// The enabled rows of one stored layer.
+fn stored_layer(
+    variables: impl IntoIterator<Item = impl Into<StoredVariable>>,
+) -> Result<HashMap<String, LayerValue>, String> {
+    variables
+        .into_iter()
+        .map(Into::into)
+        .filter(|variable: &StoredVariable| variable.enabled)
+        .map(|variable| {
+            let schema =
+                VariableSchema::parse(&variable.value_type, variable.type_options.as_deref())
+                    .map_err(|e| format!("Variable {}: {}", variable.key, e))?;
+            let command = CommandVariable::from_source(
+                &variable.value_source,
+                &variable.value,
+                variable.cache_ttl_secs,
+            );
+            let value = LayerValue {
+                value: variable.value,
+                secure: variable.secure,
+                command,
+                schema,
+            };
+            Ok((variable.key, value))
+        })
+        .collect()
+}
+
+pub async fn load_layers(
+    pool: &SqlitePool,
+    workspace_id: i64,
+    environment_id: Option<i64>,
+    action: &HashMap<String, String>,
+    request: &HashMap<String, String>,
+    request_secure: &HashSet<String>,
+) -> Result<Vec<VariableLayer>, String> {
+    let environment = match environment_id {
+        Some(id) => {
+            let environment = database::get_environment(pool, id)
+                .await?
+                .ok_or_else(|| format!("Environment {} not found", id))?;
+            if environment.workspace_id != workspace_id {
+                return Err(format!(
+                    "Environment {} does not belong to workspace {}",
+                    environment.name, workspace_id
+                ));
+            }
+            stored_layer(database::get_environment_variables(pool, id).await?)?
+        }
+        None => HashMap::new(),
+    };
+    let workspace = stored_layer(database::get_workspace_variables(pool, workspace_id).await?)?;
+    let global = stored_layer(database::get_global_variables(pool).await?)?;
+
+    Ok(vec![
+        (VariableSource::Action, plain_layer(action, &HashSet::new())),
+        (VariableSource::Environment, environment),
+        (VariableSource::Workspace, workspace),
+        (VariableSource::Request, plain_layer(request, request_secure)),
+        (VariableSource::Global, global),
+    ])
+}
+
+/// Checks the rendered value of every typed variable, or only of `names`
+/// when given. Command variables are checked only once they have been
+/// evaluated into `variables`.
+pub fn validate(
+    resolved: &ResolvedVariables,
+    variables: &dyn VariableLookup,
+    names: Option<&HashSet<String>>,
+) -> Vec<VariableViolation> {
+    let mut keys: Vec<&String> = resolved
+        .schemas
+        .keys()
+        .filter(|key| names.is_none_or(|names| names.contains(*key)))
+        .collect();
+    keys.sort();
+
+    let mut violations = Vec::new();
+    for key in keys {
+        let schema = &resolved.schemas[key];
+        let Some(raw) = variables.lookup(key) else {
+            continue;
+        };
+        if resolved.commands.get(key).is_some_and(|command| command.command == raw) {
+            continue;
+        }
+        let Some(provenance) = resolved.provenance.get(key) else {
+            continue;
+        };
+        let reason = match template::render(&raw, variables, true) {
+            Ok(value) => match schema.validate(&value) {
+                Ok(()) => continue,
+                Err(reason) if provenance.secure => crate::redact::redact(
+                    &reason,
+                    &crate::redact::secret_patterns(&[value]),
+                ),
+                Err(reason) => reason,
+            },
+            Err(error) => error,
+        };
+        violations.push(VariableViolation {
+            key: key.clone(),
+            source: provenance.source,
+            value_type: schema.value_type,
+            reason,
+        });
+    }
+    violations
+}
+
+/// Formats violations as a launch error.
+pub fn violations_error(violations: &[VariableViolation]) -> String {
+    let details: Vec<String> = violations
+        .iter()
+        .map(|violation| format!("{}: {}", violation.key, violation.reason))
+        .collect();
+    format!("Invalid variables: {}", details.join("; "))
+}
+
+/// Reports every typed variable of a workspace (and optionally one of its
+/// environments) whose value does not match its type.
+#[tauri::command]
+pub async fn validate_variables(
+    app: tauri::AppHandle,
+    workspace_id: i64,
+    environment_id: Option<i64>,
+) -> Result<Vec<VariableViolation>, String> {
+    let pool = database::app_pool(&app).await?;
+    let layers = load_layers(
+        &pool,
+        workspace_id,
+        environment_id,
+        &HashMap::new(),
+        &HashMap::new(),
+        &HashSet::new(),
+    )
+    .await?;
+    let workspace = database::get_workspace(&pool, workspace_id).await?;
+
+    let mut resolved = merge_layers(&layers);
+    let mut builtins = BuiltinContext::new(workspace_id);
+    builtins.workspace_name = workspace.map(|workspace| workspace.name);
+    decrypt_typed_values(&mut resolved, &crate::secure_store::key_dir(&app)?, &builtins)?;
+    let lookup = LaunchVariables {
+        variables: &resolved.values,
+        builtins: &builtins,
+    };
+    Ok(validate(&resolved, &lookup, None))
+}
+
+/// Shows where each variable for a workspace (and optionally one of its
+/// environments and actions) comes from. `names` lists extra names to look
+/// up in the process environment when no layer defines them.
+#[tauri::command]
+pub async fn resolve_variables(
+    app: tauri::AppHandle,
+    workspace_id: i64,
+    environment_id: Option<i64>,
+    action_id: Option<i64>,
+    names: Option<Vec<String>>,
+) -> Result<VariableResolution, String> {
+    let pool = database::app_pool(&app).await?;
+    let action = match action_id {
+        Some(id) => {
+            let action = database::get_action(&pool, id)
+                .await?
+                .ok_or_else(|| format!("Action {} not found", id))?;
+            let config: Value = serde_json::from_str(&action.config)
+                .map_err(|e| format!("Invalid config for action {}: {}", id, e))?;
+            action_overrides(&config)?
+        }
+        None => HashMap::new(),
+    };
+    let layers = load_layers(
+        &pool,
+        workspace_id,
+        environment_id,
+        &action,
+        &HashMap::new(),
+        &HashSet::new(),
+    )
+    .await?;
+
+    let mut resolved = merge_layers(&layers);
+    for name in names.unwrap_or_default() {
+        if resolved.values.contains_key(&name) {
+            continue;
+        }
+        if let Ok(value) = std::env::var(&name) {
+            resolved.values.insert(name.clone(), value);
+            resolved.provenance.insert(
+                name,
+                VariableProvenance {
+                    source: VariableSource::Env,
+                    secure: false,
+                    command: false,
+                },
+            );
+        }
+    }
+
+    for (key, provenance) in &resolved.provenance {
+        if provenance.secure {
+            resolved.values.insert(key.clone(), crate::redact::MASK.to_string());
+        }
+    }
+    Ok(VariableResolution {
+        values: resolved.values,
+        provenance: resolved.provenance,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn resolves_layers_in_priority_order() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test.db");
+        database::run_migrations(&db_path).await.expect("migrate");
+
+        let pool = database::connect(&db_path).await.expect("connect");
+        sqlx::query("INSERT INTO workspaces (id, name) VALUES (3, 'api')")
+            .execute(&pool)
+            .await
+            .expect("insert workspace");
+        sqlx::query(
+            "INSERT INTO variables (workspace_id, key, value, is_secure, enabled) VALUES \
+             (3, 'PORT', '8080', 0, 1), (3, 'TOKEN', 'ws-secret', 1, 1), (3, 'OFF', 'x', 0, 0)",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert variables");
+        sqlx::query(
+            "INSERT INTO global_variables (key, value, enabled) VALUES \
+             ('PORT', '80', 1), ('REGION', 'eu', 1), ('OFF', 'global', 1)",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert globals");
+
+        let action = action_overrides(&serde_json::json!({ "variables": { "PORT": 9000 } }))
+            .expect("overrides");
+        let request = map(&[
+            ("REGION", "us"),
+            ("FROM_PARENT", "yes"),
+            ("PARENT_KEY", "k"),
+        ]);
+        let request_secure = HashSet::from(["PARENT_KEY".to_string()]);
+        let layers = load_layers(&pool, 3, None, &action, &request, &request_secure)
+            .await
+            .expect("layers");
+
+        let resolved = merge_layers(&layers);
+        assert_eq!(resolved.values["PORT"], "9000");
+        assert_eq!(resolved.provenance["PORT"].source, VariableSource::Action);
+        assert_eq!(resolved.values["TOKEN"], "ws-secret");
+        assert!(resolved.provenance["TOKEN"].secure);
+        assert_eq!(resolved.values["REGION"], "us");
+        assert_eq!(resolved.provenance["REGION"].source, VariableSource::Request);
+        assert_eq!(resolved.values["FROM_PARENT"], "yes");
+        assert!(!resolved.provenance["FROM_PARENT"].secure);
+        assert!(resolved.provenance["PARENT_KEY"].secure);
+        assert_eq!(resolved.values["OFF"], "global");
+        assert_eq!(resolved.provenance["OFF"].source, VariableSource::Global);
+
+        sqlx::query(
+            "INSERT INTO workspaces (id, name) VALUES (4, 'other'); \
+             INSERT INTO environments (id, workspace_id, name) VALUES (1, 3, 'staging'), (2, 4, 'dev'); \
+             INSERT INTO environment_variables (environment_id, key, value) VALUES \
+             (1, 'PORT', '8443'), (1, 'API_URL', 'https://staging.example.com')",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert environments");
+        let layers = load_layers(&pool, 3, Some(1), &HashMap::new(), &request, &request_secure)
+            .await
+            .expect("layers");
+        let resolved = merge_layers(&layers);
+        assert_eq!(resolved.values["PORT"], "8443");
+        assert_eq!(resolved.provenance["PORT"].source, VariableSource::Environment);
+        assert_eq!(resolved.values["API_URL"], "https://staging.example.com");
+        assert_eq!(resolved.values["TOKEN"], "ws-secret");
+
+        let err = load_layers(&pool, 3, Some(2), &HashMap::new(), &request, &request_secure)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Environment dev does not belong to workspace 3");
+        pool.close().await;
+    }
+
+    #[test]
+    fn validates_typed_variables() {
+        let schema = |value_type: &str| VariableSchema::parse(value_type, None).unwrap();
+        let typed = |value: &str, value_type: &str, secure: bool| LayerValue {
+            value: value.to_string(),
+            secure,
+            command: None,
+            schema: schema(value_type),
+        };
+        let workspace = HashMap::from([
+            ("BASE".to_string(), typed("80", "string", false)),
+            ("PORT".to_string(), typed("${BASE}80", "port", false)),
+            ("API_URL".to_string(), typed("not a url", "url", false)),
+            ("TOKEN_PORT".to_string(), typed("s3cret-port", "port", true)),
+            ("MISSING".to_string(), typed("${NOPE}", "integer", false)),
+        ]);
+        let action = HashMap::from([("API_URL".to_string(), typed("https://x.dev", "string", false))]);
+        let resolved = merge_layers(&[
+            (VariableSource::Action, action),
+            (VariableSource::Workspace, workspace),
+        ]);
+
+        let violations = validate(&resolved, &resolved.values, None);
+        let keys: Vec<&str> = violations.iter().map(|v| v.key.as_str()).collect();
+        assert_eq!(keys, ["MISSING", "TOKEN_PORT"]);
+        assert_eq!(violations[0].reason, "Unresolved variables: NOPE");
+        assert_eq!(violations[1].reason, "******** is not a port between 1 and 65535");
+        assert_eq!(violations[1].source, VariableSource::Workspace);
+
+        let only = HashSet::from(["PORT".to_string(), "MISSING".to_string()]);
+        assert_eq!(validate(&resolved, &resolved.values, Some(&only)).len(), 1);
+        assert_eq!(
+            violations_error(&violations[..1]),
+            "Invalid variables: MISSING: Unresolved variables: NOPE"
+        );
+    }
+
+    #[test]
+    fn decrypts_only_typed_values_and_their_references() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let encrypt =
+            |value: &str| crate::secure_store::encrypt(dir.path(), value).expect("encrypt");
+        // Fails to decrypt if it is ever touched: no key has this id.
+        let foreign = format!("{}unknownkey:AAAA", crate::secure_store::ENCRYPTED_PREFIX);
+        let layer = |value: String, value_type: &str| LayerValue {
+            value,
+            secure: true,
+            command: None,
+            schema: VariableSchema::parse(value_type, None).unwrap(),
+        };
+        let workspace = HashMap::from([
+            ("PORT".to_string(), layer(encrypt("${INNER}"), "port")),
+            ("INNER".to_string(), layer(encrypt("8080"), "string")),
+            ("UNUSED".to_string(), layer(foreign.clone(), "string")),
+        ]);
+        let mut resolved = merge_layers(&[(VariableSource::Workspace, workspace)]);
+
+        decrypt_typed_values(&mut resolved, dir.path(), &BuiltinContext::new(1))
+            .expect("decrypt");
+        assert_eq!(resolved.values["PORT"], "${INNER}");
+        assert_eq!(resolved.values["INNER"], "8080");
+        assert_eq!(resolved.values["UNUSED"], foreign);
+        assert!(validate(&resolved, &resolved.values, None).is_empty());
+    }
+
+    #[test]
+    fn rejects_non_string_overrides() {
+        let err = action_overrides(&serde_json::json!({ "variables": { "A": [1] } })).unwrap_err();
+        assert!(err.contains("Variable A"), "{}", err);
+        assert!(action_overrides(&serde_json::json!({ "variables": "A=1" })).is_err());
+    }
+}
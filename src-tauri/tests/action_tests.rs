@@ -287,7 +287,7 @@ async fn test_replace_variables_basic() {
     variables.insert("HOME".to_string(), "/home/user".to_string());
     variables.insert("APP".to_string(), "myapp".to_string());
 
-    let result = replace_variables("${HOME}/apps/${APP}", &variables);
+    let result = replace_variables("${HOME}/apps/${APP}", &variables).expect("render");
     eprintln!("[TEST] Replace variables result: {}", result);
     assert_eq!(result, "/home/user/apps/myapp");
 }
@@ -300,7 +300,7 @@ async fn test_replace_variables_multiple() {
     variables.insert("VAR2".to_string(), "value2".to_string());
     variables.insert("VAR3".to_string(), "value3".to_string());
 
-    let result = replace_variables("${VAR1}-${VAR2}-${VAR3}", &variables);
+    let result = replace_variables("${VAR1}-${VAR2}-${VAR3}", &variables).expect("render");
     eprintln!("[TEST] Replace multiple variables result: {}", result);
     assert_eq!(result, "value1-value2-value3");
 }
@@ -310,11 +310,24 @@ async fn test_replace_variables_multiple() {
 async fn test_replace_variables_no_match() {
     let variables = std::collections::HashMap::new();
 
-    let result = replace_variables("no variables here", &variables);
+    let result = replace_variables("no variables here", &variables).expect("render");
     eprintln!("[TEST] Replace no match result: {}", result);
     assert_eq!(result, "no variables here");
 }
 
+#[tokio::test]
+#[serial]
+async fn test_replace_variables_required_fails() {
+    let variables = std::collections::HashMap::new();
+
+    let result = replace_variables("${API_URL:?set API_URL first}", &variables);
+    assert_eq!(result.unwrap_err(), "API_URL: set API_URL first");
+    assert_eq!(
+        replace_variables("${UNKNOWN}", &variables).expect("render"),
+        "${UNKNOWN}"
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn test_spawn_hidden_process_basic() {
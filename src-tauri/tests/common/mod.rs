@@ -40,6 +40,7 @@ impl TestProcess {
     }
 }
 
+#[allow(clippy::zombie_processes)]
 pub fn spawn_long_running_process(name: Option<&str>) -> TestProcess {
     let display_name = name.unwrap_or("test_process");
 
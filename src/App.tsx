@@ -11,22 +11,30 @@ import {
 	Switch,
 } from "solid-js";
 import { Layout } from "@/components/Layout";
+import { OrphanedProcessesDialog } from "@/components/OrphanedProcessesDialog";
 import { UpdateDialog } from "@/components/UpdateDialog";
 import { Toaster } from "@/components/ui/sonner";
 import {
 	checkDatabaseSchema,
 	createRun,
+	emitReattachedProcesses,
 	initializeDatabase,
 	listAutoLaunchActions,
 	listenToActionEvents,
 	listGlobalVariables,
 	listVariablesByWorkspace,
+	type OrphanedProcess,
+	takeStartupOrphans,
 } from "@/libs/api";
-import type { ActionCompletedEvent } from "@/libs/api/types";
+import type {
+	ActionCompletedEvent,
+	ActionReattachedEvent,
+} from "@/libs/api/types";
 import {
 	launchAction as launchActionTS,
 	prepareVariables,
 } from "@/libs/launcher";
+import { getSelectedEnvironment } from "@/libs/selectedEnvironment";
 import { checkForUpdatesOnStartup } from "@/libs/updater";
 import { SettingsHotkeysPage } from "@/pages/SettingsHotkeysPage";
 import { SettingsPage } from "@/pages/SettingsPage";
@@ -34,19 +42,34 @@ import WorkspaceDetailPage from "@/pages/WorkspaceDetailPage";
 import { WorkspacesListPage } from "@/pages/WorkspacesListPage";
 import { startPidChecker, stopPidChecker } from "@/services/pidChecker";
 import {
+	handleTrackedProcessExit,
 	isActionTrackedAndAlive,
+	reattachRunningAction,
 	reconcileRunningActions,
 } from "@/services/processTracking";
 import { runningActionsService } from "@/services/runningActions";
 import { StoreProvider } from "@/store";
-import type { NewRun } from "@/types/database";
+import type { NewRun, RunStatus } from "@/types/database";
 
 const App: Component = () => {
 	const [initialized, setInitialized] = createSignal(false);
 	const [error, setError] = createSignal<string | null>(null);
+	const [startupOrphans, setStartupOrphans] = createSignal<OrphanedProcess[]>(
+		[],
+	);
+	const [orphansDialogOpen, setOrphansDialogOpen] = createSignal(false);
 
 	const handleActionCompleted = (event: CustomEvent<ActionCompletedEvent>) => {
-		const { action_id, workspace_id, exit_code, success } = event.detail;
+		const { action_id, workspace_id, exit_code, success, environment_id } =
+			event.detail;
+
+		if (event.detail.process_id !== undefined) {
+			handleTrackedProcessExit(
+				event.detail.process_id,
+				event.detail.completed_at,
+			).catch(console.error);
+			return;
+		}
 
 		const isTracked = runningActionsService
 			.getAll()
@@ -59,8 +82,10 @@ const App: Component = () => {
 			return;
 		}
 
-		let status: "success" | "failed" | "cancelled" = "failed";
-		if (success) {
+		let status: RunStatus = "failed";
+		if (success === null) {
+			status = "completed";
+		} else if (success) {
 			status = "success";
 		} else if (exit_code !== undefined && exit_code !== null) {
 			status = exit_code === 0 ? "success" : "failed";
@@ -73,7 +98,9 @@ const App: Component = () => {
 			started_at: new Date().toISOString(),
 			completed_at: new Date().toISOString(),
 			exit_code: exit_code ?? undefined,
-			error_message: success ? undefined : "Action completed with errors",
+			error_message:
+				success === false ? "Action completed with errors" : undefined,
+			environment_id: environment_id ?? null,
 		};
 
 		createRun(newRun)
@@ -92,6 +119,12 @@ const App: Component = () => {
 			});
 	};
 
+	const handleActionReattached = (
+		event: CustomEvent<ActionReattachedEvent>,
+	) => {
+		reattachRunningAction(event.detail);
+	};
+
 	onMount(async () => {
 		try {
 			console.log("Starting app initialization...");
@@ -114,16 +147,27 @@ const App: Component = () => {
 
 			console.log("Database schema validated");
 
-			listenToActionEvents();
-
 			window.addEventListener(
 				"action-completed",
 				handleActionCompleted as EventListener,
 			);
+			window.addEventListener(
+				"action-reattached",
+				handleActionReattached as EventListener,
+			);
+
+			await listenToActionEvents();
+			await emitReattachedProcesses();
 
 			await reconcileRunningActions({ cold: true });
 			startPidChecker();
 
+			const orphansResult = await takeStartupOrphans();
+			if (orphansResult.isOk() && orphansResult.value.length > 0) {
+				setStartupOrphans(orphansResult.value);
+				setOrphansDialogOpen(true);
+			}
+
 			checkForUpdatesOnStartup().catch((err) => {
 				console.error("Failed to check for updates:", err);
 			});
@@ -165,6 +209,7 @@ const App: Component = () => {
 							const context = {
 								workspaceId: action.workspace_id,
 								variables: variableMap,
+								environmentId: getSelectedEnvironment(action.workspace_id),
 							};
 
 							const result = await launchActionTS(action, context);
@@ -202,11 +247,20 @@ const App: Component = () => {
 			"action-completed",
 			handleActionCompleted as EventListener,
 		);
+		window.removeEventListener(
+			"action-reattached",
+			handleActionReattached as EventListener,
+		);
 	});
 
 	return (
 		<>
 			<UpdateDialog />
+			<OrphanedProcessesDialog
+				orphans={startupOrphans()}
+				open={orphansDialogOpen()}
+				onOpenChange={setOrphansDialogOpen}
+			/>
 			<Switch>
 				<Match when={error()}>
 					<div class="flex items-center justify-center h-screen bg-destructive/10">
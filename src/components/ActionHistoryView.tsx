@@ -34,6 +34,8 @@ const getStatusBadge = (status: Run["status"]) => {
 					Cancelled
 				</Badge>
 			);
+		case "completed":
+			return <Badge variant="secondary">Completed</Badge>;
 		default:
 			return <Badge variant="secondary">{status}</Badge>;
 	}
@@ -192,6 +194,12 @@ export const ActionHistoryView: Component<ActionHistoryViewProps> = (props) => {
 													Action was manually stopped by user
 												</div>
 											</Show>
+
+											<Show when={run.status === "completed"}>
+												<div class="text-xs text-muted-foreground italic">
+													Exited with an unknown exit status
+												</div>
+											</Show>
 										</div>
 										<Button
 											variant="ghost"
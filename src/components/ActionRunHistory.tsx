@@ -37,6 +37,8 @@ const getStatusBadge = (status: Run["status"]) => {
 					Cancelled
 				</Badge>
 			);
+		case "completed":
+			return <Badge variant="secondary">Completed</Badge>;
 		default:
 			return <Badge variant="secondary">{status}</Badge>;
 	}
@@ -211,6 +213,12 @@ export const ActionRunHistory: Component<ActionRunHistoryProps> = (props) => {
 													Manually stopped by user
 												</div>
 											</Show>
+
+											<Show when={run.status === "completed"}>
+												<div class="text-xs text-muted-foreground italic">
+													Exited with an unknown exit status
+												</div>
+											</Show>
 										</div>
 									</Card>
 								);
@@ -0,0 +1,244 @@
+import {
+	type Component,
+	createEffect,
+	createSignal,
+	For,
+	on,
+	Show,
+} from "solid-js";
+import {
+	AlertDialog,
+	AlertDialogAction,
+	AlertDialogClose,
+	AlertDialogContent,
+	AlertDialogDescription,
+	AlertDialogFooter,
+	AlertDialogHeader,
+	AlertDialogTitle,
+} from "@/components/ui/alert-dialog";
+import { Button } from "@/components/ui/button";
+import {
+	Dialog,
+	DialogContent,
+	DialogDescription,
+	DialogFooter,
+	DialogHeader,
+	DialogTitle,
+} from "@/components/ui/dialog";
+import {
+	TextField,
+	TextFieldLabel,
+	TextFieldRoot,
+} from "@/components/ui/textfield";
+import {
+	cloneEnvironment,
+	createEnvironment,
+	deleteEnvironment,
+	listEnvironments,
+} from "@/libs/api";
+import { showToast } from "@/libs/toast";
+import type { Environment } from "@/types/database";
+
+interface EnvironmentSelectorProps {
+	workspaceId: number;
+	/** Selected environment, or `null` to launch with workspace values only. */
+	value: number | null;
+	onChange: (environmentId: number | null) => void;
+}
+
+/** Picks the environment launches use and creates, clones or deletes one. */
+export const EnvironmentSelector: Component<EnvironmentSelectorProps> = (
+	props,
+) => {
+	const [environments, setEnvironments] = createSignal<Environment[]>([]);
+	const [naming, setNaming] = createSignal<"create" | "clone" | null>(null);
+	const [name, setName] = createSignal("");
+	const [saving, setSaving] = createSignal(false);
+	const [confirmingDelete, setConfirmingDelete] = createSignal(false);
+
+	const load = async (workspaceId: number) => {
+		const result = await listEnvironments(workspaceId);
+		if (result.isErr()) {
+			console.error(result.error.message);
+			return;
+		}
+		setEnvironments(result.value);
+		if (
+			props.value !== null &&
+			!result.value.some((environment) => environment.id === props.value)
+		) {
+			props.onChange(null);
+		}
+	};
+
+	createEffect(on(() => props.workspaceId, (id) => void load(id)));
+
+	const selected = () =>
+		environments().find((environment) => environment.id === props.value);
+
+	const openNaming = (mode: "create" | "clone") => {
+		setName(mode === "clone" ? `${selected()?.name ?? ""} copy` : "");
+		setNaming(mode);
+	};
+
+	const handleSave = async () => {
+		const trimmed = name().trim();
+		if (!trimmed) return;
+		setSaving(true);
+		const source = selected();
+		const result =
+			naming() === "clone" && source
+				? await cloneEnvironment(source.id, trimmed)
+				: await createEnvironment({
+						workspace_id: props.workspaceId,
+						name: trimmed,
+					});
+		setSaving(false);
+		if (result.isErr()) {
+			const error = result.error;
+			showToast({
+				title: "Failed to Save Environment",
+				description: typeof error === "string" ? error : error.message,
+				variant: "destructive",
+			});
+			return;
+		}
+		setNaming(null);
+		await load(props.workspaceId);
+		props.onChange(result.value.id);
+	};
+
+	const handleDelete = async () => {
+		const environment = selected();
+		setConfirmingDelete(false);
+		if (!environment) return;
+		const result = await deleteEnvironment(environment.id);
+		if (result.isErr()) {
+			showToast({
+				title: "Failed to Delete Environment",
+				description: result.error.message,
+				variant: "destructive",
+			});
+			return;
+		}
+		props.onChange(null);
+		await load(props.workspaceId);
+	};
+
+	return (
+		<div class="flex items-center gap-1">
+			<select
+				class="flex h-9 rounded-md bg-elevated-2 px-3 py-2 text-sm shadow-sm focus:outline-none focus-visible:(ring-1.5 ring-ring)"
+				title="Environment used when launching actions"
+				value={props.value?.toString() ?? ""}
+				onChange={(e) => {
+					const value = e.currentTarget.value;
+					props.onChange(value ? Number(value) : null);
+				}}
+			>
+				<option value="">No environment</option>
+				<For each={environments()}>
+					{(environment) => (
+						<option value={environment.id.toString()}>
+							{environment.name}
+						</option>
+					)}
+				</For>
+			</select>
+			<Button
+				variant="outline"
+				size="icon"
+				onClick={() => openNaming("create")}
+				title="New Environment"
+			>
+				<div class="i-mdi-plus w-4 h-4" />
+			</Button>
+			<Show when={selected()}>
+				<Button
+					variant="outline"
+					size="icon"
+					onClick={() => openNaming("clone")}
+					title="Clone Environment"
+				>
+					<div class="i-mdi-content-copy w-4 h-4" />
+				</Button>
+				<Button
+					variant="outline"
+					size="icon"
+					onClick={() => setConfirmingDelete(true)}
+					title="Delete Environment"
+				>
+					<div class="i-mdi-delete w-4 h-4" />
+				</Button>
+			</Show>
+
+			<Dialog
+				open={naming() !== null}
+				onOpenChange={(open) => !open && setNaming(null)}
+			>
+				<DialogContent class="max-w-md">
+					<DialogHeader>
+						<DialogTitle>
+							{naming() === "clone" ? "Clone Environment" : "New Environment"}
+						</DialogTitle>
+						<DialogDescription>
+							{naming() === "clone"
+								? `Copies "${selected()?.name ?? ""}" and its variables.`
+								: "Environment variables override workspace variables."}
+						</DialogDescription>
+					</DialogHeader>
+					<div class="px-4 py-4">
+						<TextFieldRoot>
+							<TextFieldLabel for="environment-name">Name *</TextFieldLabel>
+							<TextField
+								id="environment-name"
+								value={name()}
+								onInput={(e: InputEvent) =>
+									setName((e.target as HTMLInputElement).value)
+								}
+								placeholder="staging"
+							/>
+						</TextFieldRoot>
+					</div>
+					<DialogFooter>
+						<Button
+							variant="outline"
+							onClick={() => setNaming(null)}
+							disabled={saving()}
+						>
+							Cancel
+						</Button>
+						<Button
+							onClick={() => void handleSave()}
+							disabled={saving() || !name().trim()}
+						>
+							{naming() === "clone" ? "Clone" : "Create"}
+						</Button>
+					</DialogFooter>
+				</DialogContent>
+			</Dialog>
+
+			<AlertDialog open={confirmingDelete()} onOpenChange={setConfirmingDelete}>
+				<AlertDialogContent>
+					<AlertDialogHeader>
+						<AlertDialogTitle>Delete Environment</AlertDialogTitle>
+						<AlertDialogDescription>
+							Are you sure you want to delete the environment "
+							{selected()?.name}" and its variables? This action cannot be
+							undone.
+						</AlertDialogDescription>
+					</AlertDialogHeader>
+					<AlertDialogFooter>
+						<AlertDialogClose>Cancel</AlertDialogClose>
+						<AlertDialogAction
+							onClick={() => void handleDelete()}
+							class="bg-destructive text-destructive-foreground hover:bg-destructive/90"
+						>
+							Delete Environment
+						</AlertDialogAction>
+					</AlertDialogFooter>
+				</AlertDialogContent>
+			</AlertDialog>
+		</div>
+	);
+};
@@ -0,0 +1,82 @@
+import { type Component, createSignal, For } from "solid-js";
+import {
+	AlertDialog,
+	AlertDialogAction,
+	AlertDialogClose,
+	AlertDialogContent,
+	AlertDialogDescription,
+	AlertDialogFooter,
+	AlertDialogHeader,
+	AlertDialogTitle,
+} from "@/components/ui/alert-dialog";
+import { type OrphanedProcess, stopOrphanedProcesses } from "@/libs/api";
+import { showToast } from "@/libs/toast";
+
+interface OrphanedProcessesDialogProps {
+	orphans: OrphanedProcess[];
+	open: boolean;
+	onOpenChange: (open: boolean) => void;
+}
+
+/** Lists leftover processes of a previous session and stops them on confirm. */
+export const OrphanedProcessesDialog: Component<
+	OrphanedProcessesDialogProps
+> = (props) => {
+	const [stopping, setStopping] = createSignal(false);
+
+	const handleStop = async () => {
+		setStopping(true);
+		const result = await stopOrphanedProcesses(props.orphans);
+		setStopping(false);
+		props.onOpenChange(false);
+		if (result.isErr()) {
+			showToast({
+				title: "Failed to stop leftover processes",
+				description: result.error,
+				variant: "destructive",
+			});
+			return;
+		}
+		const { orphans, killed, failed } = result.value;
+		showToast({
+			title: `Stopped ${killed.length} of ${orphans.length} leftover processes`,
+			description:
+				failed.length > 0 ? `Could not stop PIDs ${failed.join(", ")}` : undefined,
+			variant: failed.length > 0 ? "destructive" : undefined,
+		});
+	};
+
+	return (
+		<AlertDialog open={props.open} onOpenChange={props.onOpenChange}>
+			<AlertDialogContent>
+				<AlertDialogHeader>
+					<AlertDialogTitle>Stop Leftover Processes</AlertDialogTitle>
+					<AlertDialogDescription>
+						These processes were launched by a previous session and belong to
+						no running action. Stop them?
+					</AlertDialogDescription>
+				</AlertDialogHeader>
+				<ul class="max-h-64 overflow-y-auto space-y-1 text-sm">
+					<For each={props.orphans}>
+						{(orphan) => (
+							<li class="font-mono break-all">
+								<span class="text-muted-foreground">{orphan.pid}</span>{" "}
+								{orphan.cmd.length > 0 ? orphan.cmd.join(" ") : orphan.name}
+							</li>
+						)}
+					</For>
+				</ul>
+				<AlertDialogFooter>
+					<AlertDialogClose disabled={stopping()}>Keep Running</AlertDialogClose>
+					<AlertDialogAction
+						onClick={handleStop}
+						disabled={stopping()}
+						class="bg-destructive text-destructive-foreground hover:bg-destructive/90"
+					>
+						{stopping() ? "Stopping..." : "Stop Processes"}
+					</AlertDialogAction>
+				</AlertDialogFooter>
+			</AlertDialogContent>
+		</AlertDialog>
+	);
+};
@@ -7,6 +7,7 @@ import {
 	onMount,
 	Show,
 } from "solid-js";
+import { isWindows } from "@/libs/launcher";
 import { useRunStore } from "@/store";
 import { Badge } from "./ui/badge";
 import { Button } from "./ui/button";
@@ -34,7 +35,7 @@ function formatDuration(startedAt: string): string {
 	return `${seconds}s`;
 }
 
-function statusBadge(action: { status?: string }): {
+function statusBadge(action: { status?: string; suspended?: boolean }): {
 	label: string;
 	className: string;
 } {
@@ -44,7 +45,9 @@ function statusBadge(action: { status?: string }): {
 		case "exited":
 			return { label: "Exited", className: "bg-muted-foreground" };
 		default:
-			return { label: "Running", className: "bg-blue-500" };
+			return action.suspended
+				? { label: "Suspended", className: "bg-slate-500" }
+				: { label: "Running", className: "bg-blue-500" };
 	}
 }
 
@@ -154,6 +157,21 @@ export const RunningActionsPanel: Component<RunningActionsPanelProps> = (
 											>
 												Stop
 											</Button>
+											{/* Suspending relies on SIGSTOP, which Windows lacks. */}
+											<Show
+												when={
+													!isWindows() &&
+													(action.status ?? "running") === "running"
+												}
+											>
+												<Button
+													variant="outline"
+													size="sm"
+													onClick={() => actions.toggleSuspend(action)}
+												>
+													{action.suspended ? "Resume" : "Suspend"}
+												</Button>
+											</Show>
 											<Show
 												when={
 													action.status === "unreachable" ||
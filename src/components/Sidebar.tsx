@@ -21,6 +21,7 @@ import { listGlobalVariables } from "@/libs/api";
 import { cn } from "@/libs/cn";
 import { hotkeyTitle } from "@/libs/hotkeys";
 import { launchWorkspace, prepareVariables } from "@/libs/launcher";
+import { getSelectedEnvironment } from "@/libs/selectedEnvironment";
 import { showToast } from "@/libs/toast";
 import { runningActionsService } from "@/services/runningActions";
 import { useActionStore } from "@/store/action";
@@ -237,6 +238,7 @@ export const Sidebar: Component<SidebarProps> = (props) => {
 										const context = {
 											workspaceId: workspace.id,
 											variables,
+											environmentId: getSelectedEnvironment(workspace.id),
 										};
 
 										await launchWorkspace(actionStore.actions, context);
@@ -41,6 +41,7 @@ import { useToolStore } from "@/store/tool";
 import { useVariableStore } from "@/store/variable";
 import type {
 	CustomToolActionConfig,
+	InstancePolicy,
 	NewAction,
 	NewTool,
 	PlaceholderDefinition,
@@ -113,6 +114,8 @@ export const ActionDialogStepper: Component<ActionDialogStepperProps> = (
 	const [autoLaunch, setAutoLaunch] = createSignal<boolean>(
 		props.action?.auto_launch ?? false,
 	);
+	const [instancePolicy, setInstancePolicy] =
+		createSignal<InstancePolicy>("allow_multiple");
 
 	const [customToolType, setCustomToolType] =
 		createSignal<CustomToolType>("cli");
@@ -301,6 +304,7 @@ export const ActionDialogStepper: Component<ActionDialogStepperProps> = (
 		setAutoLaunch(action.auto_launch ?? false);
 
 		const parsedConfig = parseToolActionConfig(action.config);
+		setInstancePolicy(parsedConfig?.instance_policy ?? "allow_multiple");
 		if (parsedConfig?.source === "saved") {
 			applySavedConfig(parsedConfig);
 			return;
@@ -323,6 +327,7 @@ export const ActionDialogStepper: Component<ActionDialogStepperProps> = (
 		setDetached(false);
 		setTrackProcess(true);
 		setAutoLaunch(false);
+		setInstancePolicy("allow_multiple");
 		chooseDefaultTool();
 	};
 
@@ -366,10 +371,16 @@ export const ActionDialogStepper: Component<ActionDialogStepperProps> = (
 		};
 	};
 
-	const buildActionConfig = (): ToolActionConfig | null =>
-		toolMode() === "saved"
-			? buildSavedActionConfig()
-			: buildCustomActionConfig();
+	const buildActionConfig = (): ToolActionConfig | null => {
+		const config =
+			toolMode() === "saved"
+				? buildSavedActionConfig()
+				: buildCustomActionConfig();
+		if (config && instancePolicy() !== "allow_multiple") {
+			config.instance_policy = instancePolicy();
+		}
+		return config;
+	};
 
 	const buildActionPayload = (config: ToolActionConfig): NewAction => ({
 		workspace_id: Number(props.workspaceId),
@@ -1028,6 +1039,46 @@ export const ActionDialogStepper: Component<ActionDialogStepperProps> = (
 											/>
 										</TextFieldRoot>
 									</div>
+
+									<div class="col-span-2 space-y-2">
+										<div>
+											<p class="text-sm font-medium">When already running</p>
+											<p class="text-xs text-muted-foreground">
+												What launching does while a tracked instance runs
+											</p>
+										</div>
+										<ToggleGroup
+											class="justify-start"
+											value={instancePolicy()}
+											onChange={(value) => {
+												if (
+													value === "allow_multiple" ||
+													value === "skip_if_running" ||
+													value === "restart_if_running"
+												)
+													setInstancePolicy(value);
+											}}
+										>
+											<ToggleGroupItem
+												value="allow_multiple"
+												class="h-7 px-3 text-xs"
+											>
+												Start another
+											</ToggleGroupItem>
+											<ToggleGroupItem
+												value="skip_if_running"
+												class="h-7 px-3 text-xs"
+											>
+												Skip
+											</ToggleGroupItem>
+											<ToggleGroupItem
+												value="restart_if_running"
+												class="h-7 px-3 text-xs"
+											>
+												Restart
+											</ToggleGroupItem>
+										</ToggleGroup>
+									</div>
 								</div>
 							</CollapsibleContent>
 						</Collapsible>
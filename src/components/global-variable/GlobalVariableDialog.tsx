@@ -17,9 +17,14 @@ import {
 	TextFieldRoot,
 } from "@/components/ui/textfield";
 import { variableSchema } from "@/components/variable/VariableDialogValidation";
+import { VariableTypeFields } from "@/components/variable/VariableTypeFields";
 import { showToast } from "@/libs/toast";
 import { useGlobalVariableStore } from "@/store/globalVariable";
-import type { GlobalVariable } from "@/types/database";
+import {
+	ENCRYPTED_VALUE_PREFIX,
+	type GlobalVariable,
+	type VariableValueType,
+} from "@/types/database";
 
 type GlobalVariableDialogProps = {
 	variable?: GlobalVariable;
@@ -31,20 +36,30 @@ type GlobalVariableDialogProps = {
 export const GlobalVariableDialog: Component<GlobalVariableDialogProps> = (
 	props,
 ) => {
-	const [, globalVariableStoreActions] = useGlobalVariableStore() ?? [
-		null,
-		null,
-	];
+	const [globalVariableStore, globalVariableStoreActions] =
+		useGlobalVariableStore() ?? [null, null];
 	const [open, setOpen] = createSignal(false);
 	const isOpen = () =>
 		props.forceOpen !== undefined ? props.forceOpen : open();
 
 	const [key, setKey] = createSignal(props.variable?.key || "");
-	const [value, setValue] = createSignal(props.variable?.value || "");
+	// Stored secure values are ciphertext: the field starts empty and an empty
+	// value on save keeps the stored one.
+	const hasStoredSecret = () =>
+		props.variable?.value.startsWith(ENCRYPTED_VALUE_PREFIX) ?? false;
+	const [value, setValue] = createSignal(
+		hasStoredSecret() ? "" : props.variable?.value || "",
+	);
 	const [isSecure, setIsSecure] = createSignal(
 		props.variable?.is_secure || false,
 	);
 	const [enabled, setEnabled] = createSignal(props.variable?.enabled ?? true);
+	const [valueType, setValueType] = createSignal<VariableValueType>(
+		props.variable?.value_type ?? "string",
+	);
+	const [typeOptions, setTypeOptions] = createSignal(
+		props.variable?.type_options ?? null,
+	);
 	const [keyError, setKeyError] = createSignal<string | null>(null);
 
 	const [loading, setLoading] = createSignal(false);
@@ -55,12 +70,16 @@ export const GlobalVariableDialog: Component<GlobalVariableDialogProps> = (
 			setValue("");
 			setIsSecure(false);
 			setEnabled(true);
+			setValueType("string");
+			setTypeOptions(null);
 			setKeyError(null);
 		} else if (isOpen() && props.variable) {
 			setKey(props.variable.key);
-			setValue(props.variable.value);
+			setValue(hasStoredSecret() ? "" : props.variable.value);
 			setIsSecure(props.variable.is_secure);
 			setEnabled(props.variable.enabled ?? true);
+			setValueType(props.variable.value_type ?? "string");
+			setTypeOptions(props.variable.type_options ?? null);
 			setKeyError(null);
 		}
 	});
@@ -102,9 +121,16 @@ export const GlobalVariableDialog: Component<GlobalVariableDialogProps> = (
 			if (props.variable) {
 				await globalVariableStoreActions.updateVariable(props.variable.id, {
 					key: key(),
-					value: value(),
+					value:
+						value() === "" && hasStoredSecret()
+							? props.variable.value
+							: value(),
 					is_secure: isSecure(),
 					enabled: enabled(),
+					value_source: props.variable.value_source,
+					cache_ttl_secs: props.variable.cache_ttl_secs,
+					value_type: valueType(),
+					type_options: typeOptions(),
 				});
 			} else {
 				const newVariable = {
@@ -112,9 +138,14 @@ export const GlobalVariableDialog: Component<GlobalVariableDialogProps> = (
 					value: value(),
 					is_secure: isSecure(),
 					enabled: enabled(),
+					value_type: valueType(),
+					type_options: typeOptions(),
 				};
 				await globalVariableStoreActions.addVariable(newVariable);
 			}
+			if (globalVariableStore?.error) {
+				throw globalVariableStore.error;
+			}
 
 			setOpen(false);
 			props.onClose?.();
@@ -206,10 +237,23 @@ export const GlobalVariableDialog: Component<GlobalVariableDialogProps> = (
 								onInput={(e: InputEvent) =>
 									setValue((e.target as HTMLInputElement).value)
 								}
-								placeholder="Variable value"
+								placeholder={
+									hasStoredSecret()
+										? "Unchanged (stored encrypted)"
+										: "Variable value"
+								}
 							/>
 						</TextFieldRoot>
 
+						<VariableTypeFields
+							valueType={valueType()}
+							typeOptions={typeOptions()}
+							onChange={(nextType, nextOptions) => {
+								setValueType(nextType);
+								setTypeOptions(nextOptions);
+							}}
+						/>
+
 						<div class="space-y-3 pt-2">
 							<div class="flex items-center justify-between space-x-2">
 								<div class="space-y-0.5">
@@ -3,6 +3,7 @@ import type { Component } from "solid-js";
 import { createSignal, onMount } from "solid-js";
 import { DatabaseResetDialog } from "@/components/DatabaseResetDialog";
 import { ImportDialog } from "@/components/ImportDialog";
+import { OrphanedProcessesDialog } from "@/components/OrphanedProcessesDialog";
 import { ShareDialog } from "@/components/ShareDialog";
 import { Button } from "@/components/ui/button";
 import {
@@ -31,6 +32,9 @@ export const GeneralSettings: Component = () => {
 	const [, toolActions] = useToolStore();
 	const [autoLaunch, setAutoLaunch] = createSignal(false);
 	const [extraPathDirectories, setExtraPathDirectories] = createSignal("");
+	const [sweepOrphans, setSweepOrphans] = createSignal(false);
+	const [orphans, setOrphans] = createSignal<api.OrphanedProcess[]>([]);
+	const [orphansDialogOpen, setOrphansDialogOpen] = createSignal(false);
 	const [resetDialogOpen, setResetDialogOpen] = createSignal(false);
 	const [backupDialogOpen, setBackupDialogOpen] = createSignal(false);
 	const openDataLocation = async () => {
@@ -58,8 +62,47 @@ export const GeneralSettings: Component = () => {
 				setExtraPathDirectories(result.value.value);
 			}
 		});
+		api.getSetting(SETTING_KEYS.SWEEP_ORPHANS_ON_STARTUP).then((result) => {
+			if (result.isOk()) {
+				setSweepOrphans(result.value?.value === "true");
+			}
+		});
 	});
 
+	const toggleSweepOrphans = async (enabled: boolean) => {
+		setSweepOrphans(enabled);
+		const result = await api.setSetting(
+			SETTING_KEYS.SWEEP_ORPHANS_ON_STARTUP,
+			String(enabled),
+		);
+		if (result.isErr()) {
+			setSweepOrphans(!enabled);
+			showToast({
+				title: "Failed to save",
+				description: result.error.message,
+				variant: "destructive",
+			});
+		}
+	};
+
+	const findOrphanedProcesses = async () => {
+		const result = await api.findOrphanedProcesses();
+		if (result.isErr()) {
+			showToast({
+				title: "Failed to list leftover processes",
+				description: result.error,
+				variant: "destructive",
+			});
+			return;
+		}
+		if (result.value.length === 0) {
+			showToast({ title: "No leftover processes" });
+			return;
+		}
+		setOrphans(result.value);
+		setOrphansDialogOpen(true);
+	};
+
 	const saveExtraPathDirectories = async (value: string) => {
 		setExtraPathDirectories(value);
 		const result = await api.setSetting(
@@ -193,6 +236,29 @@ export const GeneralSettings: Component = () => {
 
 					<Separator />
 
+					<div class="flex items-center justify-between">
+						<div class="space-y-1">
+							<p class="text-sm font-medium">Stop leftover processes</p>
+							<p class="text-sm text-muted-foreground">
+								Stop processes a crashed session left running; the switch checks
+								for them on every startup
+							</p>
+						</div>
+						<div class="flex items-center gap-3">
+							<Button variant="outline" onClick={findOrphanedProcesses}>
+								<span class="iconify w-4 h-4 mr-2" data-icon="mdi:broom" />
+								Stop Now
+							</Button>
+							<Switch checked={sweepOrphans()} onChange={toggleSweepOrphans}>
+								<SwitchControl>
+									<SwitchThumb />
+								</SwitchControl>
+							</Switch>
+						</div>
+					</div>
+
+					<Separator />
+
 					<TextFieldRoot>
 						<TextFieldLabel for="extra-paths">
 							Extra PATH directories
@@ -417,6 +483,12 @@ export const GeneralSettings: Component = () => {
 				description="This will export all your data to a JSON file and then reset the database. Your data will be safely backed up. Are you sure you want to continue?"
 				confirmText="Backup & Reset"
 			/>
+
+			<OrphanedProcessesDialog
+				orphans={orphans()}
+				open={orphansDialogOpen()}
+				onOpenChange={setOrphansDialogOpen}
+			/>
 		</div>
 	);
 };
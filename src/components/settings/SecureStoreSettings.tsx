@@ -0,0 +1,148 @@
+import type { Component } from "solid-js";
+import { createSignal, onMount, Show } from "solid-js";
+import { Button } from "@/components/ui/button";
+import {
+	Card,
+	CardContent,
+	CardDescription,
+	CardHeader,
+	CardTitle,
+} from "@/components/ui/card";
+import { Separator } from "@/components/ui/separator";
+import {
+	TextField,
+	TextFieldLabel,
+	TextFieldRoot,
+} from "@/components/ui/textfield";
+import * as api from "@/libs/api";
+import { showToast } from "@/libs/toast";
+import type { SecureStoreStatus } from "@/types/database";
+
+export const SecureStoreSettings: Component = () => {
+	const [status, setStatus] = createSignal<SecureStoreStatus | null>(null);
+	const [unlockPassphrase, setUnlockPassphrase] = createSignal("");
+	const [newPassphrase, setNewPassphrase] = createSignal("");
+	const [busy, setBusy] = createSignal(false);
+
+	const loadStatus = async () => {
+		const result = await api.getSecureStoreStatus();
+		if (result.isOk()) {
+			setStatus(result.value);
+		} else {
+			console.error("Failed to load secure store status:", result.error);
+		}
+	};
+
+	onMount(() => void loadStatus());
+
+	const unlock = async () => {
+		setBusy(true);
+		const result = await api.unlockSecureStore(unlockPassphrase());
+		setBusy(false);
+		if (result.isErr()) {
+			showToast({
+				title: "Unlock failed",
+				description: result.error,
+				variant: "destructive",
+			});
+			return;
+		}
+		setUnlockPassphrase("");
+		await loadStatus();
+		showToast({ title: "Secure values unlocked", variant: "success" });
+	};
+
+	const rotate = async () => {
+		setBusy(true);
+		const result = await api.rotateSecureKey(newPassphrase() || undefined);
+		setBusy(false);
+		if (result.isErr()) {
+			showToast({
+				title: "Key rotation failed",
+				description: result.error,
+				variant: "destructive",
+			});
+			return;
+		}
+		setNewPassphrase("");
+		await loadStatus();
+		showToast({
+			title: "Key rotated",
+			description: `Re-encrypted ${result.value} secure value${result.value === 1 ? "" : "s"}`,
+			variant: "success",
+		});
+	};
+
+	return (
+		<Card>
+			<CardHeader>
+				<CardTitle class="flex items-center gap-2">
+					<div class="i-mdi-lock w-5 h-5" />
+					Secure Values
+				</CardTitle>
+				<CardDescription>
+					Secure variables are stored encrypted.{" "}
+					{status()?.passphrase
+						? "The key is protected by a passphrase."
+						: "The key is kept in the application data folder."}
+				</CardDescription>
+			</CardHeader>
+			<CardContent class="space-y-4">
+				<Show when={status()?.locked}>
+					<TextFieldRoot>
+						<TextFieldLabel for="secure-store-unlock">
+							Passphrase
+						</TextFieldLabel>
+						<div class="flex gap-2">
+							<TextField
+								id="secure-store-unlock"
+								type="password"
+								value={unlockPassphrase()}
+								onInput={(e: InputEvent) =>
+									setUnlockPassphrase((e.target as HTMLInputElement).value)
+								}
+								placeholder="Unlock secure values for this session"
+							/>
+							<Button
+								onClick={() => void unlock()}
+								disabled={busy() || !unlockPassphrase()}
+							>
+								Unlock
+							</Button>
+						</div>
+					</TextFieldRoot>
+
+					<Separator />
+				</Show>
+
+				<TextFieldRoot>
+					<TextFieldLabel for="secure-store-rotate">
+						Rotate encryption key
+					</TextFieldLabel>
+					<div class="flex gap-2">
+						<TextField
+							id="secure-store-rotate"
+							type="password"
+							value={newPassphrase()}
+							onInput={(e: InputEvent) =>
+								setNewPassphrase((e.target as HTMLInputElement).value)
+							}
+							placeholder="New passphrase (optional)"
+						/>
+						<Button
+							variant="outline"
+							onClick={() => void rotate()}
+							disabled={busy() || status()?.locked}
+						>
+							Rotate Key
+						</Button>
+					</div>
+					<p class="text-xs text-muted-foreground mt-1">
+						Re-encrypts every secure value under a new key. Without a
+						passphrase the new key is stored in the data folder.
+					</p>
+				</TextFieldRoot>
+			</CardContent>
+		</Card>
+	);
+};
@@ -18,8 +18,13 @@ import {
 } from "@/components/ui/textfield";
 import { showToast } from "@/libs/toast";
 import type { Variable } from "@/models/variable.model";
+import {
+	ENCRYPTED_VALUE_PREFIX,
+	type VariableValueType,
+} from "@/types/database";
 import { useVariableStore } from "@/store/variable";
 import { variableSchema } from "./VariableDialogValidation";
+import { VariableTypeFields } from "./VariableTypeFields";
 
 type VariableDialogProps = {
 	workspaceId: string;
@@ -30,17 +35,32 @@ type VariableDialogProps = {
 };
 
 export const VariableDialog: Component<VariableDialogProps> = (props) => {
-	const [, variableStoreActions] = useVariableStore() ?? [null, null];
+	const [variableStore, variableStoreActions] = useVariableStore() ?? [
+		null,
+		null,
+	];
 	const [open, setOpen] = createSignal(false);
 	const isOpen = () =>
 		props.forceOpen !== undefined ? props.forceOpen : open();
 
 	const [key, setKey] = createSignal(props.variable?.key || "");
-	const [value, setValue] = createSignal(props.variable?.value || "");
+	// Stored secure values are ciphertext: the field starts empty and an empty
+	// value on save keeps the stored one.
+	const hasStoredSecret = () =>
+		props.variable?.value.startsWith(ENCRYPTED_VALUE_PREFIX) ?? false;
+	const [value, setValue] = createSignal(
+		hasStoredSecret() ? "" : props.variable?.value || "",
+	);
 	const [isSecure, setIsSecure] = createSignal(
 		props.variable?.is_secure || false,
 	);
 	const [enabled, setEnabled] = createSignal(props.variable?.enabled ?? true);
+	const [valueType, setValueType] = createSignal<VariableValueType>(
+		props.variable?.value_type ?? "string",
+	);
+	const [typeOptions, setTypeOptions] = createSignal(
+		props.variable?.type_options ?? null,
+	);
 	const [keyError, setKeyError] = createSignal<string | null>(null);
 
 	const [loading, setLoading] = createSignal(false);
@@ -51,12 +71,16 @@ export const VariableDialog: Component<VariableDialogProps> = (props) => {
 			setValue("");
 			setIsSecure(false);
 			setEnabled(true);
+			setValueType("string");
+			setTypeOptions(null);
 			setKeyError(null);
 		} else if (isOpen() && props.variable) {
 			setKey(props.variable.key);
-			setValue(props.variable.value);
+			setValue(hasStoredSecret() ? "" : props.variable.value);
 			setIsSecure(props.variable.is_secure);
 			setEnabled(props.variable.enabled ?? true);
+			setValueType(props.variable.value_type ?? "string");
+			setTypeOptions(props.variable.type_options ?? null);
 			setKeyError(null);
 		}
 	});
@@ -99,9 +123,16 @@ export const VariableDialog: Component<VariableDialogProps> = (props) => {
 				await variableStoreActions.updateVariable(props.variable.id, {
 					workspace_id: Number(props.workspaceId),
 					key: key(),
-					value: value(),
+					value:
+						value() === "" && hasStoredSecret()
+							? props.variable.value
+							: value(),
 					is_secure: isSecure(),
 					enabled: enabled(),
+					value_source: props.variable.value_source,
+					cache_ttl_secs: props.variable.cache_ttl_secs,
+					value_type: valueType(),
+					type_options: typeOptions(),
 				});
 			} else {
 				const newVariable = {
@@ -110,9 +141,14 @@ export const VariableDialog: Component<VariableDialogProps> = (props) => {
 					value: value(),
 					is_secure: isSecure(),
 					enabled: enabled(),
+					value_type: valueType(),
+					type_options: typeOptions(),
 				};
 				await variableStoreActions.addVariable(newVariable);
 			}
+			if (variableStore?.error) {
+				throw variableStore.error;
+			}
 
 			setOpen(false);
 			props.onClose?.();
@@ -202,10 +238,23 @@ export const VariableDialog: Component<VariableDialogProps> = (props) => {
 								onInput={(e: InputEvent) =>
 									setValue((e.target as HTMLInputElement).value)
 								}
-								placeholder="Variable value"
+								placeholder={
+									hasStoredSecret()
+										? "Unchanged (stored encrypted)"
+										: "Variable value"
+								}
 							/>
 						</TextFieldRoot>
 
+						<VariableTypeFields
+							valueType={valueType()}
+							typeOptions={typeOptions()}
+							onChange={(nextType, nextOptions) => {
+								setValueType(nextType);
+								setTypeOptions(nextOptions);
+							}}
+						/>
+
 						<div class="space-y-3 pt-2">
 							<div class="flex items-center justify-between space-x-2">
 								<div class="space-y-0.5">
@@ -0,0 +1,122 @@
+import { type Component, createSignal, For, Show } from "solid-js";
+import {
+	TextField,
+	TextFieldLabel,
+	TextFieldRoot,
+} from "@/components/ui/textfield";
+import type { VariableValueType } from "@/types/database";
+
+const VALUE_TYPES: { value: VariableValueType; label: string }[] = [
+	{ value: "string", label: "Text" },
+	{ value: "path", label: "Path" },
+	{ value: "directory", label: "Existing directory" },
+	{ value: "file", label: "Existing file" },
+	{ value: "url", label: "URL" },
+	{ value: "integer", label: "Integer" },
+	{ value: "port", label: "Port" },
+	{ value: "enum", label: "One of a list" },
+	{ value: "pattern", label: "Matches a pattern" },
+];
+
+interface VariableTypeFieldsProps {
+	valueType: VariableValueType;
+	/** JSON options: `{"values": [...]}` for enum, `{"pattern": "..."}`. */
+	typeOptions: string | null;
+	onChange: (valueType: VariableValueType, typeOptions: string | null) => void;
+}
+
+function parseOptions(typeOptions: string | null): {
+	values?: string[];
+	pattern?: string;
+} {
+	try {
+		return typeOptions ? JSON.parse(typeOptions) : {};
+	} catch {
+		return {};
+	}
+}
+
+/** Type of a variable, checked against its value on save and at launch. */
+export const VariableTypeFields: Component<VariableTypeFieldsProps> = (
+	props,
+) => {
+	// Kept as typed so separators are not dropped mid-edit.
+	const [enumText, setEnumText] = createSignal(
+		(parseOptions(props.typeOptions).values ?? []).join(", "),
+	);
+	const pattern = () => parseOptions(props.typeOptions).pattern ?? "";
+
+	const setType = (valueType: VariableValueType) => {
+		const options =
+			valueType === "enum"
+				? JSON.stringify({ values: parseOptions(props.typeOptions).values ?? [] })
+				: valueType === "pattern"
+					? JSON.stringify({ pattern: pattern() })
+					: null;
+		props.onChange(valueType, options);
+	};
+
+	return (
+		<>
+			<TextFieldRoot>
+				<TextFieldLabel for="variable-type">Type</TextFieldLabel>
+				<select
+					id="variable-type"
+					class="flex h-9 w-full rounded-md bg-elevated-2 px-3 py-2 text-sm shadow-sm focus:outline-none focus-visible:(ring-1.5 ring-ring)"
+					value={props.valueType}
+					onChange={(e) =>
+						setType(e.currentTarget.value as VariableValueType)
+					}
+				>
+					<For each={VALUE_TYPES}>
+						{(type) => <option value={type.value}>{type.label}</option>}
+					</For>
+				</select>
+			</TextFieldRoot>
+
+			<Show when={props.valueType === "enum"}>
+				<TextFieldRoot>
+					<TextFieldLabel for="variable-enum-values">
+						Allowed values
+					</TextFieldLabel>
+					<TextField
+						id="variable-enum-values"
+						value={enumText()}
+						onInput={(e: InputEvent) => {
+							const text = (e.target as HTMLInputElement).value;
+							setEnumText(text);
+							const values = text
+								.split(",")
+								.map((value) => value.trim())
+								.filter((value) => value.length > 0);
+							props.onChange("enum", JSON.stringify({ values }));
+						}}
+						placeholder="dev, staging, prod"
+					/>
+				</TextFieldRoot>
+			</Show>
+
+			<Show when={props.valueType === "pattern"}>
+				<TextFieldRoot>
+					<TextFieldLabel for="variable-pattern">Pattern</TextFieldLabel>
+					<TextField
+						id="variable-pattern"
+						value={pattern()}
+						onInput={(e: InputEvent) =>
+							props.onChange(
+								"pattern",
+								JSON.stringify({
+									pattern: (e.target as HTMLInputElement).value,
+								}),
+							)
+						}
+						placeholder="^v\d+\.\d+$"
+					/>
+					<p class="text-xs text-muted-foreground mt-1">
+						Regular expression the value must match
+					</p>
+				</TextFieldRoot>
+			</Show>
+		</>
+	);
+};
@@ -1,20 +1,32 @@
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import Database from "@tauri-apps/plugin-sql";
 import { err, ok, type Result } from "neverthrow";
 import type {
 	Action,
+	BuiltinVariable,
+	Environment,
+	EnvironmentVariable,
 	GlobalVariable,
 	NewAction,
+	NewEnvironment,
+	NewEnvironmentVariable,
 	NewGlobalVariable,
 	NewRun,
 	NewTool,
 	NewVariable,
 	NewWorkspace,
+	PidMatcher,
 	Run,
+	RunStatus,
+	SecureStoreStatus,
 	Setting,
 	Tool,
 	Variable,
+	VariableResolution,
+	VariableValueSource,
+	VariableValueType,
+	VariableViolation,
 	Workspace,
 } from "@/types/database";
 
@@ -31,11 +43,15 @@ export interface LaunchActionRequest {
 	action_type: string;
 	config: Record<string, unknown>;
 	variables: Record<string, string>;
+	environment_id?: number | null;
+	/** Shared by the requests of one launch so command variables run once. */
+	launch_id?: string | null;
 }
 
 export interface LaunchWorkspaceRequest {
 	workspace_id: number;
 	actions: LaunchActionRequest[];
+	environment_id?: number | null;
 }
 
 export interface LaunchResult {
@@ -47,6 +63,7 @@ export interface LaunchResult {
 
 export interface ActionStartedEvent {
 	action_id: number;
+	environment_id?: number | null;
 	workspace_id: number;
 	run_id: number;
 	process_id?: number;
@@ -54,10 +71,43 @@ export interface ActionStartedEvent {
 
 export interface ActionCompletedEvent {
 	action_id: number;
+	environment_id?: number | null;
 	workspace_id: number;
 	run_id: number;
 	exit_code?: number;
-	success: boolean;
+	/** `null` when the exit status is unknown (a process we did not spawn). */
+	success: boolean | null;
+	/** Set when the backend exit watcher saw a tracked process exit. */
+	process_id?: number;
+	completed_at?: string;
+}
+
+export interface ActionReattachedEvent {
+	action_id: number;
+	action_name: string;
+	environment_id?: number | null;
+	workspace_id: number;
+	run_id?: number | null;
+	process_id: number;
+	process_start_time_secs: number;
+	process_name: string;
+}
+
+export interface ListeningSocket {
+	pid: number;
+	protocol: "tcp" | "tcp6" | "udp" | "udp6";
+	port: number;
+}
+
+export interface ActionPortsChangedEvent {
+	action_id: number;
+	workspace_id: number;
+	run_id?: number | null;
+	process_id: number;
+	/** Every socket the action's processes listen on now. */
+	ports: ListeningSocket[];
+	/** The sockets that appeared since the last event. */
+	added: ListeningSocket[];
 }
 
 export interface ActionLogEvent {
@@ -386,6 +436,10 @@ interface RawVariableRow {
 	value: string;
 	is_secure: string | number | boolean;
 	enabled: string | number | boolean;
+	value_source: VariableValueSource;
+	cache_ttl_secs: number | null;
+	value_type: VariableValueType;
+	type_options: string | null;
 	created_at: string;
 	updated_at: string;
 }
@@ -437,6 +491,29 @@ function convertDatabaseRowToVariable(row: RawVariableRow): Variable {
 	return converted;
 }
 
+/**
+ * Validates the value against its declared type, then encrypts secure
+ * values in the backend before they are stored.
+ */
+async function storedValue(variable: {
+	value: string;
+	is_secure?: boolean;
+	value_source?: VariableValueSource;
+	value_type?: VariableValueType;
+	type_options?: string | null;
+}): Promise<string> {
+	await invoke<void>("check_variable_value", {
+		valueType: variable.value_type ?? "string",
+		typeOptions: variable.type_options ?? null,
+		value: variable.value,
+		valueSource: variable.value_source ?? null,
+	});
+	if (!variable.is_secure) {
+		return variable.value;
+	}
+	return invoke<string>("encrypt_secure_value", { value: variable.value });
+}
+
 export async function createVariable(
 	variable: NewVariable,
 ): Promise<Result<Variable, ApiError>> {
@@ -445,19 +522,23 @@ export async function createVariable(
 		const db = getDatabase();
 
 		const result = await db.execute(
-			"INSERT INTO variables (workspace_id, key, value, is_secure, enabled) VALUES ($1, $2, $3, $4, $5)",
+			"INSERT INTO variables (workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
 			[
 				variable.workspace_id,
 				variable.key,
-				variable.value,
+				await storedValue(variable),
 				variable.is_secure ? 1 : 0,
 				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
 			],
 		);
 		console.log("Variable inserted, ID:", result.lastInsertId);
 
 		const rows = await db.select<RawVariableRow[]>(
-			"SELECT id, workspace_id, key, value, is_secure, enabled, created_at, updated_at FROM variables WHERE id = $1",
+			"SELECT id, workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM variables WHERE id = $1",
 			[result.lastInsertId],
 		);
 		if (rows.length === 0) {
@@ -478,7 +559,7 @@ export async function listVariablesByWorkspace(
 	try {
 		const db = getDatabase();
 		const rows = await db.select<RawVariableRow[]>(
-			"SELECT id, workspace_id, key, value, is_secure, enabled, created_at, updated_at FROM variables WHERE workspace_id = $1 ORDER BY key ASC",
+			"SELECT id, workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM variables WHERE workspace_id = $1 ORDER BY key ASC",
 			[workspaceId],
 		);
 		const variables = rows.map((row) => convertDatabaseRowToVariable(row));
@@ -495,18 +576,22 @@ export async function updateVariable(
 	try {
 		const db = getDatabase();
 		await db.execute(
-			"UPDATE variables SET workspace_id = $1, key = $2, value = $3, is_secure = $4, enabled = $5, updated_at = CURRENT_TIMESTAMP WHERE id = $6",
+			"UPDATE variables SET workspace_id = $1, key = $2, value = $3, is_secure = $4, enabled = $5, value_source = $6, cache_ttl_secs = $7, value_type = $8, type_options = $9, updated_at = CURRENT_TIMESTAMP WHERE id = $10",
 			[
 				variable.workspace_id,
 				variable.key,
-				variable.value,
+				await storedValue(variable),
 				variable.is_secure ? 1 : 0,
 				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
 				id,
 			],
 		);
 		const rows = await db.select<RawVariableRow[]>(
-			"SELECT id, workspace_id, key, value, is_secure, enabled, created_at, updated_at FROM variables WHERE id = $1",
+			"SELECT id, workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM variables WHERE id = $1",
 			[id],
 		);
 		if (rows.length === 0) {
@@ -542,7 +627,7 @@ export async function toggleVariableEnabled(
 			[enabled ? 1 : 0, id],
 		);
 		const rows = await db.select<RawVariableRow[]>(
-			"SELECT id, workspace_id, key, value, is_secure, enabled, created_at, updated_at FROM variables WHERE id = $1",
+			"SELECT id, workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM variables WHERE id = $1",
 			[id],
 		);
 		if (rows.length === 0) {
@@ -561,6 +646,10 @@ interface RawGlobalVariableRow {
 	value: string;
 	is_secure: string | number | boolean;
 	enabled: string | number | boolean;
+	value_source: VariableValueSource;
+	cache_ttl_secs: number | null;
+	value_type: VariableValueType;
+	type_options: string | null;
 	created_at: string;
 	updated_at: string;
 }
@@ -581,16 +670,20 @@ export async function createGlobalVariable(
 	try {
 		const db = getDatabase();
 		const result = await db.execute(
-			"INSERT INTO global_variables (key, value, is_secure, enabled) VALUES ($1, $2, $3, $4)",
+			"INSERT INTO global_variables (key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
 			[
 				variable.key,
-				variable.value,
+				await storedValue(variable),
 				variable.is_secure ? 1 : 0,
 				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
 			],
 		);
 		const rows = await db.select<RawGlobalVariableRow[]>(
-			"SELECT id, key, value, is_secure, enabled, created_at, updated_at FROM global_variables WHERE id = $1",
+			"SELECT id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM global_variables WHERE id = $1",
 			[result.lastInsertId],
 		);
 		if (rows.length === 0) {
@@ -608,7 +701,7 @@ export async function listGlobalVariables(): Promise<
 	try {
 		const db = getDatabase();
 		const rows = await db.select<RawGlobalVariableRow[]>(
-			"SELECT id, key, value, is_secure, enabled, created_at, updated_at FROM global_variables ORDER BY key ASC",
+			"SELECT id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM global_variables ORDER BY key ASC",
 		);
 		const variables = rows.map((row) =>
 			convertDatabaseRowToGlobalVariable(row),
@@ -626,17 +719,21 @@ export async function updateGlobalVariable(
 	try {
 		const db = getDatabase();
 		await db.execute(
-			"UPDATE global_variables SET key = $1, value = $2, is_secure = $3, enabled = $4, updated_at = CURRENT_TIMESTAMP WHERE id = $5",
+			"UPDATE global_variables SET key = $1, value = $2, is_secure = $3, enabled = $4, value_source = $5, cache_ttl_secs = $6, value_type = $7, type_options = $8, updated_at = CURRENT_TIMESTAMP WHERE id = $9",
 			[
 				variable.key,
-				variable.value,
+				await storedValue(variable),
 				variable.is_secure ? 1 : 0,
 				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
 				id,
 			],
 		);
 		const rows = await db.select<RawGlobalVariableRow[]>(
-			"SELECT id, key, value, is_secure, enabled, created_at, updated_at FROM global_variables WHERE id = $1",
+			"SELECT id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM global_variables WHERE id = $1",
 			[id],
 		);
 		if (rows.length === 0) {
@@ -671,7 +768,7 @@ export async function toggleGlobalVariableEnabled(
 			[enabled ? 1 : 0, id],
 		);
 		const rows = await db.select<RawGlobalVariableRow[]>(
-			"SELECT id, key, value, is_secure, enabled, created_at, updated_at FROM global_variables WHERE id = $1",
+			"SELECT id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at FROM global_variables WHERE id = $1",
 			[id],
 		);
 		if (rows.length === 0) {
@@ -683,6 +780,208 @@ export async function toggleGlobalVariableEnabled(
 	}
 }
 
+// ============================================================================
+// Environment Management
+// ============================================================================
+
+export async function listEnvironments(
+	workspaceId: number,
+): Promise<Result<Environment[], ApiError>> {
+	try {
+		const db = getDatabase();
+		const rows = await db.select<Environment[]>(
+			"SELECT * FROM environments WHERE workspace_id = $1 ORDER BY name ASC",
+			[workspaceId],
+		);
+		return ok(rows);
+	} catch (error) {
+		return err({ message: `Failed to list environments: ${error}` });
+	}
+}
+
+export async function createEnvironment(
+	environment: NewEnvironment,
+): Promise<Result<Environment, ApiError>> {
+	try {
+		const db = getDatabase();
+		const result = await db.execute(
+			"INSERT INTO environments (workspace_id, name, description) VALUES ($1, $2, $3)",
+			[
+				environment.workspace_id,
+				environment.name,
+				environment.description ?? null,
+			],
+		);
+		const rows = await db.select<Environment[]>(
+			"SELECT * FROM environments WHERE id = $1",
+			[result.lastInsertId],
+		);
+		return ok(rows[0]);
+	} catch (error) {
+		return err({ message: `Failed to create environment: ${error}` });
+	}
+}
+
+export async function updateEnvironment(
+	id: number,
+	environment: NewEnvironment,
+): Promise<Result<Environment, ApiError>> {
+	try {
+		const db = getDatabase();
+		await db.execute(
+			"UPDATE environments SET name = $1, description = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $3",
+			[environment.name, environment.description ?? null, id],
+		);
+		const rows = await db.select<Environment[]>(
+			"SELECT * FROM environments WHERE id = $1",
+			[id],
+		);
+		if (rows.length === 0) {
+			throw new Error("Environment not found");
+		}
+		return ok(rows[0]);
+	} catch (error) {
+		return err({ message: `Failed to update environment: ${error}` });
+	}
+}
+
+export async function deleteEnvironment(
+	id: number,
+): Promise<Result<void, ApiError>> {
+	try {
+		const db = getDatabase();
+		await db.execute(
+			"DELETE FROM environment_variables WHERE environment_id = $1",
+			[id],
+		);
+		await db.execute("DELETE FROM environments WHERE id = $1", [id]);
+		return ok(undefined);
+	} catch (error) {
+		return err({ message: `Failed to delete environment: ${error}` });
+	}
+}
+
+/** Copies an environment and its variables under a new name. */
+export async function cloneEnvironment(
+	environmentId: number,
+	name: string,
+): Promise<Result<Environment, string>> {
+	try {
+		return ok(
+			await invoke<Environment>("clone_environment", { environmentId, name }),
+		);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+type RawEnvironmentVariableRow = Omit<
+	EnvironmentVariable,
+	"is_secure" | "enabled"
+> & {
+	is_secure: string | number | boolean;
+	enabled: string | number | boolean;
+};
+
+function convertEnvironmentVariableRow(
+	row: RawEnvironmentVariableRow,
+): EnvironmentVariable {
+	return {
+		...row,
+		is_secure: sqliteBoolean(row.is_secure),
+		enabled: sqliteBoolean(row.enabled),
+	};
+}
+
+export async function listEnvironmentVariables(
+	environmentId: number,
+): Promise<Result<EnvironmentVariable[], ApiError>> {
+	try {
+		const db = getDatabase();
+		const rows = await db.select<RawEnvironmentVariableRow[]>(
+			"SELECT * FROM environment_variables WHERE environment_id = $1 ORDER BY key ASC",
+			[environmentId],
+		);
+		return ok(rows.map(convertEnvironmentVariableRow));
+	} catch (error) {
+		return err({ message: `Failed to list environment variables: ${error}` });
+	}
+}
+
+export async function createEnvironmentVariable(
+	variable: NewEnvironmentVariable,
+): Promise<Result<EnvironmentVariable, ApiError>> {
+	try {
+		const db = getDatabase();
+		const result = await db.execute(
+			"INSERT INTO environment_variables (environment_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+			[
+				variable.environment_id,
+				variable.key,
+				await storedValue(variable),
+				variable.is_secure ? 1 : 0,
+				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
+			],
+		);
+		const rows = await db.select<RawEnvironmentVariableRow[]>(
+			"SELECT * FROM environment_variables WHERE id = $1",
+			[result.lastInsertId],
+		);
+		return ok(convertEnvironmentVariableRow(rows[0]));
+	} catch (error) {
+		return err({ message: `Failed to create environment variable: ${error}` });
+	}
+}
+
+export async function updateEnvironmentVariable(
+	id: number,
+	variable: NewEnvironmentVariable,
+): Promise<Result<EnvironmentVariable, ApiError>> {
+	try {
+		const db = getDatabase();
+		await db.execute(
+			"UPDATE environment_variables SET key = $1, value = $2, is_secure = $3, enabled = $4, value_source = $5, cache_ttl_secs = $6, value_type = $7, type_options = $8, updated_at = CURRENT_TIMESTAMP WHERE id = $9",
+			[
+				variable.key,
+				await storedValue(variable),
+				variable.is_secure ? 1 : 0,
+				(variable.enabled ?? true) ? 1 : 0,
+				variable.value_source ?? "static",
+				variable.cache_ttl_secs ?? null,
+				variable.value_type ?? "string",
+				variable.type_options ?? null,
+				id,
+			],
+		);
+		const rows = await db.select<RawEnvironmentVariableRow[]>(
+			"SELECT * FROM environment_variables WHERE id = $1",
+			[id],
+		);
+		if (rows.length === 0) {
+			throw new Error("Environment variable not found");
+		}
+		return ok(convertEnvironmentVariableRow(rows[0]));
+	} catch (error) {
+		return err({ message: `Failed to update environment variable: ${error}` });
+	}
+}
+
+export async function deleteEnvironmentVariable(
+	id: number,
+): Promise<Result<void, ApiError>> {
+	try {
+		const db = getDatabase();
+		await db.execute("DELETE FROM environment_variables WHERE id = $1", [id]);
+		return ok(undefined);
+	} catch (error) {
+		return err({ message: `Failed to delete environment variable: ${error}` });
+	}
+}
+
 export async function getSetting(
 	key: string,
 ): Promise<Result<Setting | null, ApiError>> {
@@ -762,27 +1061,90 @@ export async function launchWorkspace(
 	}
 }
 
-export function listenToActionEvents() {
-	listen<ActionStartedEvent>("action-started", (event) => {
+export async function emitReattachedProcesses(): Promise<
+	Result<number, ApiError>
+> {
+	try {
+		return ok(await invoke<number>("emit_reattached_processes"));
+	} catch (error) {
+		console.error("Failed to re-attach tracked processes:", error);
+		return err({ message: `Failed to re-attach tracked processes: ${error}` });
+	}
+}
+
+/** Every criterion that is set must hold for the process to be adopted. */
+export interface AdoptProcessCriteria {
+	pid?: number;
+	name?: string;
+	cmdline_contains?: string;
+	/** Listening TCP or UDP port (Linux only). */
+	port?: number;
+}
+
+/**
+ * Attaches a process started outside the launcher to an action. It is then
+ * listed as running via an `action-reattached` event and can be stopped.
+ */
+export async function adoptProcess(
+	actionId: number,
+	criteria: AdoptProcessCriteria,
+): Promise<Result<ActionReattachedEvent, string>> {
+	try {
+		const result = await invoke<ActionReattachedEvent>("adopt_process", {
+			req: { action_id: actionId, ...criteria },
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export function listenToActionEvents(): Promise<UnlistenFn[]> {
+	const started = listen<ActionStartedEvent>("action-started", (event) => {
 		console.log("Action started:", event.payload);
 		window.dispatchEvent(
 			new CustomEvent("action-started", { detail: event.payload }),
 		);
 	});
 
-	listen<ActionCompletedEvent>("action-completed", (event) => {
-		console.log("Action completed:", event.payload);
-		window.dispatchEvent(
-			new CustomEvent("action-completed", { detail: event.payload }),
-		);
-	});
+	const completed = listen<ActionCompletedEvent>(
+		"action-completed",
+		(event) => {
+			console.log("Action completed:", event.payload);
+			window.dispatchEvent(
+				new CustomEvent("action-completed", { detail: event.payload }),
+			);
+		},
+	);
 
-	listen<ActionLogEvent>("action-log", (event) => {
+	const reattached = listen<ActionReattachedEvent>(
+		"action-reattached",
+		(event) => {
+			console.log("Action re-attached:", event.payload);
+			window.dispatchEvent(
+				new CustomEvent("action-reattached", { detail: event.payload }),
+			);
+		},
+	);
+
+	const portsChanged = listen<ActionPortsChangedEvent>(
+		"action-ports-changed",
+		(event) => {
+			console.log("Action ports changed:", event.payload);
+			window.dispatchEvent(
+				new CustomEvent("action-ports-changed", { detail: event.payload }),
+			);
+		},
+	);
+
+	const log = listen<ActionLogEvent>("action-log", (event) => {
 		console.log("Action log:", event.payload);
 		window.dispatchEvent(
 			new CustomEvent("action-log", { detail: event.payload }),
 		);
 	});
+
+	return Promise.all([started, completed, reattached, portsChanged, log]);
 }
 
 export async function prepareActionLaunchRequest(
@@ -992,7 +1354,7 @@ export async function createRun(run: NewRun): Promise<Result<Run, string>> {
 	try {
 		const db = getDatabase();
 		const result = await db.execute(
-			"INSERT INTO runs (workspace_id, action_id, status, started_at, completed_at, exit_code, error_message) VALUES (?, ?, ?, ?, ?, ?, ?)",
+			"INSERT INTO runs (workspace_id, action_id, status, started_at, completed_at, exit_code, error_message, environment_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
 			[
 				run.workspace_id,
 				run.action_id,
@@ -1001,6 +1363,7 @@ export async function createRun(run: NewRun): Promise<Result<Run, string>> {
 				run.completed_at ?? null,
 				run.exit_code ?? null,
 				run.error_message ?? null,
+				run.environment_id ?? null,
 			],
 		);
 		const created = await db.select<Run[]>("SELECT * FROM runs WHERE id = ?", [
@@ -1015,7 +1378,7 @@ export async function createRun(run: NewRun): Promise<Result<Run, string>> {
 
 export async function updateRunStatus(
 	id: number,
-	status: "success" | "failed" | "cancelled",
+	status: RunStatus,
 	exitCode?: number,
 	errorMessage?: string,
 ): Promise<Result<void, string>> {
@@ -1123,6 +1486,8 @@ export interface KillProcessResponse {
 	success: boolean;
 	message: string;
 	denied?: boolean;
+	/** The PID was reused by another process; nothing was signalled. */
+	identity_mismatch?: boolean;
 }
 
 export interface DiscoverExecutableResponse {
@@ -1173,11 +1538,266 @@ export async function stopProcess(
 	}
 }
 
+export interface ProcessTreeNode {
+	pid: number;
+	parent_pid?: number;
+	name: string;
+	cmd: string[];
+	cwd?: string;
+	start_time_secs: number;
+	cpu_usage: number;
+	memory: number;
+	/** Tracked by the launcher itself, not only as a descendant. */
+	tracked: boolean;
+	suspended: boolean;
+	children: ProcessTreeNode[];
+}
+
+/** Everything a run (or a single process) has spawned, as a live tree. */
+export async function getActionProcessTree(target: {
+	runId?: number;
+	pid?: number;
+}): Promise<Result<ProcessTreeNode | null, string>> {
+	try {
+		const result = await invoke<ProcessTreeNode | null>(
+			"get_action_process_tree",
+			{ req: { run_id: target.runId ?? null, pid: target.pid ?? null } },
+		);
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Sockets the processes of a run (or a single process tree) listen on. */
+export async function getActionPorts(target: {
+	runId?: number;
+	pid?: number;
+}): Promise<Result<ListeningSocket[], string>> {
+	try {
+		const result = await invoke<ListeningSocket[]>("get_action_ports", {
+			req: { run_id: target.runId ?? null, pid: target.pid ?? null },
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export interface OrphanedProcess {
+	pid: number;
+	name: string;
+	cmd: string[];
+	run_id: number;
+	start_time_secs: number;
+}
+
+export interface OrphanSweepResult {
+	orphans: OrphanedProcess[];
+	killed: number[];
+	failed: number[];
+}
+
+/** Processes launched by us that belong to no live run. */
+export async function findOrphanedProcesses(): Promise<
+	Result<OrphanedProcess[], string>
+> {
+	try {
+		return ok(await invoke<OrphanedProcess[]>("find_orphaned_processes"));
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Stops orphans the user confirmed. */
+export async function stopOrphanedProcesses(
+	orphans: OrphanedProcess[],
+): Promise<Result<OrphanSweepResult, string>> {
+	try {
+		const result = await invoke<OrphanSweepResult>("stop_orphaned_processes", {
+			orphans,
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Orphans found at startup when the sweep setting is on; returned once. */
+export async function takeStartupOrphans(): Promise<
+	Result<OrphanedProcess[], string>
+> {
+	try {
+		return ok(await invoke<OrphanedProcess[]>("take_startup_orphans"));
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export interface SuspendResult {
+	action_id: number;
+	pid: number;
+	suspended: boolean;
+	message: string;
+}
+
+/** Freezes the running process of an action (Unix only). */
+export async function suspendAction(
+	actionId: number,
+): Promise<Result<SuspendResult, string>> {
+	try {
+		const result = await invoke<SuspendResult>("suspend_action", {
+			actionId,
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export async function resumeAction(
+	actionId: number,
+): Promise<Result<SuspendResult, string>> {
+	try {
+		const result = await invoke<SuspendResult>("resume_action", { actionId });
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export interface PidCandidate {
+	pid: number;
+	parent_pid?: number | null;
+	name: string;
+	cmd: string[];
+	exe?: string | null;
+	cwd?: string | null;
+	start_time_secs: number;
+	descendant: boolean;
+	matched: boolean;
+	/** One line per matcher criterion explaining the outcome. */
+	reasons: string[];
+}
+
+export interface PidMatcherReport {
+	selected?: number | null;
+	candidates: PidCandidate[];
+}
+
+/** Shows which processes a PID matcher would pick and why. */
+export async function testPidMatcher(
+	matcher: PidMatcher,
+	options?: { parentPid?: number; startedAfterSecs?: number },
+): Promise<Result<PidMatcherReport, string>> {
+	try {
+		const result = await invoke<PidMatcherReport>("test_pid_matcher", {
+			req: {
+				matcher,
+				parent_pid: options?.parentPid ?? null,
+				started_after_secs: options?.startedAfterSecs ?? null,
+			},
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Shows which layer each variable of a workspace/action resolves from. */
+export async function resolveVariables(
+	workspaceId: number,
+	actionId?: number,
+	names?: string[],
+	environmentId?: number,
+): Promise<Result<VariableResolution, string>> {
+	try {
+		const result = await invoke<VariableResolution>("resolve_variables", {
+			workspaceId,
+			environmentId: environmentId ?? null,
+			actionId: actionId ?? null,
+			names: names ?? null,
+		});
+		return ok(result);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Typed variables of a workspace whose values do not match their type. */
+export async function validateVariables(
+	workspaceId: number,
+	environmentId?: number,
+): Promise<Result<VariableViolation[], string>> {
+	try {
+		return ok(
+			await invoke<VariableViolation[]>("validate_variables", {
+				workspaceId,
+				environmentId: environmentId ?? null,
+			}),
+		);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Built-in `${...}` variables, for autocomplete in variable inputs. */
+export async function listBuiltinVariables(): Promise<
+	Result<BuiltinVariable[], string>
+> {
+	try {
+		return ok(await invoke<BuiltinVariable[]>("list_builtin_variables"));
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/** Unlocks a passphrase-protected secure store for this session. */
+export async function unlockSecureStore(
+	passphrase: string,
+): Promise<Result<void, string>> {
+	try {
+		return ok(await invoke<void>("unlock_secure_store", { passphrase }));
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+export async function getSecureStoreStatus(): Promise<
+	Result<SecureStoreStatus, string>
+> {
+	try {
+		return ok(await invoke<SecureStoreStatus>("get_secure_store_status"));
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
+/**
+ * Re-encrypts all secure values under a new key, derived from `passphrase`
+ * when given. Resolves to the number of values re-encrypted.
+ */
+export async function rotateSecureKey(
+	passphrase?: string,
+): Promise<Result<number, string>> {
+	try {
+		return ok(
+			await invoke<number>("rotate_secure_key", {
+				passphrase: passphrase ?? null,
+			}),
+		);
+	} catch (error) {
+		return err(String(error));
+	}
+}
+
 export async function resetDatabase(): Promise<Result<void, ApiError>> {
 	try {
 		const db = getDatabase();
 		const tables = [
 			"runs",
+			"environment_variables",
+			"environments",
 			"variables",
 			"global_variables",
 			"actions",
@@ -1211,6 +1831,8 @@ export async function backupAndResetDatabase(): Promise<
 		let variables: Variable[] = [];
 		let tools: Tool[] = [];
 		let settings: Setting[] = [];
+		let environments: Environment[] = [];
+		let environmentVariables: EnvironmentVariable[] = [];
 
 		try {
 			workspaces = await db.select<Workspace[]>("SELECT * FROM workspaces");
@@ -1218,6 +1840,12 @@ export async function backupAndResetDatabase(): Promise<
 			variables = await db.select<Variable[]>("SELECT * FROM variables");
 			tools = await db.select<Tool[]>("SELECT * FROM tools");
 			settings = await db.select<Setting[]>("SELECT * FROM settings");
+			environments = await db.select<Environment[]>(
+				"SELECT * FROM environments",
+			);
+			environmentVariables = await db.select<EnvironmentVariable[]>(
+				"SELECT * FROM environment_variables",
+			);
 		} catch (_backupError) {
 			console.log("Some tables don't exist, creating empty backup...");
 		}
@@ -1228,6 +1856,8 @@ export async function backupAndResetDatabase(): Promise<
 			variables,
 			tools,
 			settings,
+			environments,
+			environment_variables: environmentVariables,
 		};
 
 		const timestamp =
@@ -1251,6 +1881,8 @@ export async function backupAndResetDatabase(): Promise<
 
 		const tables = [
 			"runs",
+			"environment_variables",
+			"environments",
 			"variables",
 			"global_variables",
 			"actions",
@@ -1338,7 +1970,7 @@ export async function backupAndResetDatabase(): Promise<
 
 			for (const variable of variables) {
 				await db.execute(
-					`INSERT INTO variables (id, workspace_id, key, value, is_secure, enabled, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)`,
+					`INSERT INTO variables (id, workspace_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
 					[
 						variable.id,
 						variable.workspace_id,
@@ -1346,6 +1978,44 @@ export async function backupAndResetDatabase(): Promise<
 						variable.value,
 						variable.is_secure ? 1 : 0,
 						variable.enabled ? 1 : 0,
+						variable.value_source ?? "static",
+						variable.cache_ttl_secs ?? null,
+						variable.value_type ?? "string",
+						variable.type_options ?? null,
+						variable.created_at,
+						variable.updated_at,
+					],
+				);
+			}
+
+			for (const environment of environments) {
+				await db.execute(
+					`INSERT INTO environments (id, workspace_id, name, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)`,
+					[
+						environment.id,
+						environment.workspace_id,
+						environment.name,
+						environment.description,
+						environment.created_at,
+						environment.updated_at,
+					],
+				);
+			}
+
+			for (const variable of environmentVariables) {
+				await db.execute(
+					`INSERT INTO environment_variables (id, environment_id, key, value, is_secure, enabled, value_source, cache_ttl_secs, value_type, type_options, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+					[
+						variable.id,
+						variable.environment_id,
+						variable.key,
+						variable.value,
+						variable.is_secure ? 1 : 0,
+						variable.enabled ? 1 : 0,
+						variable.value_source ?? "static",
+						variable.cache_ttl_secs ?? null,
+						variable.value_type ?? "string",
+						variable.type_options ?? null,
 						variable.created_at,
 						variable.updated_at,
 					],
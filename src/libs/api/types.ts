@@ -25,6 +25,7 @@ export interface LaunchResult {
 
 export interface ActionStartedEvent {
 	action_id: number;
+	environment_id?: number | null;
 	workspace_id: number;
 	run_id: number;
 	process_id?: number;
@@ -32,10 +33,43 @@ export interface ActionStartedEvent {
 
 export interface ActionCompletedEvent {
 	action_id: number;
+	environment_id?: number | null;
 	workspace_id: number;
 	run_id: number;
 	exit_code?: number;
-	success: boolean;
+	/** `null` when the exit status is unknown (a process we did not spawn). */
+	success: boolean | null;
+	/** Set when the backend exit watcher saw a tracked process exit. */
+	process_id?: number;
+	completed_at?: string;
+}
+
+export interface ActionReattachedEvent {
+	action_id: number;
+	action_name: string;
+	environment_id?: number | null;
+	workspace_id: number;
+	run_id?: number | null;
+	process_id: number;
+	process_start_time_secs: number;
+	process_name: string;
+}
+
+export interface ListeningSocket {
+	pid: number;
+	protocol: "tcp" | "tcp6" | "udp" | "udp6";
+	port: number;
+}
+
+export interface ActionPortsChangedEvent {
+	action_id: number;
+	workspace_id: number;
+	run_id?: number | null;
+	process_id: number;
+	/** Every socket the action's processes listen on now. */
+	ports: ListeningSocket[];
+	/** The sockets that appeared since the last event. */
+	added: ListeningSocket[];
 }
 
 export interface ActionLogEvent {
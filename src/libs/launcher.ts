@@ -10,24 +10,48 @@ import type {
 	ActionConfig,
 	CommandActionConfig,
 	DelayActionConfig,
+	InstanceDecision,
 	ToolActionConfig,
 	URLActionConfig,
+	VariableValueSource,
+	WorkspaceActionConfig,
 } from "@/types/database";
-import { SETTING_KEYS } from "@/types/database";
+import { ENCRYPTED_VALUE_PREFIX, SETTING_KEYS } from "@/types/database";
 
 export interface LaunchContext {
 	workspaceId: number;
+	/**
+	 * Plain workspace and global values, used for client-side defaults only.
+	 * The backend loads every variable layer itself, so launch requests send
+	 * no variables of their own.
+	 */
 	variables: Record<string, string>;
+	/** Environment whose variables are layered over the workspace's. */
+	environmentId?: number | null;
+	/** Shared by the actions of one launch so command variables run once. */
+	launchId?: string;
 }
 
 function normalizeActionConfig(
 	actionType: string,
 	config: ActionConfig,
 	_context: LaunchContext,
-): { type: "tool" | "command" | "url" | "delay"; config: ActionConfig } {
+): {
+	type: "tool" | "command" | "url" | "delay" | "workspace";
+	config: ActionConfig;
+} {
 	const t = actionType.toLowerCase();
-	if (t === "tool" || t === "command" || t === "url" || t === "delay") {
-		return { type: t as "tool" | "command" | "url" | "delay", config };
+	if (
+		t === "tool" ||
+		t === "command" ||
+		t === "url" ||
+		t === "delay" ||
+		t === "workspace"
+	) {
+		return {
+			type: t as "tool" | "command" | "url" | "delay" | "workspace",
+			config,
+		};
 	}
 
 	type ConfigWithUnknownKeys = ActionConfig & Record<string, unknown>;
@@ -83,6 +107,7 @@ export interface LaunchResult {
 	processId?: number;
 	runId?: number;
 	workingDirectory?: string;
+	instanceDecision?: InstanceDecision;
 }
 
 async function getExtraPathDirectories(): Promise<string | undefined> {
@@ -159,6 +184,7 @@ async function trackRunningAction(
 		action_name: action.name,
 		process_id: processId,
 		run_id: runId,
+		environment_id: context.environmentId ?? null,
 		started_at: new Date().toISOString(),
 		working_directory: workingDirectory,
 		launched_at_secs: Math.floor(Date.now() / 1000),
@@ -175,24 +201,72 @@ async function trackRunningAction(
 	);
 }
 
-function isWindows(): boolean {
+export function isWindows(): boolean {
 	return navigator.userAgent.includes("Windows");
 }
 
-function replaceVariables(
-	input: string,
-	variables: Record<string, string>,
-): string {
-	let result = input;
-	for (const [key, value] of Object.entries(variables)) {
-		result = result.replace(new RegExp(`\\$\\{${key}\\}`, "g"), value);
+/**
+ * Renders `${...}` references in `inputs` with the variables the backend
+ * resolves for the action. `config` carries the raw templates, so the command
+ * variables it references are run once here.
+ */
+async function renderLaunchStrings(
+	actionType: string,
+	config: ActionConfig,
+	context: LaunchContext,
+	actionId: number | undefined,
+	inputs: string[],
+): Promise<string[]> {
+	return invoke<string[]>("render_launch_strings", {
+		request: {
+			action_id: actionId ?? 0,
+			workspace_id: context.workspaceId,
+			action_type: actionType,
+			config,
+			variables: {},
+			environment_id: context.environmentId ?? null,
+			launch_id: context.launchId ?? null,
+		},
+		inputs,
+	});
+}
+
+/**
+ * Renders the working directory of a backend launch for process tracking.
+ * Only the directory is sent, so no other command variable is run.
+ */
+async function renderWorkingDirectory(
+	actionType: string,
+	config: ActionConfig,
+	context: LaunchContext,
+	actionId: number | undefined,
+	workingDir: string,
+): Promise<string> {
+	if (!workingDir.includes("${")) {
+		return workingDir;
 	}
-	return result;
+	const [rendered] = await renderLaunchStrings(
+		actionType,
+		{
+			type: config.type,
+			variables: config.variables,
+			working_directory: workingDir,
+		} as ActionConfig,
+		context,
+		actionId,
+		[workingDir],
+	);
+	return rendered;
 }
 
 export function prepareVariables(
 	variables: Variable[],
-	globalVariables: { key: string; value: string; enabled: boolean }[] = [],
+	globalVariables: {
+		key: string;
+		value: string;
+		enabled: boolean;
+		value_source?: VariableValueSource;
+	}[] = [],
 ): Record<string, string> {
 	console.log("prepareVariables called with:", {
 		workspaceVariablesCount: variables.length,
@@ -202,14 +276,15 @@ export function prepareVariables(
 
 	const variableMap: Record<string, string> = {};
 
-	for (const variable of globalVariables) {
-		if (variable.enabled) {
-			variableMap[variable.key] = variable.value;
-		}
-	}
-
-	for (const variable of variables) {
-		if (variable.enabled) {
+	// Encrypted secure values are left out, as are command variables whose
+	// value is the command rather than its output; the backend resolves both
+	// at launch time.
+	for (const variable of [...globalVariables, ...variables]) {
+		if (
+			variable.enabled &&
+			variable.value_source !== "command" &&
+			!variable.value.startsWith(ENCRYPTED_VALUE_PREFIX)
+		) {
 			variableMap[variable.key] = variable.value;
 		}
 	}
@@ -218,12 +293,24 @@ export function prepareVariables(
 	return variableMap;
 }
 
+/**
+ * Gives `context` a launch id unless it already has one, so the renders and
+ * the launch requests of an action share one run of each command variable.
+ */
+function withLaunchId(context: LaunchContext): LaunchContext {
+	return context.launchId
+		? context
+		: { ...context, launchId: crypto.randomUUID() };
+}
+
 export async function launchAction(
 	action: Action,
 	context: LaunchContext,
 ): Promise<LaunchResult> {
 	console.log(`Launching action: ${action.name} (type: ${action.action_type})`);
 
+	const launchContext = withLaunchId(context);
+
 	try {
 		let config: ActionConfig;
 		try {
@@ -235,7 +322,7 @@ export async function launchAction(
 		const normalized = normalizeActionConfig(
 			action.action_type,
 			config,
-			context,
+			launchContext,
 		);
 		if (normalized.type === "tool" || normalized.type === "command") {
 			const cfg = normalized.config as CommandActionConfig | ToolActionConfig;
@@ -247,38 +334,51 @@ export async function launchAction(
 			case "tool":
 				result = await launchToolAction(
 					normalized.config as ToolActionConfig,
-					context,
+					launchContext,
 					action.id,
 				);
 				break;
 			case "command":
 				result = await launchCommandAction(
 					normalized.config as CommandActionConfig,
-					context,
+					launchContext,
 					action.id,
 				);
 				break;
 			case "url":
 				result = await launchURLAction(
 					normalized.config as URLActionConfig,
-					context,
+					launchContext,
+					action.id,
 				);
 				break;
 			case "delay":
 				result = await launchDelayAction(
 					normalized.config as DelayActionConfig,
-					context,
+					launchContext,
+				);
+				break;
+			case "workspace":
+				result = await launchWorkspaceRefAction(
+					normalized.config as WorkspaceActionConfig,
+					launchContext,
+					action.id,
 				);
 				break;
 			default:
 				throw new Error(`Unknown action type: ${normalized.type}`);
 		}
 
-		if (result.success && result.processId && action.track_process) {
+		if (
+			result.success &&
+			result.processId &&
+			action.track_process &&
+			result.instanceDecision !== "skipped_running"
+		) {
 			await trackRunningAction(
 				action,
 				result.processId,
-				context,
+				launchContext,
 				result.runId,
 				result.workingDirectory,
 				extractExpectedProcessName(normalized.config, normalized.type),
@@ -303,13 +403,14 @@ export async function launchWorkspace(
 	console.log(`Launching workspace with ${actions.length} actions`);
 
 	const results: LaunchResult[] = [];
+	const launchContext = withLaunchId(context);
 
 	const sortedActions = [...actions].sort(
 		(a, b) => a.order_index - b.order_index,
 	);
 
 	for (const action of sortedActions) {
-		const result = await launchAction(action, context);
+		const result = await launchAction(action, launchContext);
 		results.push(result);
 
 		if (!result.success) {
@@ -327,19 +428,16 @@ async function launchCommandAction(
 	context: LaunchContext,
 	actionId?: number,
 ): Promise<LaunchResult> {
-	const commandStr = replaceVariables(config.command, context.variables);
-	const args =
-		config.args?.map((arg) => replaceVariables(arg, context.variables)) || [];
 	const detached = config.detached === true;
 	const trackProcess = config.track_process === true;
-	const workingDir = config.working_directory
-		? replaceVariables(config.working_directory, context.variables)
-		: context.variables.TEMP ||
-			context.variables.TMP ||
-			(isWindows() ? "C:\\Windows\\Temp" : "/tmp");
+	const workingDir =
+		config.working_directory ||
+		context.variables.TEMP ||
+		context.variables.TMP ||
+		(isWindows() ? "C:\\Windows\\Temp" : "/tmp");
 
 	console.log(
-		`Executing command: ${commandStr} ${args.join(" ")} (detached: ${detached}, track: ${trackProcess})`,
+		`Executing command: ${config.command} ${(config.args ?? []).join(" ")} (detached: ${detached}, track: ${trackProcess})`,
 	);
 
 	if (trackProcess || detached) {
@@ -351,8 +449,7 @@ async function launchCommandAction(
 				workspace_id: context.workspaceId,
 				action_type: "command",
 				config: {
-					command: commandStr,
-					args,
+					...config,
 					detached,
 					working_directory: workingDir,
 					track_process: trackProcess,
@@ -360,25 +457,45 @@ async function launchCommandAction(
 						? { extra_path_directories: extraPathDirectories }
 						: {}),
 				},
-				variables: context.variables,
+				variables: {},
+				environment_id: context.environmentId ?? null,
+				launch_id: context.launchId ?? null,
 			},
 		})) as {
 			success?: boolean;
 			message?: string;
 			process_id?: number;
 			run_id?: number;
+			instance_decision?: InstanceDecision;
 		};
 		return {
 			success: Boolean(result?.success),
 			message:
 				result?.message ||
-				`Command launched${detached ? " (detached)" : ""}: ${commandStr}`,
+				`Command launched${detached ? " (detached)" : ""}: ${config.command}`,
 			processId: result?.process_id,
 			runId: result?.run_id,
-			workingDirectory: workingDir,
+			workingDirectory: trackProcess
+				? await renderWorkingDirectory(
+						"command",
+						config,
+						context,
+						actionId,
+						workingDir,
+					)
+				: workingDir,
+			instanceDecision: result?.instance_decision,
 		};
 	}
 
+	const [commandStr, ...args] = await renderLaunchStrings(
+		"command",
+		config,
+		context,
+		actionId,
+		[config.command, ...(config.args ?? [])],
+	);
+
 	try {
 		const quotedArgs = args.map((arg) => `"${arg}"`).join(" ");
 		const fullCommand = quotedArgs ? `${commandStr} ${quotedArgs}` : commandStr;
@@ -404,8 +521,11 @@ async function launchCommandAction(
 async function launchURLAction(
 	config: URLActionConfig,
 	context: LaunchContext,
+	actionId?: number,
 ): Promise<LaunchResult> {
-	const url = replaceVariables(config.url, context.variables);
+	const [url] = await renderLaunchStrings("url", config, context, actionId, [
+		config.url,
+	]);
 
 	console.log(`Opening URL: ${url}`);
 
@@ -421,6 +541,37 @@ async function launchURLAction(
 	}
 }
 
+async function launchWorkspaceRefAction(
+	config: WorkspaceActionConfig,
+	context: LaunchContext,
+	actionId?: number,
+): Promise<LaunchResult> {
+	console.log(`Launching nested workspace: ${config.workspace_id}`);
+
+	const result = (await invoke("launch_action", {
+		request: {
+			action_id: actionId ?? 0,
+			workspace_id: context.workspaceId,
+			action_type: "workspace",
+			config,
+			variables: {},
+			environment_id: context.environmentId ?? null,
+			launch_id: context.launchId ?? null,
+		},
+	})) as {
+		success?: boolean;
+		message?: string;
+		run_id?: number;
+	};
+
+	return {
+		success: Boolean(result?.success),
+		message:
+			result?.message || `Nested workspace ${config.workspace_id} launched`,
+		runId: result?.run_id,
+	};
+}
+
 async function launchToolAction(
 	config: ToolActionConfig,
 	context: LaunchContext,
@@ -548,18 +699,13 @@ async function launchCustomTool(
 		);
 	}
 
-	const args = config.args || [];
-	const processedArgs = args.map((arg: string) =>
-		replaceVariables(arg, context.variables),
-	);
-
 	const isDetached = config.detached === true;
 
-	const workingDir = config.working_directory
-		? replaceVariables(config.working_directory, context.variables)
-		: context.variables.TEMP ||
-			context.variables.TMP ||
-			(isWindows() ? "C:\\Windows\\Temp" : "/tmp");
+	const workingDir =
+		config.working_directory ||
+		context.variables.TEMP ||
+		context.variables.TMP ||
+		(isWindows() ? "C:\\Windows\\Temp" : "/tmp");
 
 	const trackProcess = config.track_process === true;
 	const useBackend = trackProcess || isDetached;
@@ -568,6 +714,7 @@ async function launchCustomTool(
 		const { invoke } = await import("@tauri-apps/api/core");
 		const extraPathDirectories = await getExtraPathDirectories();
 		const cfg: Record<string, unknown> = {
+			...config,
 			tool_type: hasCommand ? "cli" : "binary",
 			detached: isDetached,
 			working_directory: workingDir,
@@ -575,30 +722,24 @@ async function launchCustomTool(
 			...(extraPathDirectories
 				? { extra_path_directories: extraPathDirectories }
 				: {}),
+			args: config.args || [],
 		};
-		if (hasCommand) {
-			cfg.command = replaceVariables(config.command || "", context.variables);
-			cfg.args = processedArgs;
-		} else {
-			cfg.binary_path = replaceVariables(
-				config.binary_path || "",
-				context.variables,
-			);
-			cfg.args = processedArgs;
-		}
 		const result = (await invoke("launch_action", {
 			request: {
 				action_id: actionId ?? 0,
 				workspace_id: context.workspaceId,
 				action_type: "tool",
 				config: cfg,
-				variables: context.variables,
+				variables: {},
+				environment_id: context.environmentId ?? null,
+				launch_id: context.launchId ?? null,
 			},
 		})) as {
 			success?: boolean;
 			message?: string;
 			process_id?: number;
 			run_id?: number;
+			instance_decision?: InstanceDecision;
 		};
 		return {
 			success: Boolean(result?.success),
@@ -607,18 +748,33 @@ async function launchCustomTool(
 				`${config.tool_name} launched${isDetached ? " (detached)" : ""}`,
 			processId: result?.process_id,
 			runId: result?.run_id,
-			workingDirectory: workingDir,
+			workingDirectory: trackProcess
+				? await renderWorkingDirectory(
+						"tool",
+						config,
+						context,
+						actionId,
+						workingDir,
+					)
+				: workingDir,
+			instanceDecision: result?.instance_decision,
 		};
 	}
 
+	const [processedProgram, renderedDir, ...processedArgs] =
+		await renderLaunchStrings("tool", config, context, actionId, [
+			(hasCommand ? config.command : config.binary_path) || "",
+			workingDir,
+			...(config.args || []),
+		]);
+
 	let cmd: Awaited<ReturnType<typeof Command.create>>;
 	if (hasCommand) {
-		const commandStr = config.command || "";
-		const processedCommand = replaceVariables(commandStr, context.variables);
+		const processedCommand = processedProgram;
 		console.log(
 			`Executing command: ${processedCommand} with args:`,
 			processedArgs,
-			`in directory: ${workingDir}`,
+			`in directory: ${renderedDir}`,
 			`detached: ${isDetached}`,
 		);
 
@@ -626,18 +782,17 @@ async function launchCustomTool(
 			processedCommand,
 			processedArgs,
 			isDetached,
-			workingDir,
+			renderedDir,
 		);
 	} else {
-		const binaryPath = config.binary_path || "";
-		const processedBinaryPath = replaceVariables(binaryPath, context.variables);
+		const processedBinaryPath = processedProgram;
 		console.log(
 			`Executing binary: ${processedBinaryPath} with args:`,
 			processedArgs,
-			`in directory: ${workingDir}`,
+			`in directory: ${renderedDir}`,
 		);
 
-		cmd = createBinaryCommand(processedBinaryPath, processedArgs, workingDir);
+		cmd = createBinaryCommand(processedBinaryPath, processedArgs, renderedDir);
 	}
 
 	if (hasCommand && isWindows()) {
@@ -656,7 +811,7 @@ async function launchCustomTool(
 				success: true,
 				message: `${config.tool_name} launched successfully`,
 				processId: actualPid,
-				workingDirectory: workingDir,
+				renderedDirectory: renderedDir,
 			};
 		} catch (error) {
 			console.error("Error launching command:", error);
@@ -679,7 +834,7 @@ async function launchCustomTool(
 			success: true,
 			message: `${config.tool_name} launched successfully`,
 			processId: resolvedPid,
-			workingDirectory: workingDir,
+			renderedDirectory: renderedDir,
 		};
 	} else if (isWindows()) {
 		const output = await cmd.execute();
@@ -690,7 +845,7 @@ async function launchCustomTool(
 			success: true,
 			message: `${config.tool_name} launched successfully`,
 			processId: actualPid,
-			workingDirectory: workingDir,
+			renderedDirectory: renderedDir,
 		};
 	} else {
 		const output = await cmd.execute();
@@ -701,7 +856,7 @@ async function launchCustomTool(
 			success: true,
 			message: `${config.tool_name} launched successfully`,
 			processId: actualPid,
-			workingDirectory: workingDir,
+			renderedDirectory: renderedDir,
 		};
 	}
 }
@@ -724,6 +879,7 @@ async function launchSavedTool(
 			workspace_id: context.workspaceId,
 			action_type: "tool",
 			config: {
+				...config,
 				tool_id: config.tool_id,
 				placeholder_values: config.placeholder_values,
 				...(config.detached === true ? { detached: true } : {}),
@@ -732,7 +888,9 @@ async function launchSavedTool(
 					? { extra_path_directories: extraPathDirectories }
 					: {}),
 			},
-			variables: context.variables,
+			variables: {},
+			environment_id: context.environmentId ?? null,
+			launch_id: context.launchId ?? null,
 		},
 	});
 
@@ -752,12 +910,17 @@ async function launchSavedTool(
 		result && typeof result === "object" && "run_id" in result
 			? (result as { run_id?: number }).run_id
 			: undefined;
+	const instanceDecision =
+		result && typeof result === "object" && "instance_decision" in result
+			? (result as { instance_decision?: InstanceDecision }).instance_decision
+			: undefined;
 
 	return {
 		success,
 		message,
 		processId,
 		runId,
+		instanceDecision,
 	};
 }
 
@@ -0,0 +1,25 @@
+const storageKey = (workspaceId: number) =>
+	`workspace-${workspaceId}-environment`;
+
+/** The environment last selected for a workspace, used by every launch of it. */
+export function getSelectedEnvironment(workspaceId: number): number | null {
+	try {
+		const stored = Number(localStorage.getItem(storageKey(workspaceId)));
+		return Number.isInteger(stored) && stored > 0 ? stored : null;
+	} catch {
+		return null;
+	}
+}
+
+export function setSelectedEnvironment(
+	workspaceId: number,
+	environmentId: number | null,
+): void {
+	try {
+		if (environmentId === null) {
+			localStorage.removeItem(storageKey(workspaceId));
+		} else {
+			localStorage.setItem(storageKey(workspaceId), String(environmentId));
+		}
+	} catch {}
+}
@@ -1,4 +1,8 @@
 import { boolean, number, object, optional, parse, string } from "valibot";
+import type {
+	VariableValueSource,
+	VariableValueType,
+} from "@/types/database";
 import { Id, IsoDate, type ModelAdapter } from "./common";
 
 export const VariableSchema = object({
@@ -18,6 +22,10 @@ export type Variable = {
 	value: string;
 	is_secure: boolean;
 	enabled?: boolean;
+	value_source?: VariableValueSource;
+	cache_ttl_secs?: number | null;
+	value_type?: VariableValueType;
+	type_options?: string | null;
 	created_at?: string;
 	updated_at?: string;
 };
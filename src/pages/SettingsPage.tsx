@@ -4,6 +4,7 @@ import { createEffect, createSignal } from "solid-js";
 import { AppearanceSettings } from "@/components/settings/AppearanceSettings";
 import { GeneralSettings } from "@/components/settings/GeneralSettings";
 import { GlobalVariablesSettings } from "@/components/settings/GlobalVariablesSettings";
+import { SecureStoreSettings } from "@/components/settings/SecureStoreSettings";
 import { ToolsSettings } from "@/components/settings/ToolsSettings";
 import {
 	Tabs,
@@ -64,6 +65,7 @@ export const SettingsPage: Component = () => {
 
 				<TabsContent value="variables" class="space-y-4">
 					<GlobalVariablesSettings />
+					<SecureStoreSettings />
 				</TabsContent>
 			</Tabs>
 		</div>
@@ -10,6 +10,7 @@ import { ActionAIPromptDialog } from "@/components/ActionAIPromptDialog";
 import { ActionCard } from "@/components/ActionCard";
 import { ActionHistoryView } from "@/components/ActionHistoryView";
 import { ActionDialogStepper as ActionDialog } from "@/components/action/ActionDialogStepper";
+import { EnvironmentSelector } from "@/components/EnvironmentSelector";
 import { RunningActionsPanel } from "@/components/RunningActionsPanel";
 import { Badge } from "@/components/ui/badge";
 import { Button } from "@/components/ui/button";
@@ -46,6 +47,10 @@ import {
 	buildWorkspaceScript,
 	formatScriptFilename,
 } from "@/libs/scriptBuilder";
+import {
+	getSelectedEnvironment,
+	setSelectedEnvironment,
+} from "@/libs/selectedEnvironment";
 import { showToast } from "@/libs/toast";
 import { setAppWindowTitle } from "@/libs/windowTitle";
 import {
@@ -96,6 +101,9 @@ export default function WorkspaceDetailPage() {
 		new Set(),
 	);
 	const [activeTab, setActiveTab] = createSignal(getStoredTab(workspaceId()));
+	const [environmentId, setEnvironmentId] = createSignal(
+		getSelectedEnvironment(workspaceId()),
+	);
 	const [actionsQuery, setActionsQuery] = createSignal("");
 	let filterActionsRef: HTMLInputElement | undefined;
 	let filterVarsRef: HTMLInputElement | undefined;
@@ -111,6 +119,11 @@ export default function WorkspaceDetailPage() {
 	const isActionRunning = (actionId: number) =>
 		runningActionIds().has(actionId);
 
+	const handleEnvironmentChange = (id: number | null) => {
+		setEnvironmentId(id);
+		setSelectedEnvironment(workspaceId(), id);
+	};
+
 	const handleTabChange = (value: string) => {
 		setActiveTab(value);
 		setStoredTab(workspaceId(), value);
@@ -171,6 +184,7 @@ export default function WorkspaceDetailPage() {
 		}
 
 		setActiveTab(getStoredTab(id));
+		setEnvironmentId(getSelectedEnvironment(id));
 	});
 
 	createEffect(() => {
@@ -313,7 +327,11 @@ export default function WorkspaceDetailPage() {
 				variableStore.variables,
 				globalVariableStore.variables,
 			);
-			const context = { workspaceId: workspace.id, variables };
+			const context = {
+				workspaceId: workspace.id,
+				variables,
+				environmentId: environmentId(),
+			};
 			const results = await launchWorkspaceTS(actionsToLaunch, context);
 
 			const successCount = results.filter((r) => r.success).length;
@@ -427,7 +445,11 @@ export default function WorkspaceDetailPage() {
 				variableStore.variables,
 				globalVariableStore.variables,
 			);
-			const context = { workspaceId: workspace.id, variables };
+			const context = {
+				workspaceId: workspace.id,
+				variables,
+				environmentId: environmentId(),
+			};
 			const result = await launchActionTS(action, context);
 
 			if (result.success) {
@@ -467,6 +489,11 @@ export default function WorkspaceDetailPage() {
 					<>
 						<div class="w-full flex flex-col gap-1">
 							<div class="flex items-center justify-end gap-1 px-4 pt-2 pb-1">
+								<EnvironmentSelector
+									workspaceId={workspace().id}
+									value={environmentId()}
+									onChange={handleEnvironmentChange}
+								/>
 								<Button
 									variant="outline"
 									size="icon"
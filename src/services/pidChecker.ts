@@ -1,6 +1,9 @@
 import { reconcileRunningActions } from "./processTracking";
 
-const CHECK_INTERVAL = 3000;
+// Exits of tracked processes are reported by the backend as they happen;
+// this only re-resolves servers that replaced their process and catches
+// anything the backend could not watch.
+const CHECK_INTERVAL = 30000;
 
 let intervalId: number | null = null;
 
@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { cleanupOldRuns, createRun } from "@/libs/api";
-import type { NewRun, RunningAction } from "@/types/database";
+import type { ActionReattachedEvent } from "@/libs/api/types";
+import type { NewRun, RunningAction, RunStatus } from "@/types/database";
 import { runningActionsService } from "./runningActions";
 
 const MAX_RESOLUTION_RETRIES = 2;
@@ -44,14 +45,44 @@ interface ProcessIdentity {
 	name: string;
 }
 
-async function registerTrackedPid(pid: number): Promise<void> {
+async function registerTrackedPid(
+	action: RunningAction,
+	pid = action.process_id,
+): Promise<void> {
 	try {
-		await invoke("register_tracked_pid_command", { pid });
+		await invoke("register_tracked_pid_command", {
+			pid,
+			runId: action.run_id ?? null,
+			actionId: action.action_id,
+			workspaceId: action.workspace_id,
+		});
 	} catch (error) {
 		console.error(`Failed to register tracked PID ${pid}:`, error);
 	}
 }
 
+/** Polled state of a tracked process. */
+interface ProcessRunState {
+	running: boolean;
+	/** Frozen by `suspend_action` or otherwise stopped. */
+	suspended: boolean;
+}
+
+async function areProcessesRunning(
+	pids: number[],
+): Promise<Record<number, ProcessRunState>> {
+	if (pids.length === 0) return {};
+	try {
+		return await invoke<Record<number, ProcessRunState>>(
+			"are_processes_running",
+			{ pids },
+		);
+	} catch (error) {
+		console.error("Failed to check running processes:", error);
+		return {};
+	}
+}
+
 async function verifyTrackedProcess(action: RunningAction): Promise<boolean> {
 	try {
 		return await invoke<boolean>("verify_tracked_process", {
@@ -108,9 +139,11 @@ async function createCompletedRun(
 	startedAt: string,
 	exitCode: number | null,
 	errorMessage: string | null,
-	status: "success" | "failed" | "cancelled" = "success",
+	status: RunStatus = "success",
+	environmentId: number | null = null,
+	completedAt: string = new Date().toISOString(),
 ): Promise<void> {
-	let runStatus: "success" | "failed" | "cancelled" = status;
+	let runStatus: RunStatus = status;
 	if (exitCode !== null && status === "success") {
 		runStatus = exitCode === 0 ? "success" : "failed";
 	} else if (errorMessage && status === "success") {
@@ -122,9 +155,10 @@ async function createCompletedRun(
 		action_id: actionId,
 		status: runStatus,
 		started_at: startedAt,
-		completed_at: new Date().toISOString(),
+		completed_at: completedAt,
 		exit_code: exitCode ?? undefined,
 		error_message: errorMessage ?? undefined,
+		environment_id: environmentId,
 	};
 
 	try {
@@ -141,8 +175,9 @@ export async function pruneRunningAction(
 	id: string,
 	options?: {
 		recordRun?: boolean;
-		runStatus?: "success" | "failed" | "cancelled";
+		runStatus?: RunStatus;
 		errorMessage?: string | null;
+		completedAt?: string;
 	},
 ): Promise<void> {
 	const action = runningActionsService.getById(id);
@@ -156,6 +191,8 @@ export async function pruneRunningAction(
 			null,
 			options?.errorMessage ?? null,
 			options?.runStatus ?? "success",
+			action.environment_id ?? null,
+			options?.completedAt,
 		);
 	}
 
@@ -176,26 +213,26 @@ async function adoptProcessIdentity(
 	now: string,
 ): Promise<boolean> {
 	const identity = await getProcessIdentity(pid);
-	if (!identity) return false;
+	const action = runningActionsService.getById(actionId);
+	if (!identity || !action) return false;
 
 	runningActionsService.update(actionId, {
 		process_id: pid,
 		process_start_time_secs: identity.start_time_secs,
-		expected_process_name:
-			runningActionsService.getById(actionId)?.expected_process_name ??
-			identity.name,
+		expected_process_name: action.expected_process_name ?? identity.name,
 		resolution_retries: 0,
 		status: "running",
 		last_verified_at: now,
 		stop_error: undefined,
 	});
-	await registerTrackedPid(pid);
+	await registerTrackedPid(action, pid);
 	return true;
 }
 
 async function reconcileColdAction(
 	action: RunningAction,
 	now: string,
+	states: Record<number, ProcessRunState>,
 ): Promise<void> {
 	if (action.process_start_time_secs == null) {
 		await pruneRunningAction(action.id, {
@@ -208,9 +245,10 @@ async function reconcileColdAction(
 	const alive = await verifyTrackedProcess(action);
 
 	if (alive) {
-		await registerTrackedPid(action.process_id);
+		await registerTrackedPid(action);
 		runningActionsService.update(action.id, {
 			status: "running",
+			suspended: states[action.process_id]?.suspended ?? false,
 			last_verified_at: now,
 			stop_error: undefined,
 		});
@@ -226,13 +264,19 @@ async function reconcileColdAction(
 async function reconcileRuntimeAction(
 	action: RunningAction,
 	now: string,
+	states: Record<number, ProcessRunState>,
+	completedAt?: string,
 ): Promise<void> {
-	const alive = await verifyTrackedProcess(action);
+	// Only PIDs that are still running need their identity verified.
+	const state = states[action.process_id];
+	const alive =
+		state?.running !== false && (await verifyTrackedProcess(action));
 
 	if (alive) {
-		await registerTrackedPid(action.process_id);
+		await registerTrackedPid(action);
 		runningActionsService.update(action.id, {
 			status: "running",
+			suspended: state?.suspended ?? action.suspended,
 			last_verified_at: now,
 			stop_error: undefined,
 		});
@@ -268,7 +312,62 @@ async function reconcileRuntimeAction(
 		return;
 	}
 
-	await pruneRunningAction(action.id, { runStatus: "success" });
+	// The process was not our child, so its exit status is unknown.
+	await pruneRunningAction(action.id, { runStatus: "completed", completedAt });
+}
+
+/**
+ * Handles the backend reporting that a tracked process exited: the action
+ * is re-resolved if its server replaced the process, otherwise recorded as
+ * completed. Exits of actions that were already stopped are ignored.
+ */
+export async function handleTrackedProcessExit(
+	processId: number,
+	completedAt?: string,
+): Promise<void> {
+	const action = runningActionsService
+		.getAll()
+		.find((candidate) => candidate.process_id === processId);
+	if (!action) return;
+
+	await reconcileRuntimeAction(
+		action,
+		new Date().toISOString(),
+		{ [processId]: { running: false, suspended: false } },
+		completedAt,
+	);
+}
+
+/**
+ * Restores a running action for a process the backend re-attached after a
+ * restart, unless it is still tracked in localStorage.
+ */
+export function reattachRunningAction(event: ActionReattachedEvent): void {
+	const known = runningActionsService
+		.getAll()
+		.some(
+			(action) =>
+				action.process_id === event.process_id &&
+				action.process_start_time_secs === event.process_start_time_secs,
+		);
+	if (known) return;
+
+	const now = new Date().toISOString();
+	runningActionsService.add({
+		id: `${event.action_id}-${Date.now()}`,
+		workspace_id: event.workspace_id,
+		action_id: event.action_id,
+		action_name: event.action_name,
+		process_id: event.process_id,
+		run_id: event.run_id ?? undefined,
+		environment_id: event.environment_id ?? null,
+		started_at: new Date(event.process_start_time_secs * 1000).toISOString(),
+		launched_at_secs: event.process_start_time_secs,
+		status: "running",
+		last_verified_at: now,
+		expected_process_name: event.process_name,
+		process_start_time_secs: event.process_start_time_secs,
+	});
 }
 
 export async function reconcileRunningActions(
@@ -277,12 +376,15 @@ export async function reconcileRunningActions(
 	const runningActions = runningActionsService.getAll();
 	const now = new Date().toISOString();
 	const cold = options?.cold === true;
+	const states = await areProcessesRunning(
+		runningActions.map((action) => action.process_id),
+	);
 
 	for (const action of runningActions) {
 		if (cold) {
-			await reconcileColdAction(action, now);
+			await reconcileColdAction(action, now, states);
 		} else {
-			await reconcileRuntimeAction(action, now);
+			await reconcileRuntimeAction(action, now, states);
 		}
 	}
 }
@@ -295,6 +397,7 @@ export async function stopRunningAction(
 			success: boolean;
 			message: string;
 			denied?: boolean;
+			identity_mismatch?: boolean;
 		}>("kill_process", { pid: action.process_id });
 
 		if (result.success) {
@@ -307,7 +410,9 @@ export async function stopRunningAction(
 			};
 		}
 
-		const stillAlive = await verifyTrackedProcess(action);
+		// The PID now belongs to another process, so ours already exited.
+		const stillAlive =
+			!result.identity_mismatch && (await verifyTrackedProcess(action));
 		if (!stillAlive) {
 			await pruneRunningAction(action.id, { runStatus: "cancelled" });
 			return {
@@ -1,6 +1,6 @@
 import { createContext, type JSX, useContext } from "solid-js";
 import { createStore } from "solid-js/store";
-import { listRunsByWorkspace } from "@/libs/api";
+import { listRunsByWorkspace, resumeAction, suspendAction } from "@/libs/api";
 import { showToast } from "@/libs/toast";
 import {
 	dismissRunningAction,
@@ -22,6 +22,7 @@ interface RunStoreActions {
 	loadRuns: (workspaceId: number) => Promise<void>;
 	loadRunningActions: (workspaceId?: number) => void;
 	stopAction: (action: RunningAction) => Promise<void>;
+	toggleSuspend: (action: RunningAction) => Promise<void>;
 	clearRuns: () => void;
 	refreshRunningActions: () => void;
 	reconcileAndRefresh: (workspaceId?: number) => Promise<void>;
@@ -97,6 +98,33 @@ export function RunStoreProvider(props: { readonly children: JSX.Element }) {
 			});
 		},
 
+		async toggleSuspend(action: RunningAction) {
+			const result = action.suspended
+				? await resumeAction(action.action_id)
+				: await suspendAction(action.action_id);
+
+			if (result.isErr()) {
+				showToast({
+					title: action.suspended
+						? "Failed to resume action"
+						: "Failed to suspend action",
+					description: result.error,
+					variant: "destructive",
+				});
+				return;
+			}
+
+			runningActionsService.update(action.id, {
+				suspended: result.value.suspended,
+			});
+			actions.loadRunningActions(action.workspace_id);
+			showToast({
+				title: result.value.suspended ? "Action suspended" : "Action resumed",
+				description: `${action.action_name}: ${result.value.message}`,
+				variant: "success",
+			});
+		},
+
 		async dismissAction(action: RunningAction) {
 			await dismissRunningAction(action.id);
 			actions.loadRunningActions(action.workspace_id);
@@ -46,8 +46,39 @@ export interface NewAction {
 
 export interface ActionConfigBase {
 	type: string;
+	/** Fail the launch when a `${NAME}` reference cannot be resolved. */
+	strict_variables?: boolean;
+	/** Action-level overrides; take precedence over workspace and globals. */
+	variables?: Record<string, string>;
 }
 
+export type VariableSource =
+	| "action"
+	| "environment"
+	| "workspace"
+	| "request"
+	| "global"
+	| "env";
+
+export interface BuiltinVariable {
+	/** e.g. `RUN_ID`, `DATE:%Y-%m-%d` or `GIT_BRANCH:<path>`. */
+	name: string;
+	description: string;
+}
+
+export interface VariableResolution {
+	values: Record<string, string>;
+	provenance: Record<string, { source: VariableSource; secure: boolean }>;
+}
+
+export interface SecureStoreStatus {
+	passphrase: boolean;
+	locked: boolean;
+}
+
+/** Prefix of secure variable values encrypted at rest by the backend. */
+export const ENCRYPTED_VALUE_PREFIX = "enc:v1:";
+
 export interface VSCodeActionConfig extends ActionConfigBase {
 	type: "vscode";
 	workspace_path: string;
@@ -60,7 +91,55 @@ export interface EclipseActionConfig extends ActionConfigBase {
 	binary_path?: string;
 }
 
-export interface CommandActionConfig extends ActionConfigBase {
+export type InstancePolicy =
+	| "allow_multiple"
+	| "skip_if_running"
+	| "restart_if_running";
+
+export type InstanceDecision = "started" | "skipped_running" | "restarted";
+
+/** Options handled by the backend for actions that spawn a process. */
+export interface ProcessActionOptions {
+	instance_policy?: InstancePolicy;
+	/** Ports the action binds; launch fails fast if one is already taken. */
+	ports?: number[];
+	/** Stop the port owner first if it was started by WorkspaceLauncher. */
+	kill_port_owner?: boolean;
+	resource_limits?: ResourceLimits;
+	/** Shell command run before signalling, e.g. `docker compose down`. */
+	stop_command?: string;
+	stop_signal?: StopSignal;
+	/** Time to wait after stopping before the process is killed (ms). */
+	stop_grace_period_ms?: number;
+	/** Picks the process to track instead of the built-in heuristics. */
+	pid_matcher?: PidMatcher;
+}
+
+/** Every criterion that is set must hold for a process to be tracked. */
+export interface PidMatcher {
+	/** Case-insensitive regex matched against the process name. */
+	name_regex?: string;
+	cmdline_contains?: string;
+	cwd_prefix?: string;
+	/** Full executable path, or its trailing components (`bin/java`). */
+	exe_path?: string;
+	/** How long to wait for a match after launch (default 10000). */
+	max_wait_ms?: number;
+}
+
+export type StopSignal = "TERM" | "INT" | "HUP" | "QUIT";
+
+export interface ResourceLimits {
+	/** -20 (highest) to 19 (lowest); mapped to a priority class on Windows. */
+	nice?: number;
+	address_space_mb?: number;
+	open_files?: number;
+	cpu_seconds?: number;
+}
+
+export interface CommandActionConfig
+	extends ActionConfigBase,
+		ProcessActionOptions {
 	type: "command";
 	command: string;
 	args?: string[];
@@ -81,13 +160,44 @@ export interface DelayActionConfig extends ActionConfigBase {
 	duration_ms: number;
 }
 
+export interface WorkspaceActionConfig extends ActionConfigBase {
+	type: "workspace";
+	workspace_id: number;
+	wait_for_started?: boolean;
+	timeout_ms?: number;
+	/** Name of the nested workspace's environment to launch it with. */
+	environment?: string;
+}
+
 export type ActionConfig =
 	| VSCodeActionConfig
 	| EclipseActionConfig
 	| CommandActionConfig
 	| URLActionConfig
 	| DelayActionConfig
-	| ToolActionConfig;
+	| ToolActionConfig
+	| WorkspaceActionConfig;
+
+export type VariableValueSource = "static" | "command";
+
+export type VariableValueType =
+	| "string"
+	| "path"
+	| "directory"
+	| "file"
+	| "url"
+	| "integer"
+	| "port"
+	| "enum"
+	| "pattern";
+
+/** A typed variable whose value does not match its type. */
+export interface VariableViolation {
+	key: string;
+	source: VariableSource;
+	value_type: VariableValueType;
+	reason: string;
+}
 
 export interface Variable {
 	id: number;
@@ -96,6 +206,13 @@ export interface Variable {
 	value: string;
 	is_secure: boolean;
 	enabled: boolean;
+	/** `command`: `value` is a command whose trimmed output is used. */
+	value_source: VariableValueSource;
+	/** Reuse a command's output for this many seconds across launches. */
+	cache_ttl_secs: number | null;
+	value_type: VariableValueType;
+	/** JSON options: `{"values": [...]}` for enum, `{"pattern": "..."}`. */
+	type_options: string | null;
 	created_at: string;
 	updated_at: string;
 }
@@ -106,6 +223,10 @@ export interface NewVariable {
 	value: string;
 	is_secure: boolean;
 	enabled?: boolean;
+	value_source?: VariableValueSource;
+	cache_ttl_secs?: number | null;
+	value_type?: VariableValueType;
+	type_options?: string | null;
 }
 
 export interface GlobalVariable {
@@ -114,6 +235,13 @@ export interface GlobalVariable {
 	value: string;
 	is_secure: boolean;
 	enabled: boolean;
+	/** `command`: `value` is a command whose trimmed output is used. */
+	value_source: VariableValueSource;
+	/** Reuse a command's output for this many seconds across launches. */
+	cache_ttl_secs: number | null;
+	value_type: VariableValueType;
+	/** JSON options: `{"values": [...]}` for enum, `{"pattern": "..."}`. */
+	type_options: string | null;
 	created_at: string;
 	updated_at: string;
 }
@@ -123,28 +251,81 @@ export interface NewGlobalVariable {
 	value: string;
 	is_secure: boolean;
 	enabled?: boolean;
+	value_source?: VariableValueSource;
+	cache_ttl_secs?: number | null;
+	value_type?: VariableValueType;
+	type_options?: string | null;
 }
 
+/** Named variable profile of a workspace (dev, staging, ...). */
+export interface Environment {
+	id: number;
+	workspace_id: number;
+	name: string;
+	description: string | null;
+	created_at: string;
+	updated_at: string;
+}
+
+export interface NewEnvironment {
+	workspace_id: number;
+	name: string;
+	description?: string | null;
+}
+
+export interface EnvironmentVariable {
+	id: number;
+	environment_id: number;
+	key: string;
+	value: string;
+	is_secure: boolean;
+	enabled: boolean;
+	value_source: VariableValueSource;
+	cache_ttl_secs: number | null;
+	value_type: VariableValueType;
+	/** JSON options: `{"values": [...]}` for enum, `{"pattern": "..."}`. */
+	type_options: string | null;
+	created_at: string;
+	updated_at: string;
+}
+
+export interface NewEnvironmentVariable {
+	environment_id: number;
+	key: string;
+	value: string;
+	is_secure: boolean;
+	enabled?: boolean;
+	value_source?: VariableValueSource;
+	cache_ttl_secs?: number | null;
+	value_type?: VariableValueType;
+	type_options?: string | null;
+}
+
+/** `completed`: the process exited but its exit status is unknown. */
+export type RunStatus = "success" | "failed" | "cancelled" | "completed";
+
 export interface Run {
 	id: number;
 	workspace_id: number;
 	action_id: number;
-	status: "success" | "failed" | "cancelled";
+	status: RunStatus;
 	started_at: string;
 	completed_at: string | null;
 	exit_code: number | null;
 	error_message: string | null;
 	created_at: string;
+	environment_id: number | null;
 }
 
 export interface NewRun {
 	workspace_id: number;
 	action_id: number;
-	status: "success" | "failed" | "cancelled";
+	status: RunStatus;
 	started_at: string;
 	completed_at?: string;
 	exit_code?: number;
 	error_message?: string;
+	environment_id?: number | null;
 }
 
 export type RunningActionStatus = "running" | "exited" | "unreachable";
@@ -156,6 +337,7 @@ export interface RunningAction {
 	action_name: string;
 	process_id: number;
 	run_id?: number;
+	environment_id?: number | null;
 	started_at: string;
 	working_directory?: string;
 	launched_at_secs?: number;
@@ -166,6 +348,8 @@ export interface RunningAction {
 	expected_process_name?: string;
 	process_start_time_secs?: number;
 	app_boot_id?: string;
+	/** Paused with `suspend_action`; the process is still alive. */
+	suspended?: boolean;
 }
 
 export interface Log {
@@ -232,7 +416,9 @@ export interface PlaceholderDefinition {
 	default?: string;
 }
 
-export interface SavedToolActionConfig extends ActionConfigBase {
+export interface SavedToolActionConfig
+	extends ActionConfigBase,
+		ProcessActionOptions {
 	type: "tool";
 	source: "saved";
 	tool_id: number;
@@ -244,7 +430,9 @@ export interface SavedToolActionConfig extends ActionConfigBase {
 	track_process?: boolean;
 }
 
-export interface CustomToolActionConfig extends ActionConfigBase {
+export interface CustomToolActionConfig
+	extends ActionConfigBase,
+		ProcessActionOptions {
 	type: "tool";
 	source: "custom";
 	tool_name: string;
@@ -272,6 +460,7 @@ export const SETTING_KEYS = {
 	DEFAULT_SHELL_LINUX: "default_shell_linux",
 	LOG_RETENTION_DAYS: "log_retention_days",
 	EXTRA_PATH_DIRECTORIES: "extra_path_directories",
+	SWEEP_ORPHANS_ON_STARTUP: "sweep_orphans_on_startup",
 } as const;
 
 export type SettingKey = (typeof SETTING_KEYS)[keyof typeof SETTING_KEYS];